@@ -0,0 +1,164 @@
+//Benchmarks for the solver, run with `cargo bench`
+//The solver lives in the binary so its modules are included directly
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{load_image, load_input_sized};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, SeedableRng};
+
+//(input image, tile size), the first has only a few tiles and the second
+//has a lot more
+const INPUTS: [(&str, isize); 2] = [("inputimage4.png", 3), ("inputimage1.png", 3)];
+const SEEDS: [u64; 3] = [1, 2, 3];
+const OUTPUT_SIZE: usize = 32;
+const MAX_ATTEMPTS: u32 = 64;
+//Output size of the large grid benchmark, where picking the next tile to
+//collapse matters the most
+const LARGE_OUTPUT_SIZE: usize = 128;
+//Number of tiles of the memory benchmark
+const MEMORY_TILES: usize = 500;
+
+fn bench_extract(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_image_data");
+    for (path, tile_sz) in INPUTS {
+        let data = load_image(path);
+        group.bench_with_input(BenchmarkId::from_parameter(path), &data, |b, data| {
+            b.iter(|| wfc::WFCParameters::from_image_data(data, tile_sz))
+        });
+    }
+    group.finish();
+}
+
+//Each generation retries with the following seeds if it fails so that the
+//work done for a seed is the same on every run
+fn bench_generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_grid");
+    group.sample_size(10);
+    for (path, tile_sz) in INPUTS {
+        let wfc_parameters = load_input_sized(path, tile_sz);
+        for seed in SEEDS {
+            group.bench_with_input(
+                BenchmarkId::new(path, seed),
+                &wfc_parameters,
+                |b, wfc_parameters| {
+                    b.iter(|| {
+                        wfc_parameters.generate_grid_until_success(
+                            OUTPUT_SIZE,
+                            OUTPUT_SIZE,
+                            seed,
+                            MAX_ATTEMPTS,
+                        )
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+//A single step from a fresh state, which is one collapse followed by
+//propagating it through the grid
+fn bench_propagate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("first_step");
+    for (path, tile_sz) in INPUTS {
+        let wfc_parameters = load_input_sized(path, tile_sz);
+        for seed in SEEDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut wfc_state = wfc::WFCState::new(
+                OUTPUT_SIZE,
+                OUTPUT_SIZE,
+                &wfc_parameters.wfc_tiles,
+                &wfc_parameters.wfc_frequency,
+                &mut rng,
+            );
+            group.bench_with_input(
+                BenchmarkId::new(path, seed),
+                &wfc_parameters,
+                |b, wfc_parameters| {
+                    b.iter(|| {
+                        let mut rng = StdRng::seed_from_u64(seed);
+                        wfc_state.reset(
+                            &wfc_parameters.wfc_tiles,
+                            &wfc_parameters.wfc_frequency,
+                            &mut rng,
+                        );
+                        wfc_parameters.step(OUTPUT_SIZE, OUTPUT_SIZE, &mut wfc_state, &mut rng)
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+//A single generation of a large grid with the input that has only a few
+//tiles, so most of the time goes into keeping the queue of tiles in order
+fn bench_large(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_grid");
+    group.sample_size(10);
+    let (path, tile_sz) = INPUTS[0];
+    let wfc_parameters = load_input_sized(path, tile_sz);
+    group.bench_function(BenchmarkId::from_parameter(path), |b| {
+        b.iter(|| {
+            wfc_parameters.generate_grid_until_success(
+                LARGE_OUTPUT_SIZE,
+                LARGE_OUTPUT_SIZE,
+                SEEDS[0],
+                MAX_ATTEMPTS,
+            )
+        })
+    });
+    group.finish();
+}
+
+//Creating the state of a large grid with a lot of tiles, where every cell
+//starts with every tile. Criterion only measures the time so the memory the
+//superpositions take is printed once
+fn bench_state_memory(c: &mut Criterion) {
+    let tiles: Vec<u32> = (0..MEMORY_TILES as u32).collect();
+    let frequencies = vec![1; MEMORY_TILES];
+    let new_state = || {
+        wfc::WFCState::new(
+            LARGE_OUTPUT_SIZE,
+            LARGE_OUTPUT_SIZE,
+            &tiles,
+            &frequencies,
+            &mut StdRng::seed_from_u64(SEEDS[0]),
+        )
+    };
+    let ids_bytes = LARGE_OUTPUT_SIZE
+        * LARGE_OUTPUT_SIZE
+        * (std::mem::size_of::<Vec<usize>>() + MEMORY_TILES * std::mem::size_of::<usize>());
+    eprintln!(
+        "{MEMORY_TILES} tiles at {LARGE_OUTPUT_SIZE}x{LARGE_OUTPUT_SIZE}: {} KB of bitsets, {} KB as lists of ids",
+        new_state().superposition_bytes() >> 10,
+        ids_bytes >> 10
+    );
+
+    let mut group = c.benchmark_group("new_state");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::from_parameter(MEMORY_TILES), |b| {
+        b.iter(new_state)
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_extract,
+    bench_generate,
+    bench_propagate,
+    bench_large,
+    bench_state_memory
+);
+criterion_main!(benches);
@@ -0,0 +1,138 @@
+use crate::{
+    image_data::ImageData,
+    wfc::{sample_square, tiles_match, Boundary, RuleTable, Tile, WFCParameters, SQUARE_OFFSETS},
+};
+use std::collections::HashMap;
+
+//The directions in a frame come first, then the next and the previous frame
+const NEXT_FRAME: usize = 4;
+const PREV_FRAME: usize = 5;
+
+//Offsets of the rules when the frames are h rows high, the frames are
+//stacked on top of each other (see Boundary::Frames) so the next and the
+//previous frame are a whole frame down and up
+fn offsets(h: usize) -> Result<Vec<(isize, isize)>, String> {
+    if h < 2 {
+        return Err(format!(
+            "animation frames have to be at least 2 pixels high, not {h}"
+        ));
+    }
+    let mut offsets = SQUARE_OFFSETS.to_vec();
+    offsets.push((0, h as isize));
+    offsets.push((0, -(h as isize)));
+    Ok(offsets)
+}
+
+//Rules learned from an animation, frames are treated as a third dimension
+//so that a tile also constrains what can be at the same position in the
+//previous and next frame. Time wraps around like the edges of the image,
+//so the generated animation loops
+#[derive(Clone)]
+pub struct AnimatedParameters {
+    parameters: WFCParameters,
+}
+
+impl AnimatedParameters {
+    pub fn from_frames(frames: &[ImageData], tile_sz: isize) -> Result<Self, String> {
+        let first = frames.first().ok_or("animation has no frames")?;
+        let (width, height) = (first.width(), first.height());
+        if frames
+            .iter()
+            .any(|frame| frame.width() != width || frame.height() != height)
+        {
+            return Err("all frames must have the same size".to_string());
+        }
+        if tile_sz < 1 || tile_sz as usize > width.min(height) {
+            return Err(format!(
+                "tile size {tile_sz} does not fit in the {width}x{height} frames"
+            ));
+        }
+
+        let mut tile_ids = HashMap::<Tile, usize>::new();
+        let mut tiles = Vec::<Tile>::new();
+        let mut frequency = Vec::<u32>::new();
+        //Id of the tile sampled at each position of each frame
+        let mut sampled = vec![0; width * height * frames.len()];
+        for (t, frame) in frames.iter().enumerate() {
+            for y in 0..height {
+                for x in 0..width {
+                    let tile = sample_square(frame, tile_sz, x as isize, y as isize, 0);
+                    let id = match tile_ids.get(&tile) {
+                        Some(id) => *id,
+                        None => {
+                            tile_ids.insert(tile.clone(), tiles.len());
+                            tiles.push(tile);
+                            frequency.push(0);
+                            tiles.len() - 1
+                        }
+                    };
+                    frequency[id] += 1;
+                    sampled[x + y * width + t * width * height] = id;
+                }
+            }
+        }
+
+        let offsets = offsets(height)?;
+        let mut rules = RuleTable::new(tiles.len(), &offsets)?;
+
+        //Tiles in the same frame are allowed next to each other if they overlap
+        for (id1, tile1) in tiles.iter().enumerate() {
+            for (id2, tile2) in tiles.iter().enumerate() {
+                for (direction, offset) in offsets[..NEXT_FRAME].iter().enumerate() {
+                    if tiles_match(tile1, tile2, offset.0, offset.1, tile_sz) {
+                        rules.add_rule(direction, id1, id2);
+                    }
+                }
+            }
+        }
+
+        //A tile can follow another tile in time if that happens in the input
+        for t in 0..frames.len() {
+            let next_t = (t + 1) % frames.len();
+            for i in 0..(width * height) {
+                let id1 = sampled[i + t * width * height];
+                let id2 = sampled[i + next_t * width * height];
+                rules.add_rule(NEXT_FRAME, id1, id2);
+                rules.add_rule(PREV_FRAME, id2, id1);
+            }
+        }
+
+        let tiles = tiles.iter().map(|tile| tile[0]).collect();
+        Ok(Self {
+            parameters: WFCParameters::from_parts(tiles, rules, frequency, tile_sz as usize),
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn okay(&self, direction: usize, id1: usize, id2: usize) -> bool {
+        self.parameters.wfc_rules.okay(direction, id1, id2)
+    }
+
+    //Generates `frames` frames of size w x h, the output only depends on the
+    //seed. The frames are generated as a single w x (h * frames) output with
+    //the usual solver
+    pub fn generate_seeded(
+        &self,
+        w: usize,
+        h: usize,
+        frames: usize,
+        seed: u64,
+    ) -> Result<Vec<ImageData>, String> {
+        if frames == 0 {
+            return Err("an animation needs at least one frame".to_string());
+        }
+        let parameters = WFCParameters {
+            wfc_rules: self.parameters.wfc_rules.with_offsets(&offsets(h)?)?,
+            wfc_boundary: Boundary::Frames(frames),
+            ..self.parameters.clone()
+        };
+        let ids = parameters.generate_ids_seeded(w, h * frames, seed)?;
+        Ok(ids
+            .chunks(w * h)
+            .map(|frame| {
+                let pixels: Vec<u32> = frame.iter().map(|id| parameters.wfc_tiles[*id]).collect();
+                ImageData::from_pixels(&pixels, w, h)
+            })
+            .collect())
+    }
+}
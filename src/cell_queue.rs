@@ -0,0 +1,163 @@
+use rand::Rng;
+
+//Entropies are rounded to a multiple of 1 / ENTROPY_PRECISION before they are
+//compared, log2 can differ in the last bit between platforms and this keeps
+//those differences from changing which cell is collapsed next
+const ENTROPY_PRECISION: f32 = 65536.0;
+//Position of a cell that is not in the queue
+const NOT_QUEUED: usize = usize::MAX;
+
+//NaN would be cast to 0 and put the cell at the front of the queue, it is
+//treated as infinity instead so that a bad entropy only delays its cell
+pub fn quantize_entropy(entropy: f32) -> i64 {
+    if entropy.is_nan() {
+        return i64::MAX;
+    }
+    (entropy * ENTROPY_PRECISION).round() as i64
+}
+
+//Priority queue of cells ordered by lowest entropy first, cells with the same
+//entropy are ordered by random noise that is drawn when the queue is reset.
+//Every cell is in the queue at most once and its position in the heap is
+//tracked so that its entropy can be changed in O(log n) when it shrinks
+#[derive(Clone, Default)]
+pub struct CellQueue {
+    //Binary heap of cells, the cell with the smallest key is at the front
+    heap: Vec<usize>,
+    //(quantized entropy, noise) of each cell, kept after the cell is popped
+    keys: Vec<(i64, u32)>,
+    //Index into heap of each cell, NOT_QUEUED if the cell is not queued
+    positions: Vec<usize>,
+}
+
+impl CellQueue {
+    //Empties the queue and draws new noise for each of the cells
+    pub fn reset<R: Rng>(&mut self, cells: usize, rng: &mut R) {
+        self.heap.clear();
+        self.keys.clear();
+        self.keys.extend((0..cells).map(|_| (0, rng.gen())));
+        self.positions.clear();
+        self.positions.resize(cells, NOT_QUEUED);
+    }
+
+    //Only used by the tests
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, cell: usize) -> bool {
+        self.positions[cell] != NOT_QUEUED
+    }
+
+    //Queued cells in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.heap.iter().copied()
+    }
+
+    //Quantized entropy the cell was last pushed with
+    pub fn entropy_key(&self, cell: usize) -> i64 {
+        self.keys[cell].0
+    }
+
+    pub fn peek(&self) -> Option<usize> {
+        self.heap.first().copied()
+    }
+
+    //Finds the cell peek should return by looking at every queued cell, this
+    //is how the lowest entropy cell was found before the queue kept a heap
+    pub fn lowest(&self) -> Option<usize> {
+        self.heap.iter().copied().reduce(|lowest, cell| {
+            if self.less(cell, lowest) {
+                cell
+            } else {
+                lowest
+            }
+        })
+    }
+
+    //Adds the cell to the queue or moves it if it is already queued
+    pub fn push(&mut self, cell: usize, entropy: f32) {
+        self.keys[cell].0 = quantize_entropy(entropy);
+        self.requeue(cell);
+    }
+
+    //Adds the cell back with the entropy it was last pushed with
+    pub fn requeue(&mut self, cell: usize) {
+        let pos = if self.contains(cell) {
+            self.positions[cell]
+        } else {
+            self.heap.push(cell);
+            self.heap.len() - 1
+        };
+        self.positions[cell] = pos;
+        let pos = self.sift_up(pos);
+        self.sift_down(pos);
+    }
+
+    pub fn pop(&mut self) -> Option<usize> {
+        let first = self.peek()?;
+        self.remove(first);
+        Some(first)
+    }
+
+    pub fn remove(&mut self, cell: usize) {
+        let pos = self.positions[cell];
+        if pos == NOT_QUEUED {
+            return;
+        }
+
+        self.positions[cell] = NOT_QUEUED;
+        let last = self.heap.pop().unwrap_or(cell);
+        if last != cell {
+            //Fill the hole with the last cell and move it to where it belongs
+            self.heap[pos] = last;
+            self.positions[last] = pos;
+            let pos = self.sift_up(pos);
+            self.sift_down(pos);
+        }
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        (self.keys[a], a) < (self.keys[b], b)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.positions[self.heap[i]] = i;
+        self.positions[self.heap[j]] = j;
+    }
+
+    //Returns the new position of the cell
+    fn sift_up(&mut self, mut pos: usize) -> usize {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+            if !self.less(self.heap[pos], self.heap[parent]) {
+                break;
+            }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+        pos
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let mut smallest = pos;
+            for child in [2 * pos + 1, 2 * pos + 2] {
+                if child < self.heap.len() && self.less(self.heap[child], self.heap[smallest]) {
+                    smallest = child;
+                }
+            }
+            if smallest == pos {
+                break;
+            }
+            self.swap(pos, smallest);
+            pos = smallest;
+        }
+    }
+}
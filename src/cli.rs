@@ -0,0 +1,380 @@
+use crate::{
+    config::Config,
+    wfc::{Boundary, ExtractionOptions, WeightTransform},
+};
+use clap::{
+    parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
+
+const SUBCOMMANDS: [&str; 6] = [
+    "generate",
+    "inspect",
+    "dump-rules",
+    "validate",
+    "diff",
+    "help",
+];
+const HELP_FLAGS: [&str; 4] = ["-h", "--help", "-V", "--version"];
+//Largest tile size that can be sampled from the input
+pub const MAX_TILE_SIZE: i64 = 4;
+//Largest --frequency-add, the weights of all of the tiles have to add up
+//without overflowing
+const MAX_FREQUENCY_ADD: i64 = 1_000_000;
+
+//Doc comments (///) are used by clap as the help text
+#[derive(Parser)]
+#[command(version, about = "wave function collapse demo")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Generate an image (the default if no subcommand is given)
+    //Boxed since it has far more options than the other subcommands
+    Generate(Box<GenerateArgs>),
+    /// Print information about the tiles extracted from an image
+    Inspect(InspectArgs),
+    /// Print the adjacency rules extracted from an image
+    DumpRules(InputArgs),
+    /// Check that an image only has pairs of tiles that the rules of the
+    /// input allow
+    Validate(ValidateArgs),
+    /// Compare two images of the same size (for example two outputs) and
+    /// print the pixels that are different
+    Diff(DiffArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct InputArgs {
+    //The indices are set so that the positional arguments stay in order
+    //when their defaults are replaced by the config
+    /// Input png
+    #[arg(index = 1)]
+    pub input: String,
+    /// Size of the tiles sampled from the input
+    #[arg(index = 2, default_value_t = 3, value_parser = clap::value_parser!(i64).range(1..=MAX_TILE_SIZE))]
+    pub tile_size: i64,
+    /// Json file with the options to use, options given on the command line
+    /// take precedence over it
+    #[arg(long)]
+    pub config: Option<String>,
+    /// Merge colors whose channels are within about this much of each other
+    #[arg(long, default_value_t = 0)]
+    pub tolerance: u32,
+    /// Also use the rotations of every tile in the input
+    #[arg(long)]
+    pub rotations: bool,
+    /// Treat every pixel of the input and output as a hexagon, each row is
+    /// shifted half a hexagon to the right of the row above it. Each color
+    /// is a tile so the tile size is ignored
+    #[arg(long, conflicts_with = "rotations")]
+    pub hex: bool,
+    /// Only sample the tiles that fit inside the input instead of wrapping
+    /// around its edges, for inputs whose edges don't line up
+    #[arg(long)]
+    pub finite_input: bool,
+    /// Don't wrap the output around its edges, so it doesn't have to tile
+    #[arg(long)]
+    pub finite_output: bool,
+    /// Pick tiles with a weight of frequency^ALPHA (between 0 and 1) when
+    /// collapsing, lower values make rare tiles more common
+    #[arg(long, value_parser = parse_alpha)]
+    pub frequency_alpha: Option<f32>,
+    /// Add this to the frequency of every tile when collapsing, higher values
+    /// make rare tiles more common
+    #[arg(
+        long,
+        conflicts_with = "frequency_alpha",
+        value_parser = clap::value_parser!(u32).range(..=MAX_FREQUENCY_ADD)
+    )]
+    pub frequency_add: Option<u32>,
+    /// Weight of rotated tiles in percent of the tiles in the input
+    #[arg(long, default_value_t = 100, requires = "rotations")]
+    pub rotation_weight: u32,
+}
+
+#[derive(Args, Clone)]
+pub struct InspectArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Save every tile sampled from the input side by side in this png
+    #[arg(long)]
+    pub atlas: Option<String>,
+}
+
+#[derive(Args, Clone)]
+pub struct ValidateArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Png to check, usually a generated output that was edited
+    #[arg(long)]
+    pub image: String,
+}
+
+#[derive(Args, Clone)]
+pub struct DiffArgs {
+    /// First png
+    pub a: String,
+    /// Second png
+    pub b: String,
+    /// Save a copy of the first png with the different pixels highlighted
+    #[arg(long)]
+    pub out: Option<String>,
+}
+
+fn parse_fraction(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(fraction),
+        Ok(_) => Err("must be between 0 and 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn boundary(finite: bool) -> Boundary {
+    if finite {
+        Boundary::Finite
+    } else {
+        Boundary::Wrap
+    }
+}
+
+fn parse_alpha(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Ok(alpha),
+        Ok(_) => Err("must be above 0 and at most 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+impl InputArgs {
+    pub fn n(&self) -> isize {
+        self.tile_size as isize
+    }
+
+    pub fn extraction_options(&self) -> ExtractionOptions {
+        ExtractionOptions {
+            color_tolerance: self.tolerance,
+            rotations: self.rotations,
+            rotation_weight: self.rotation_weight,
+            keep_patterns: false,
+            hex: self.hex,
+            boundary: boundary(self.finite_input),
+        }
+    }
+
+    //The smoothing of the frequencies if any was asked for
+    pub fn weight_transform(&self) -> Option<WeightTransform> {
+        match (self.frequency_alpha, self.frequency_add) {
+            (Some(alpha), _) => Some(WeightTransform::Power(alpha)),
+            (None, Some(k)) => Some(WeightTransform::AddK(k)),
+            (None, None) => None,
+        }
+    }
+
+    pub fn output_boundary(&self) -> Boundary {
+        boundary(self.finite_output)
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct GenerateArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Png whose fully transparent pixels are left blank in the output
+    #[arg(long)]
+    pub mask: Option<String>,
+    /// File that maps colors of the output to new colors, each line is a
+    /// color and its replacement written as 0xAARRGGBB
+    #[arg(long)]
+    pub palette: Option<String>,
+    /// Seed for the random number generator, random if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Where the output is saved (press S in the window to save)
+    #[arg(long)]
+    pub out: Option<String>,
+    /// Generate and save the output without opening a window
+    #[arg(long, requires = "out")]
+    pub headless: bool,
+    /// Save the state every few collapses as numbered pngs in this directory
+    /// (only when generating headless)
+    #[arg(long, requires = "headless")]
+    pub frames_dir: Option<String>,
+    /// Save a grayscale image of how uncertain each cell is part way through
+    /// the generation, brighter cells have more tiles left (only when
+    /// generating headless)
+    #[arg(long, requires = "headless")]
+    pub entropy_map: Option<String>,
+    /// Fraction of the cells (0 to 1) that are collapsed when the entropy
+    /// map is saved
+    #[arg(long, default_value_t = 0.5, value_parser = parse_fraction, requires = "entropy_map")]
+    pub entropy_at: f32,
+    /// Save the number of tiles left and the entropy of each cell as
+    /// x,y,remaining,entropy rows once the generation ends, a failed
+    /// generation saves the cells as they were when it stopped (only when
+    /// generating headless)
+    #[arg(long, requires = "headless")]
+    pub entropy_csv: Option<String>,
+    /// Also save the output repeated 2x2 times to this png, the seams show
+    /// whether its edges line up (only when generating headless)
+    #[arg(long, requires = "headless")]
+    pub tiled_preview: Option<String>,
+    /// Fill cells that run out of tiles with this tile (an id printed by
+    /// dump-rules) and keep going instead of starting over, the output always
+    /// finishes but can break the rules around the filled cells
+    #[arg(long)]
+    pub fill_contradictions: Option<usize>,
+    /// Don't show how much of the output is done while generating headless
+    #[arg(long, requires = "headless")]
+    pub quiet: bool,
+    /// Print how long extracting the rules, collapsing and propagating took
+    /// (only when generating headless, not in bands)
+    #[arg(long, requires = "headless", conflicts_with = "band_height")]
+    pub timings: bool,
+    /// Generate the output this many rows at a time and write each band to
+    /// the output as soon as it is done, for outputs that are too large to
+    /// keep in memory (only when generating headless)
+    #[arg(
+        long,
+        requires = "headless",
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["mask", "guide", "animate", "frames_dir", "entropy_map", "entropy_csv", "tiled_preview", "render_width", "render_height"]
+    )]
+    pub band_height: Option<u32>,
+    /// Generate this many seeds (starting at --seed) without opening a
+    /// window or saving anything and print how many of them ran into a
+    /// contradiction, to see how likely the rules are to fail
+    #[arg(
+        long,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with_all = ["headless", "animate"]
+    )]
+    pub stress: Option<u32>,
+    /// Png that is stretched over the output, each cell prefers tiles with
+    /// a color close to the guide (transparent pixels have no effect)
+    #[arg(long)]
+    pub guide: Option<String>,
+    /// Treat the input as an animated png and save a looping animation
+    #[arg(long, requires = "out", conflicts_with_all = ["mask", "guide"])]
+    pub animate: bool,
+    /// Width of the output
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: u32,
+    /// Height of the output
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: u32,
+    /// Width the output is scaled to when saved
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub render_width: Option<u32>,
+    /// Height the output is scaled to when saved
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub render_height: Option<u32>,
+}
+
+impl GenerateArgs {
+    pub fn out_path(&self) -> String {
+        self.out.clone().unwrap_or("output.png".to_string())
+    }
+
+    //Size the output is scaled to when saved, if only one side is given the
+    //other keeps the aspect ratio of the output
+    pub fn render_size(&self) -> Option<(usize, usize)> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        match (self.render_width, self.render_height) {
+            (None, None) => None,
+            (Some(rw), Some(rh)) => Some((rw as usize, rh as usize)),
+            (Some(rw), None) => Some((rw as usize, (rw as usize * h / w).max(1))),
+            (None, Some(rh)) => Some(((rh as usize * w / h).max(1), rh as usize)),
+        }
+    }
+}
+
+//Finds the value of --config before the arguments are parsed
+fn config_path(args: &[String]) -> Option<String> {
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        } else if arg == "--config" {
+            return args.get(i + 1).cloned();
+        }
+    }
+    None
+}
+
+//Adds the values of the config that weren't given on the command line to
+//args as if they had been, so that they are checked the same way as the
+//command line (requires, conflicts, ranges). matches is the command line
+//parsed on its own, which only has to be good enough to tell which
+//arguments it sets
+fn merge_config(
+    args: &mut Vec<String>,
+    config: &Config,
+    subcommand: &clap::Command,
+    matches: &ArgMatches,
+) {
+    let from_command_line = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    //Positional arguments go right after the subcommand or the positional
+    //argument before them
+    let mut positional_at = 2;
+    for (id, value) in config.values() {
+        let Some(arg) = subcommand.get_arguments().find(|arg| arg.get_id() == id) else {
+            continue;
+        };
+        if arg.is_positional() {
+            if from_command_line(id) {
+                //Indices count from the subcommand, which is args[1]
+                positional_at = matches.index_of(id).map_or(positional_at, |i| i + 2);
+            } else {
+                args.insert(positional_at, value);
+                positional_at += 1;
+            }
+            continue;
+        }
+
+        let (Some(long), false) = (arg.get_long(), from_command_line(id)) else {
+            continue;
+        };
+        if !arg.get_action().takes_values() {
+            //Flags that are false in the config are left unset
+            if value == "true" {
+                args.push(format!("--{long}"));
+            }
+        } else {
+            args.push(format!("--{long}={value}"));
+        }
+    }
+}
+
+//Passing just an image (and tile size) runs the generate subcommand
+pub fn parse(mut args: Vec<String>) -> Result<Cli, String> {
+    if let Some(first) = args.get(1) {
+        if !SUBCOMMANDS.contains(&first.as_str()) && !HELP_FLAGS.contains(&first.as_str()) {
+            args.insert(1, "generate".to_string());
+        }
+    }
+
+    let command = Cli::command();
+    if let Some(path) = config_path(&args) {
+        let config = Config::load(&path)?;
+        let matches = command
+            .clone()
+            .ignore_errors(true)
+            .try_get_matches_from(&args)
+            .map_err(|e| e.to_string())?;
+        if let Some((name, sub_matches)) = matches.subcommand() {
+            if let Some(subcommand) = command.find_subcommand(name) {
+                merge_config(&mut args, &config, subcommand, sub_matches);
+            }
+        }
+    }
+
+    let matches = match command.try_get_matches_from(args) {
+        Ok(matches) => matches,
+        //--help and --version print and exit like they always do
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => return Err(e.to_string()),
+    };
+    Cli::from_arg_matches(&matches).map_err(|e| e.to_string())
+}
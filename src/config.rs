@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+//Generation settings read from a json file with --config, every field is
+//optional and the flags given on the command line take precedence. The
+//field names are the same as the command line options
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub input: Option<String>,
+    pub tile_size: Option<i64>,
+    pub tolerance: Option<u32>,
+    pub rotations: Option<bool>,
+    pub rotation_weight: Option<u32>,
+    pub hex: Option<bool>,
+    pub finite_input: Option<bool>,
+    pub finite_output: Option<bool>,
+    pub frequency_alpha: Option<f32>,
+    pub frequency_add: Option<u32>,
+    pub mask: Option<String>,
+    pub palette: Option<String>,
+    pub guide: Option<String>,
+    pub seed: Option<u64>,
+    pub out: Option<String>,
+    pub headless: Option<bool>,
+    pub fill_contradictions: Option<usize>,
+    pub quiet: Option<bool>,
+    pub timings: Option<bool>,
+    pub frames_dir: Option<String>,
+    pub entropy_map: Option<String>,
+    pub entropy_at: Option<f32>,
+    pub entropy_csv: Option<String>,
+    pub tiled_preview: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub render_width: Option<u32>,
+    pub render_height: Option<u32>,
+    pub band_height: Option<u32>,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("failed to open {path}: {e}"))?;
+        serde_json::from_str(&json).map_err(|e| format!("{path}: {e}"))
+    }
+
+    //The id of each argument that is set in the config along with its value,
+    //in the order the positional arguments are in
+    pub fn values(&self) -> Vec<(&'static str, String)> {
+        fn value<T: ToString>(
+            id: &'static str,
+            value: &Option<T>,
+        ) -> Option<(&'static str, String)> {
+            value.as_ref().map(|value| (id, value.to_string()))
+        }
+
+        [
+            value("input", &self.input),
+            value("tile_size", &self.tile_size),
+            value("tolerance", &self.tolerance),
+            value("rotations", &self.rotations),
+            value("rotation_weight", &self.rotation_weight),
+            value("hex", &self.hex),
+            value("finite_input", &self.finite_input),
+            value("finite_output", &self.finite_output),
+            value("frequency_alpha", &self.frequency_alpha),
+            value("frequency_add", &self.frequency_add),
+            value("mask", &self.mask),
+            value("palette", &self.palette),
+            value("guide", &self.guide),
+            value("seed", &self.seed),
+            value("out", &self.out),
+            value("headless", &self.headless),
+            value("fill_contradictions", &self.fill_contradictions),
+            value("quiet", &self.quiet),
+            value("timings", &self.timings),
+            value("frames_dir", &self.frames_dir),
+            value("entropy_map", &self.entropy_map),
+            value("entropy_at", &self.entropy_at),
+            value("entropy_csv", &self.entropy_csv),
+            value("tiled_preview", &self.tiled_preview),
+            value("width", &self.width),
+            value("height", &self.height),
+            value("render_width", &self.render_width),
+            value("render_height", &self.render_height),
+            value("band_height", &self.band_height),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
@@ -0,0 +1,72 @@
+use crate::wfc::{self, RenderOptions, WFCParameters};
+use std::{fs, path::PathBuf};
+
+//Upper limit on the number of frames saved for one generation, larger
+//outputs save a frame less often to stay under it
+const MAX_FRAMES: usize = 10000;
+
+//Saves the state of a generation every few collapses as numbered pngs
+//(frame_00001.png, frame_00002.png, ...) so they can be made into a video
+pub struct FrameWriter {
+    dir: PathBuf,
+    //Number of collapses between frames
+    stride: usize,
+    collapses: usize,
+    count: usize,
+    //First error that happened while saving, later frames are skipped
+    error: Option<String>,
+}
+
+impl FrameWriter {
+    //Creates the directory if it doesn't exist, a frame is saved every
+    //`speed` collapses unless that would give more than MAX_FRAMES frames
+    pub fn new(dir: &str, speed: usize, w: usize, h: usize) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("failed to create {dir}: {e}"))?;
+        Ok(Self {
+            dir: PathBuf::from(dir),
+            stride: speed.max((w * h).div_ceil(MAX_FRAMES)).max(1),
+            collapses: 0,
+            count: 0,
+            error: None,
+        })
+    }
+
+    //Called after each collapse, the last state (remaining is 0) is always saved
+    pub fn step(
+        &mut self,
+        superpositions: &[wfc::Superposition],
+        remaining: usize,
+        masked: &[bool],
+        wfc_parameters: &WFCParameters,
+        (w, h): (usize, usize),
+        render_options: &RenderOptions,
+    ) {
+        self.collapses += 1;
+        if self.error.is_some() || (!self.collapses.is_multiple_of(self.stride) && remaining > 0) {
+            return;
+        }
+
+        self.count += 1;
+        let image = wfc::superpositions_to_image(
+            superpositions,
+            masked,
+            &wfc_parameters.wfc_tiles,
+            &wfc_parameters.wfc_frequency,
+            w,
+            h,
+            render_options,
+        );
+        let path = self.dir.join(format!("frame_{:05}.png", self.count));
+        if let Err(e) = image.save_png(&path.to_string_lossy()) {
+            self.error = Some(format!("failed to save {}: {e}", path.display()));
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
@@ -0,0 +1,617 @@
+use crate::{
+    check_parameters,
+    cli::{GenerateArgs, InputArgs, MAX_TILE_SIZE},
+    create_manifest,
+    image_data::{self, ImageData},
+    layout::{compute_layout, throttled, visible_pixels, Layout},
+    load_parameters,
+    palette::{self, Palette},
+    save_output, wfc, RENDER_OPTIONS, SPEED,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use sdl2::{
+    event::{Event, WindowEvent},
+    keyboard::Keycode,
+    mouse::MouseButton,
+    pixels::{Color, PixelFormatEnum},
+    rect::Rect,
+    render::{Canvas, Texture, TextureCreator},
+    video::{Window, WindowContext},
+    EventPump,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+const MAX_PIXEL_SIZE: f32 = 8.0;
+const WINDOW_TITLE: &str = "wave function collapse demo";
+//How many states are kept for backtracking
+const BACKTRACK_DEPTH: usize = 32;
+//Times the output is repeated in each direction while previewing its tiling
+const PREVIEW_REPEATS: usize = 3;
+
+//Process events
+#[derive(Default)]
+struct ProcessedEvents {
+    dropped_file: Option<String>,
+    save: bool,
+    toggle_pause: bool,
+    step: bool,
+    next_retry_strategy: bool,
+    toggle_learning: bool,
+    next_tile_size: bool,
+    toggle_rotations: bool,
+    toggle_preview: bool,
+    //Position of the last left click in the window
+    clicked: Option<(i32, i32)>,
+    //The window changed size and has to be drawn again with a new layout
+    resized: bool,
+}
+
+//Closing the window or pressing escape sets cancel, which stops the
+//generation before its next step
+fn process_events(event_pump: &mut EventPump, cancel: &AtomicBool) -> ProcessedEvents {
+    let mut processed = ProcessedEvents::default();
+
+    for event in event_pump.poll_iter() {
+        match event {
+            Event::Quit { .. }
+            | Event::KeyDown {
+                keycode: Some(Keycode::Escape),
+                ..
+            } => cancel.store(true, Ordering::Relaxed),
+            Event::DropFile { filename, .. } => processed.dropped_file = Some(filename),
+            Event::KeyDown {
+                keycode: Some(Keycode::S),
+                ..
+            } => processed.save = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::Space),
+                ..
+            } => processed.toggle_pause = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::Right),
+                ..
+            } => processed.step = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::R),
+                ..
+            } => processed.next_retry_strategy = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::L),
+                ..
+            } => processed.toggle_learning = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::T),
+                ..
+            } => processed.next_tile_size = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::O),
+                ..
+            } => processed.toggle_rotations = true,
+            Event::KeyDown {
+                keycode: Some(Keycode::P),
+                ..
+            } => processed.toggle_preview = true,
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => processed.clicked = Some((x, y)),
+            Event::Window {
+                win_event: WindowEvent::SizeChanged(..),
+                ..
+            } => processed.resized = true,
+            _ => {}
+        }
+    }
+
+    processed
+}
+
+type LoadedInput = Result<(ImageData, wfc::WFCParameters), String>;
+
+//Extracts the rules with the options of input on another thread since that
+//can take a while for larger images. The image is loaded from the path in
+//input first unless data is given
+fn load_input_in_background(input: InputArgs, data: Option<ImageData>) -> Receiver<LoadedInput> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let path = &input.input;
+        let data = match data {
+            Some(data) => Ok(data),
+            None => ImageData::load(path).map_err(|e| format!("failed to open {path}: {e}")),
+        };
+        let loaded = data.and_then(|data| {
+            let wfc_parameters = load_parameters(&data, &input)?;
+            Ok((data, wfc_parameters))
+        });
+        //The receiver is gone if the window was closed, nothing to do then
+        let _ = sender.send(loaded);
+    });
+    receiver
+}
+
+fn window_title(retry_strategy: wfc::RetryStrategy, learning: bool, input: &InputArgs) -> String {
+    let rotations = if input.rotations { ", rotations" } else { "" };
+    let title = format!(
+        "{WINDOW_TITLE} - tile size {}{rotations} - on contradiction: {retry_strategy}",
+        input.tile_size
+    );
+    if learning {
+        format!("{title} - learning from edits")
+    } else {
+        title
+    }
+}
+
+//How the window handles contradictions unless told otherwise, reports where
+//it happened and recovers with the strategy picked with the R key
+fn on_contradiction(
+    contradiction: &wfc::Contradiction,
+    retry_strategy: wfc::RetryStrategy,
+) -> wfc::ContradictionAction {
+    eprintln!(
+        "contradiction #{} at ({}, {}) with {} tiles left, recovering with {retry_strategy}",
+        contradiction.count, contradiction.x, contradiction.y, contradiction.remaining
+    );
+    wfc::ContradictionAction::Retry(retry_strategy)
+}
+
+//What is done about the contradiction a step or a click ran into, msg is the
+//error it failed with
+fn handle_contradiction(
+    wfc_parameters: &wfc::WFCParameters,
+    wfc_state: &wfc::WFCState,
+    (w, h): (usize, usize),
+    count: u32,
+    retry_strategy: wfc::RetryStrategy,
+    msg: &str,
+) -> wfc::ContradictionAction {
+    match wfc_state.contradiction(w, count) {
+        Some(contradiction) => {
+            let (x, y) = (contradiction.x, contradiction.y);
+            eprintln!(
+                "{}",
+                wfc_parameters.explain_contradiction(wfc_state, x, y, w, h)
+            );
+            on_contradiction(&contradiction, retry_strategy)
+        }
+        None => {
+            eprintln!("{msg}");
+            wfc::ContradictionAction::Retry(retry_strategy)
+        }
+    }
+}
+
+fn texture_from_image<'a>(
+    data: &ImageData,
+    texture_creator: &'a TextureCreator<WindowContext>,
+) -> Result<Texture<'a>, String> {
+    //Create the texture
+    let mut texture = texture_creator
+        .create_texture_streaming(
+            PixelFormatEnum::BGRA8888,
+            data.width() as u32,
+            data.height() as u32,
+        )
+        .map_err(|e| e.to_string())?;
+
+    texture
+        .with_lock(None, |pixels: &mut [u8], _pitch: usize| {
+            for y in 0..data.height() {
+                for x in 0..data.width() {
+                    let pixel = data.get_pixel(x, y);
+                    let col = image_data::u32_to_color(pixel);
+                    pixels[y * data.width() * 4 + x * 4 + 1] = (col.0 * 255.0) as u8;
+                    pixels[y * data.width() * 4 + x * 4 + 2] = (col.1 * 255.0) as u8;
+                    pixels[y * data.width() * 4 + x * 4 + 3] = (col.2 * 255.0) as u8;
+                    pixels[y * data.width() * 4 + x * 4] = 0xff;
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    Ok(texture)
+}
+
+fn texture_size(texture: &Texture) -> (usize, usize) {
+    (
+        texture.query().width as usize,
+        texture.query().height as usize,
+    )
+}
+
+fn window_layout(
+    canvas: &Canvas<Window>,
+    input_texture: &Texture,
+    output_texture: &Texture,
+) -> Result<Layout, String> {
+    Ok(compute_layout(
+        texture_size(input_texture),
+        texture_size(output_texture),
+        canvas.output_size()?,
+        MAX_PIXEL_SIZE,
+    ))
+}
+
+//Draws the part of the texture placed at pos that is inside the window,
+//nothing is drawn if all of it is outside
+fn copy_visible(
+    canvas: &mut Canvas<Window>,
+    texture: &Texture,
+    pos: (f32, f32),
+    pixel_size: f32,
+) -> Result<(), String> {
+    let Some((x, y, w, h)) = visible_pixels(
+        pos,
+        pixel_size,
+        texture_size(texture),
+        canvas.output_size()?,
+    ) else {
+        return Ok(());
+    };
+
+    canvas.copy(
+        texture,
+        Rect::new(x as i32, y as i32, w as u32, h as u32),
+        Rect::new(
+            (pos.0 + x as f32 * pixel_size) as i32,
+            (pos.1 + y as f32 * pixel_size) as i32,
+            (w as f32 * pixel_size) as u32,
+            (h as f32 * pixel_size) as u32,
+        ),
+    )
+}
+
+fn display_loop(
+    canvas: &mut Canvas<Window>,
+    input_texture: &Texture,
+    output_texture: &Texture,
+) -> Result<(), String> {
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    canvas.clear();
+
+    let layout = window_layout(canvas, input_texture, output_texture)?;
+    copy_visible(canvas, input_texture, layout.input_pos, layout.pixel_size)?;
+    copy_visible(canvas, output_texture, layout.output_pos, layout.pixel_size)?;
+
+    canvas.present();
+
+    Ok(())
+}
+
+pub fn main_loop(
+    data: &ImageData,
+    mut wfc_parameters: wfc::WFCParameters,
+    args: &GenerateArgs,
+    mask: Option<&ImageData>,
+    palette: &Palette,
+) -> Result<(), String> {
+    //Init sdl
+    let ctx = sdl2::init()?;
+    let video_subsystem = ctx.video()?;
+    let mut retry_strategy = wfc::RetryStrategy::default();
+    //When set, clicking a cell can place any tile and the pairs it makes
+    //with its neighbors are added to the rules
+    let mut learning = false;
+    //States before the last few steps, only kept when backtracking
+    let mut history: Vec<wfc::WFCState> = vec![];
+    let window = video_subsystem
+        .window(
+            &window_title(retry_strategy, learning, &args.input),
+            800,
+            640,
+        )
+        .position_centered()
+        .resizable()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut canvas = window
+        .into_canvas()
+        .present_vsync()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let texture_creator = canvas.texture_creator();
+    let mut event_pump = ctx.event_pump()?;
+
+    let mut events = ProcessedEvents::default();
+    //The input that is being loaded and the options it is loaded with
+    let mut loading: Option<(Receiver<LoadedInput>, InputArgs)> = None;
+
+    let mut input_texture = texture_from_image(data, &texture_creator)?;
+    let w = args.width as usize;
+    let h = args.height as usize;
+    let mut output_image = ImageData::new(w, h);
+    let mut output_texture = texture_from_image(&output_image, &texture_creator)?;
+    let mut current_frame = 0;
+    let mut paused = false;
+    //Shows the output repeated in a grid so the seams can be checked
+    let mut preview = false;
+    //Set after a contradiction to what is done about it, which happens on
+    //the next step
+    let mut recovery: Option<wfc::ContradictionAction> = None;
+    let mut contradictions = 0;
+    //Set when the state has changed since the output was last drawn
+    let mut changed = true;
+    //The cell that was last collapsed by clicking on it, the state before
+    //the click and which of its tiles was picked. Clicking the same cell
+    //again goes back to that state and picks the next tile
+    let mut selected: Option<(usize, wfc::WFCState, usize)> = None;
+    let masked = match mask {
+        Some(mask) => mask.transparent_mask(w, h),
+        None => vec![false; w * h],
+    };
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    //The input and the options its rules were extracted with, changed by
+    //dropping a file or with the T and O keys
+    let mut input_args = args.input.clone();
+    let mut input_data = data.clone();
+    let mut wfc_state = wfc_parameters.start_state(w, h, &masked, &mut rng);
+    let cancel = AtomicBool::new(false);
+    while !cancel.load(Ordering::Relaxed) {
+        let running = !paused && !wfc_state.done();
+        let throttled = throttled(running, current_frame, SPEED);
+
+        if recovery.is_some() && (running || events.step) {
+            //Recover from the contradiction, this is done a step after it so
+            //that it can be seen in the output
+            match recovery.take() {
+                Some(wfc::ContradictionAction::Retry(strategy)) => {
+                    let allowed = wfc_parameters.retry(
+                        strategy,
+                        w,
+                        h,
+                        &mut wfc_state,
+                        &mut history,
+                        &mut rng,
+                    );
+                    //The relaxed rules are kept for the rest of the session
+                    if allowed > 0 {
+                        eprintln!("relaxed the rules, {allowed} pairs of tiles are now allowed");
+                    }
+                }
+                Some(wfc::ContradictionAction::Abort) => {
+                    return Err("generation aborted after a contradiction".to_string())
+                }
+                None => {}
+            }
+            changed = true;
+            selected = None;
+        } else if (running || events.step) && !wfc_state.done() {
+            changed = true;
+            //The tile picked by a click can't be changed once the solver
+            //has moved on
+            selected = None;
+            if retry_strategy == wfc::RetryStrategy::Backtrack {
+                history.push(wfc_state.clone());
+                if history.len() > BACKTRACK_DEPTH {
+                    history.remove(0);
+                }
+            }
+            if let Err(msg) = wfc_parameters.step(w, h, &mut wfc_state, &mut rng) {
+                contradictions += 1;
+                recovery = Some(handle_contradiction(
+                    &wfc_parameters,
+                    &wfc_state,
+                    (w, h),
+                    contradictions,
+                    retry_strategy,
+                    &msg,
+                ));
+            }
+        }
+
+        if changed && (!throttled || recovery.is_some()) {
+            wfc::copy_superpositions_to_grid(
+                output_image.pixels_mut(),
+                wfc_state.superpositions(),
+                wfc_state.masked(),
+                (w, h),
+                &wfc_parameters.wfc_tiles,
+                &wfc_parameters.wfc_frequency,
+                &RENDER_OPTIONS,
+            );
+            palette::remap(&mut output_image, palette);
+            output_texture = if preview {
+                let tiled = output_image.tiled(PREVIEW_REPEATS, PREVIEW_REPEATS);
+                texture_from_image(&tiled, &texture_creator)?
+            } else {
+                texture_from_image(&output_image, &texture_creator)?
+            };
+            changed = false;
+        }
+
+        //The layout is computed from the size of the window every time it is
+        //drawn, a resized window is drawn right away so that it is not left
+        //with the old layout until the next frame that is not throttled
+        if !throttled || events.resized {
+            display_loop(&mut canvas, &input_texture, &output_texture)?;
+        }
+
+        current_frame += 1;
+
+        events = process_events(&mut event_pump, &cancel);
+        if events.toggle_pause {
+            paused = !paused;
+        }
+        if events.toggle_preview {
+            preview = !preview;
+            changed = true;
+        }
+
+        if events.next_retry_strategy || events.toggle_learning {
+            if events.next_retry_strategy {
+                retry_strategy = retry_strategy.next();
+                //The history is only kept up to date while backtracking
+                history.clear();
+            }
+            learning ^= events.toggle_learning;
+            canvas
+                .window_mut()
+                .set_title(&window_title(retry_strategy, learning, &input_args))
+                .map_err(|e| e.to_string())?;
+        }
+
+        //T and O extract the rules of the current input again with the next
+        //tile size or with rotations toggled
+        if events.next_tile_size || events.toggle_rotations {
+            let mut new_input = input_args.clone();
+            if events.next_tile_size {
+                new_input.tile_size = new_input.tile_size % MAX_TILE_SIZE + 1;
+            }
+            new_input.rotations ^= events.toggle_rotations;
+            canvas
+                .window_mut()
+                .set_title(&format!(
+                    "{WINDOW_TITLE} - extracting tile size {}",
+                    new_input.tile_size
+                ))
+                .map_err(|e| e.to_string())?;
+            let receiver = load_input_in_background(new_input.clone(), Some(input_data.clone()));
+            loading = Some((receiver, new_input));
+        }
+
+        //Clicking a cell collapses it before the solver picks the next one
+        if let Some(clicked) = events.clicked {
+            let layout = window_layout(&canvas, &input_texture, &output_texture)?;
+            //The window and the canvas can have different sizes on high dpi
+            //displays
+            let window_size = canvas.window().size();
+            let canvas_size = canvas.output_size()?;
+            let pos = (
+                clicked.0 as f32 * canvas_size.0 as f32 / window_size.0.max(1) as f32,
+                clicked.1 as f32 * canvas_size.1 as f32 / window_size.1.max(1) as f32,
+            );
+            //Every copy of the output in the preview is the same cells
+            let repeats = if preview { PREVIEW_REPEATS } else { 1 };
+            let cell = layout.output_cell(pos, (w * repeats, h * repeats));
+            if let Some((x, y)) = cell.map(|(x, y)| (x % w, y % h)) {
+                let index = x + y * w;
+                let next = match selected.take() {
+                    Some((selected_index, before, choice)) if selected_index == index => {
+                        wfc_state = before.clone();
+                        Some((before, choice + 1))
+                    }
+                    //Masked out cells have nothing to pick and collapsed cells
+                    //can only be changed to a tile that is not allowed there
+                    _ if wfc_state.masked()[index] => None,
+                    _ if wfc_state.superpositions()[index].len() <= 1 && !learning => None,
+                    _ => Some((wfc_state.clone(), 0)),
+                };
+
+                if let Some((before, choice)) = next {
+                    let options: Vec<usize> = if learning {
+                        (0..wfc_parameters.wfc_tiles.len()).collect()
+                    } else {
+                        before.superpositions()[index].iter().collect()
+                    };
+                    let choice = choice % options.len();
+                    let tile = options[choice];
+                    recovery = None;
+                    changed = true;
+                    match wfc_parameters.place_tile(&mut wfc_state, index, tile, w, h, learning) {
+                        Ok(0) => {}
+                        Ok(learned) => eprintln!("learned {learned} new pairs of tiles"),
+                        Err(msg) => {
+                            contradictions += 1;
+                            recovery = Some(handle_contradiction(
+                                &wfc_parameters,
+                                &wfc_state,
+                                (w, h),
+                                contradictions,
+                                retry_strategy,
+                                &msg,
+                            ));
+                        }
+                    }
+                    selected = Some((index, before, choice));
+                }
+            }
+        }
+
+        if events.save {
+            wfc::copy_superpositions_to_grid(
+                output_image.pixels_mut(),
+                wfc_state.superpositions(),
+                wfc_state.masked(),
+                (w, h),
+                &wfc_parameters.wfc_tiles,
+                &wfc_parameters.wfc_frequency,
+                &RENDER_OPTIONS,
+            );
+            palette::remap(&mut output_image, palette);
+            let current_args = GenerateArgs {
+                input: input_args.clone(),
+                ..args.clone()
+            };
+            let manifest = create_manifest(&current_args, &wfc_parameters, seed, w, h);
+            save_output(&output_image, &args.out_path(), &manifest);
+        }
+
+        //Dropping a png onto the window replaces the input image
+        if let Some(path) = events.dropped_file.take() {
+            canvas
+                .window_mut()
+                .set_title(&format!("{WINDOW_TITLE} - loading {path}"))
+                .map_err(|e| e.to_string())?;
+            let new_input = InputArgs {
+                input: path,
+                ..input_args.clone()
+            };
+            loading = Some((load_input_in_background(new_input.clone(), None), new_input));
+        }
+
+        let loaded = loading
+            .as_ref()
+            .and_then(|(receiver, new_input)| Some((receiver.try_recv().ok()?, new_input.clone())));
+        if let Some((loaded, new_input)) = loaded {
+            loading = None;
+            //The guide and the contradiction policy belong to the output so
+            //they are kept, the fill tile has to exist in the new tiles
+            let loaded = loaded.and_then(|(new_data, mut new_parameters)| {
+                new_parameters.wfc_guide = wfc_parameters.wfc_guide.clone();
+                new_parameters.wfc_contradiction_policy = wfc_parameters.wfc_contradiction_policy;
+                check_parameters(&new_parameters)?;
+                Ok((new_data, new_parameters))
+            });
+            match loaded {
+                Ok((new_data, new_parameters)) => {
+                    input_texture = texture_from_image(&new_data, &texture_creator)?;
+                    input_data = new_data;
+                    input_args = new_input;
+                    wfc_parameters = new_parameters;
+                    selected = None;
+                    history.clear();
+                    //Start over from the seed so that the seed in the
+                    //manifest generates the output of the new input again
+                    rng = StdRng::seed_from_u64(seed);
+                    wfc_state = wfc_parameters.start_state(w, h, &masked, &mut rng);
+                    recovery = None;
+                    changed = true;
+                    canvas
+                        .window_mut()
+                        .set_title(&window_title(retry_strategy, learning, &input_args))
+                        .map_err(|e| e.to_string())?;
+                }
+                Err(msg) => {
+                    //Keep the current input and show the error in the title
+                    eprintln!("{msg}");
+                    canvas
+                        .window_mut()
+                        .set_title(&format!("{WINDOW_TITLE} - {msg}"))
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,118 @@
-use std::fs::File;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Decode(png::DecodingError),
+    Encode(png::EncodingError),
+    UnsupportedColorType(png::ColorType),
+    UnsupportedBitDepth(png::BitDepth),
+    EmptyImage,
+    PartialFrame,
+    RaggedLine {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+    UnknownCharacter {
+        character: char,
+        line: usize,
+    },
+    TooManyCharacters(usize),
+    TooLarge {
+        width: usize,
+        height: usize,
+        frames: usize,
+        bytes: u128,
+    },
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "{e}"),
+            ImageError::Decode(e) => write!(f, "{e}"),
+            ImageError::Encode(e) => write!(f, "{e}"),
+            ImageError::UnsupportedColorType(color_type) => {
+                write!(f, "unsupported color type {color_type:?}, expected RGBA")
+            }
+            ImageError::UnsupportedBitDepth(bit_depth) => {
+                write!(f, "unsupported bit depth {bit_depth:?}, expected 8 bits")
+            }
+            ImageError::EmptyImage => write!(f, "image has no pixels"),
+            ImageError::PartialFrame => {
+                write!(f, "animation frames must cover the whole image")
+            }
+            ImageError::RaggedLine {
+                line,
+                width,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "line {line} has {width} characters but the first line has {expected}"
+                )
+            }
+            ImageError::UnknownCharacter { character, line } => {
+                write!(f, "character '{character}' on line {line} has no color")
+            }
+            ImageError::TooManyCharacters(count) => {
+                write!(
+                    f,
+                    "text grid has {count} different characters, at most {} are supported",
+                    TEXT_COLORS.len()
+                )
+            }
+            ImageError::TooLarge {
+                width,
+                height,
+                frames,
+                bytes,
+            } => {
+                write!(
+                    f,
+                    "loading {frames} {width}x{height} frame(s) would need about {} MB, more than the limit of {} MB, try a smaller crop of the image",
+                    bytes >> 20,
+                    MAX_LOAD_BYTES >> 20
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImageError::Io(e) => Some(e),
+            ImageError::Decode(e) => Some(e),
+            ImageError::Encode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ImageError {
+    fn from(e: std::io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(e: png::DecodingError) -> Self {
+        ImageError::Decode(e)
+    }
+}
+
+impl From<png::EncodingError> for ImageError {
+    fn from(e: png::EncodingError) -> Self {
+        ImageError::Encode(e)
+    }
+}
 
 #[derive(Clone)]
 pub struct ImageData {
@@ -7,12 +121,60 @@ pub struct ImageData {
     height: usize,
 }
 
-pub fn wrap_value(v: isize, max: usize) -> usize {
-    if v < 0 {
-        max - ((-v) as usize % max)
-    } else {
-        v as usize % max
+//Images that would need more memory than this to load are rejected before
+//anything is allocated
+const MAX_LOAD_BYTES: u128 = 1 << 30;
+
+//Bytes needed to load the frames of a png: the decoded rgba buffer that is
+//reused for every frame and the pixels of each frame, both 4 bytes a pixel
+pub fn load_bytes(width: usize, height: usize, frames: usize) -> u128 {
+    let pixels = width as u128 * height as u128;
+    let frame = pixels.saturating_mul(4);
+    frame.saturating_add(frame.saturating_mul(frames as u128))
+}
+
+//Fails if loading the frames would need more than MAX_LOAD_BYTES
+pub fn check_load_size(width: usize, height: usize, frames: usize) -> Result<(), ImageError> {
+    let bytes = load_bytes(width, height, frames);
+    if bytes > MAX_LOAD_BYTES {
+        return Err(ImageError::TooLarge {
+            width,
+            height,
+            frames,
+            bytes,
+        });
     }
+    Ok(())
+}
+
+//Colors given to the characters of a text grid in the order they first appear
+const TEXT_COLORS: [u32; 12] = [
+    0xff000000, 0xffffffff, 0xff0000ff, 0xff00ff00, 0xffff0000, 0xff00ffff, 0xffff00ff, 0xffffff00,
+    0xff808080, 0xff0080ff, 0xff800080, 0xff13458b,
+];
+
+//Gives each character of a text grid a color, newlines are skipped
+pub fn text_palette(text: &str) -> Result<HashMap<char, u32>, ImageError> {
+    let mut palette = HashMap::new();
+    for character in text.chars().filter(|c| *c != '\n' && *c != '\r') {
+        if palette.contains_key(&character) {
+            continue;
+        }
+        let color = TEXT_COLORS
+            .get(palette.len())
+            .ok_or(ImageError::TooManyCharacters(palette.len() + 1))?;
+        palette.insert(character, *color);
+    }
+    Ok(palette)
+}
+
+//Color of the pixels that are different in a diff image (magenta)
+const DIFF_COLOR: u32 = 0xffff00ff;
+
+//Wraps v into the range 0..max, works for any offset including negative
+//multiples of max
+pub fn wrap_value(v: isize, max: usize) -> usize {
+    v.rem_euclid(max as isize) as usize
 }
 
 #[allow(dead_code)]
@@ -33,32 +195,218 @@ impl ImageData {
         }
     }
 
+    //Each line of the text is a row of pixels and each character is a pixel
+    //with the color it has in the palette, empty lines at the end are ignored
+    pub fn from_text_grid(text: &str, palette: &HashMap<char, u32>) -> Result<Self, ImageError> {
+        let lines: Vec<&str> = text.trim_end_matches(['\n', '\r']).lines().collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        if width == 0 {
+            return Err(ImageError::EmptyImage);
+        }
+
+        let mut pixels = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let line_width = line.chars().count();
+            if line_width != width {
+                return Err(ImageError::RaggedLine {
+                    line: i + 1,
+                    width: line_width,
+                    expected: width,
+                });
+            }
+            for character in line.chars() {
+                let color = palette
+                    .get(&character)
+                    .ok_or(ImageError::UnknownCharacter {
+                        character,
+                        line: i + 1,
+                    })?;
+                pixels.push(*color);
+            }
+        }
+
+        Ok(Self {
+            pixels,
+            width,
+            height: lines.len(),
+        })
+    }
+
+    //Load a text grid where each character gets a color from text_palette
+    pub fn load_text_grid(path: &str) -> Result<Self, ImageError> {
+        let text = fs::read_to_string(path)?;
+        Self::from_text_grid(&text, &text_palette(&text)?)
+    }
+
+    //Loads a text grid if the path ends with .txt and a png otherwise
+    pub fn load(path: &str) -> Result<Self, ImageError> {
+        if path.ends_with(".txt") {
+            Self::load_text_grid(path)
+        } else {
+            Self::load_png(path)
+        }
+    }
+
     //Load the image data from a png
-    pub fn load_png(path: &str) -> Result<Self, String> {
-        let decoder = png::Decoder::new(File::open(path).map_err(|e| e.to_string())?);
-        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    pub fn load_png(path: &str) -> Result<Self, ImageError> {
+        let decoder = png::Decoder::new(File::open(path)?);
+        let mut reader = decoder.read_info()?;
+        let (color_type, bit_depth) = reader.output_color_type();
+        if color_type != png::ColorType::Rgba {
+            return Err(ImageError::UnsupportedColorType(color_type));
+        }
+        if bit_depth != png::BitDepth::Eight {
+            return Err(ImageError::UnsupportedBitDepth(bit_depth));
+        }
+        let (width, height) = (reader.info().width, reader.info().height);
+        check_load_size(width as usize, height as usize, 1)?;
         let mut buf = vec![0; reader.output_buffer_size()];
-        let info = reader.next_frame(&mut buf).map_err(|e| e.to_string())?;
+        let info = reader.next_frame(&mut buf)?;
+        if info.width == 0 || info.height == 0 {
+            return Err(ImageError::EmptyImage);
+        }
 
-        let mut pix_data = vec![0; buf.len() / 4];
+        Ok(Self {
+            pixels: pixels_from_rgba(&buf),
+            width: info.width as usize,
+            height: info.height as usize,
+        })
+    }
+
+    //Load every frame of an animated png, a regular png is loaded as a
+    //single frame. Every frame has to cover the whole image
+    pub fn load_png_frames(path: &str) -> Result<Vec<Self>, ImageError> {
+        let decoder = png::Decoder::new(File::open(path)?);
+        let mut reader = decoder.read_info()?;
+        let (color_type, bit_depth) = reader.output_color_type();
+        if color_type != png::ColorType::Rgba {
+            return Err(ImageError::UnsupportedColorType(color_type));
+        }
+        if bit_depth != png::BitDepth::Eight {
+            return Err(ImageError::UnsupportedBitDepth(bit_depth));
+        }
+        let (width, height) = (reader.info().width, reader.info().height);
+        if width == 0 || height == 0 {
+            return Err(ImageError::EmptyImage);
+        }
+        let frame_count = match reader.info().animation_control() {
+            Some(animation_control) => animation_control.num_frames as usize,
+            None => 1,
+        };
+        check_load_size(width as usize, height as usize, frame_count)?;
 
-        for i in 0..(buf.len() / 4) {
-            if i * 4 + 3 >= buf.len() {
-                break;
+        let mut frames = vec![];
+        let mut buf = vec![0; reader.output_buffer_size()];
+        for _ in 0..frame_count {
+            let info = reader.next_frame(&mut buf)?;
+            if info.width != width || info.height != height {
+                return Err(ImageError::PartialFrame);
             }
-            pix_data[i] = (buf[4 * i] as u32)
-                | (buf[4 * i + 1] as u32) << 8
-                | (buf[4 * i + 2] as u32) << 16
-                | (buf[4 * i + 3] as u32) << 24;
+            frames.push(Self {
+                pixels: pixels_from_rgba(&buf[..info.buffer_size()]),
+                width: width as usize,
+                height: height as usize,
+            });
+        }
+
+        Ok(frames)
+    }
+
+    //Copies the w x h region with its top left corner at (x, y), the region
+    //is clamped to the edges of the image
+    pub fn crop(&self, x: usize, y: usize, w: usize, h: usize) -> Result<Self, String> {
+        if x >= self.width || y >= self.height {
+            return Err(format!(
+                "crop position ({x}, {y}) is outside of the {}x{} image",
+                self.width, self.height
+            ));
+        }
+
+        let w = w.min(self.width - x);
+        let h = h.min(self.height - y);
+        if w == 0 || h == 0 {
+            return Err("crop region is empty".to_string());
+        }
+
+        let mut pixels = Vec::with_capacity(w * h);
+        for row in y..(y + h) {
+            let start = row * self.width + x;
+            pixels.extend_from_slice(&self.pixels[start..(start + w)]);
         }
 
         Ok(Self {
-            pixels: pix_data,
-            width: info.width as usize,
-            height: info.height as usize,
+            pixels,
+            width: w,
+            height: h,
         })
     }
 
+    //Cuts a sprite sheet of columns x rows tiles that are each tile_w x
+    //tile_h pixels into one image per tile, row by row from the top left
+    #[allow(dead_code)]
+    pub fn split_grid(
+        &self,
+        tile_w: usize,
+        tile_h: usize,
+        columns: usize,
+        rows: usize,
+    ) -> Result<Vec<Self>, String> {
+        self.split_grid_spaced(tile_w, tile_h, columns, rows, (0, 0), (0, 0))
+    }
+
+    //Same as split_grid for sheets where the first tile starts margin (x, y)
+    //pixels from the top left corner and there are spacing (x, y) pixels
+    //between neighboring tiles. Fails if a tile doesn't fit in the sheet
+    #[allow(dead_code)]
+    pub fn split_grid_spaced(
+        &self,
+        tile_w: usize,
+        tile_h: usize,
+        columns: usize,
+        rows: usize,
+        margin: (usize, usize),
+        spacing: (usize, usize),
+    ) -> Result<Vec<Self>, String> {
+        if tile_w == 0 || tile_h == 0 {
+            return Err("tiles of a sprite sheet can't be empty".to_string());
+        }
+        let end = |margin: usize, tile: usize, spacing: usize, count: usize| {
+            margin + count * tile + count.saturating_sub(1) * spacing
+        };
+        let sheet_w = end(margin.0, tile_w, spacing.0, columns);
+        let sheet_h = end(margin.1, tile_h, spacing.1, rows);
+        if sheet_w > self.width || sheet_h > self.height {
+            return Err(format!(
+                "{columns}x{rows} tiles of {tile_w}x{tile_h} pixels need a {sheet_w}x{sheet_h} sheet but the image is {}x{}",
+                self.width, self.height
+            ));
+        }
+
+        let mut tiles = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = margin.0 + column * (tile_w + spacing.0);
+                let y = margin.1 + row * (tile_h + spacing.1);
+                tiles.push(self.crop(x, y, tile_w, tile_h)?);
+            }
+        }
+        Ok(tiles)
+    }
+
+    //Save the image data as an RGBA png
+    pub fn save_png(&self, path: &str) -> Result<(), ImageError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+
+        writer.write_image_data(&rgba_from_pixels(&self.pixels))?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
     //Get pixel data, if it is out of bounds return 0
     pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
         if x >= self.width || y >= self.height {
@@ -75,6 +423,117 @@ impl ImageData {
         self.pixels[wrapped_x + wrapped_y * self.width]
     }
 
+    //Scales the image to w x h using the nearest pixel, so scaling by a whole
+    //number turns each pixel into a block of the same color
+    pub fn scale_nearest(&self, w: usize, h: usize) -> Self {
+        let mut pixels = Vec::with_capacity(w * h);
+        for y in 0..h {
+            for x in 0..w {
+                pixels.push(self.get_pixel(x * self.width / w, y * self.height / h));
+            }
+        }
+
+        Self {
+            pixels,
+            width: w,
+            height: h,
+        }
+    }
+
+    //The image scaled up by each of the whole number factors, so one output
+    //can be saved at several sizes without generating it again. A scale of
+    //0 is treated as 1
+    #[allow(dead_code)]
+    pub fn render_multi(&self, scales: &[usize]) -> Vec<Self> {
+        scales
+            .iter()
+            .map(|scale| {
+                let scale = (*scale).max(1);
+                self.scale_nearest(self.width * scale, self.height * scale)
+            })
+            .collect()
+    }
+
+    //The image repeated columns times side by side and rows times on top of
+    //each other, the seams show whether the edges of the image line up
+    pub fn tiled(&self, columns: usize, rows: usize) -> Self {
+        let width = self.width * columns;
+        let mut pixels = Vec::with_capacity(width * self.height * rows);
+        for _ in 0..rows {
+            for row in self.pixels.chunks(self.width.max(1)) {
+                for _ in 0..columns {
+                    pixels.extend_from_slice(row);
+                }
+            }
+        }
+        Self {
+            pixels,
+            width,
+            height: self.height * rows,
+        }
+    }
+
+    //Positions (x, y) of the pixels that are different in other, row by row
+    //from the top left. Fails if the images are not the same size
+    pub fn diff(&self, other: &Self) -> Result<Vec<(usize, usize)>, String> {
+        if self.width != other.width || self.height != other.height {
+            return Err(format!(
+                "can't compare a {}x{} image with a {}x{} image",
+                self.width, self.height, other.width, other.height
+            ));
+        }
+
+        Ok(self
+            .pixels
+            .iter()
+            .zip(&other.pixels)
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(i, _)| (i % self.width, i / self.width))
+            .collect())
+    }
+
+    //The image faded toward white with the pixels at positions in
+    //DIFF_COLOR, so the differences found by diff stand out
+    pub fn highlight_diff(&self, positions: &[(usize, usize)]) -> Self {
+        let mut pixels: Vec<u32> = self
+            .pixels
+            .iter()
+            .map(|pixel| {
+                let mut faded = 0xff000000;
+                for shift in [0, 8, 16] {
+                    let channel = (pixel >> shift) & 0xff;
+                    faded |= ((channel + 3 * 0xff) / 4) << shift;
+                }
+                faded
+            })
+            .collect();
+        for (x, y) in positions {
+            if let Some(pixel) = pixels.get_mut(y * self.width + x) {
+                *pixel = DIFF_COLOR;
+            }
+        }
+
+        Self {
+            pixels,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    //Scales the image to w x h and returns which pixels are fully
+    //transparent, used to mask out parts of the output
+    pub fn transparent_mask(&self, w: usize, h: usize) -> Vec<bool> {
+        let mut mask = vec![false; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let pixel = self.get_pixel(x * self.width / w, y * self.height / h);
+                mask[y * w + x] = pixel >> 24 == 0;
+            }
+        }
+        mask
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -92,6 +551,72 @@ impl ImageData {
     }
 }
 
+//Packs RGBA bytes into one u32 per pixel
+fn pixels_from_rgba(buf: &[u8]) -> Vec<u32> {
+    buf.chunks_exact(4)
+        .map(|pixel| u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]))
+        .collect()
+}
+
+fn rgba_from_pixels(pixels: &[u32]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_le_bytes())
+        .collect()
+}
+
+//Writes a png one row at a time so that the whole image never has to be in
+//memory, finish has to be called after the last row
+pub struct PngStream {
+    writer: png::StreamWriter<'static, BufWriter<File>>,
+    width: usize,
+}
+
+impl PngStream {
+    pub fn create(path: &str, width: usize, height: usize) -> Result<Self, ImageError> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let writer = encoder.write_header()?.into_stream_writer()?;
+        Ok(Self { writer, width })
+    }
+
+    pub fn write_row(&mut self, row: &[u32]) -> Result<(), ImageError> {
+        debug_assert_eq!(row.len(), self.width);
+        self.writer.write_all(&rgba_from_pixels(row))?;
+        Ok(())
+    }
+
+    //Fails if fewer rows than the height were written
+    pub fn finish(self) -> Result<(), ImageError> {
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+//Save the frames as an animated png, all frames must have the same size
+#[allow(dead_code)]
+pub fn save_apng(frames: &[ImageData], path: &str) -> Result<(), ImageError> {
+    let first = frames.first().ok_or(ImageError::EmptyImage)?;
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, first.width as u32, first.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frames.len() as u32, 0)?;
+    let mut writer = encoder.write_header()?;
+
+    for frame in frames {
+        if frame.width != first.width || frame.height != first.height {
+            return Err(ImageError::PartialFrame);
+        }
+        writer.write_image_data(&rgba_from_pixels(&frame.pixels))?;
+    }
+    writer.finish()?;
+
+    Ok(())
+}
+
 //Converts a u32 into a color struct (r, g, b)
 pub fn u32_to_color(pixel: u32) -> (f32, f32, f32) {
     let b = ((pixel >> 16) & 0xff) as f32;
@@ -0,0 +1,91 @@
+//Where the input and output images are placed in the window
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Layout {
+    pub pixel_size: f32,
+    pub input_pos: (f32, f32),
+    pub output_pos: (f32, f32),
+}
+
+impl Layout {
+    //Cell of the output under a position in the window, None if the position
+    //is outside of the output
+    pub fn output_cell(&self, pos: (f32, f32), output: (usize, usize)) -> Option<(usize, usize)> {
+        let x = (pos.0 - self.output_pos.0) / self.pixel_size;
+        let y = (pos.1 - self.output_pos.1) / self.pixel_size;
+        if x < 0.0 || y < 0.0 || x >= output.0 as f32 || y >= output.1 as f32 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+}
+
+//Size of one image pixel when the images are placed in a grid of
+//`units_w` x `units_h` image pixels inside the window
+fn fit_pixel_size(units_w: usize, units_h: usize, window: (u32, u32)) -> f32 {
+    let size_x = window.0 as f32 / units_w.max(1) as f32;
+    let size_y = window.1 as f32 / units_h.max(1) as f32;
+    size_x.min(size_y)
+}
+
+//Places the input and output images either side by side or stacked on top
+//of each other, whichever allows for the larger pixel size, with a margin of
+//one pixel around and between the images. The pixel size is never larger
+//than `max_pixel_size`
+pub fn compute_layout(
+    input: (usize, usize),
+    output: (usize, usize),
+    window: (u32, u32),
+    max_pixel_size: f32,
+) -> Layout {
+    let side_by_side = fit_pixel_size(input.0 + output.0 + 3, input.1.max(output.1) + 2, window);
+    let stacked = fit_pixel_size(input.0.max(output.0) + 2, input.1 + output.1 + 3, window);
+
+    if side_by_side >= stacked {
+        let pixel_size = side_by_side.min(max_pixel_size);
+        Layout {
+            pixel_size,
+            input_pos: (pixel_size, pixel_size),
+            output_pos: ((input.0 + 2) as f32 * pixel_size, pixel_size),
+        }
+    } else {
+        let pixel_size = stacked.min(max_pixel_size);
+        Layout {
+            pixel_size,
+            input_pos: (pixel_size, pixel_size),
+            output_pos: (pixel_size, (input.1 + 2) as f32 * pixel_size),
+        }
+    }
+}
+
+//Pixels of an image placed at pos that are at least partly inside the window,
+//as (x, y, w, h) in image pixels. None if the whole image is outside of it
+pub fn visible_pixels(
+    pos: (f32, f32),
+    pixel_size: f32,
+    image: (usize, usize),
+    window: (u32, u32),
+) -> Option<(usize, usize, usize, usize)> {
+    if pixel_size <= 0.0 {
+        return None;
+    }
+
+    let visible_range = |pos: f32, len: usize, window: u32| {
+        let first = (-pos / pixel_size).floor().max(0.0) as usize;
+        let last = ((window as f32 - pos) / pixel_size).ceil().max(0.0) as usize;
+        (first, last.min(len))
+    };
+    let (x, end_x) = visible_range(pos.0, image.0, window.0);
+    let (y, end_y) = visible_range(pos.1, image.1, window.1);
+    if x >= end_x || y >= end_y {
+        return None;
+    }
+    Some((x, y, end_x - x, end_y - y))
+}
+
+//Whether drawing the output is skipped this frame. Only the frames in
+//between every `speed`th frame are skipped and only while the solver is
+//running on its own, when paused or stepping the output is drawn every frame
+//so it always shows the current state
+pub fn throttled(running: bool, frame: u32, speed: u32) -> bool {
+    running && !frame.is_multiple_of(speed)
+}
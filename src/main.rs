@@ -1,238 +1,626 @@
-use crate::image_data::ImageData;
-use sdl2::{
-    event::Event,
-    pixels::{Color, PixelFormatEnum},
-    rect::Rect,
-    render::{Canvas, Texture, TextureCreator},
-    video::{Window, WindowContext},
-    EventPump,
+use crate::{
+    cli::{Command, DiffArgs, GenerateArgs, InputArgs, InspectArgs, ValidateArgs},
+    frames::FrameWriter,
+    image_data::{ImageData, PngStream},
+    manifest::{manifest_path, Manifest},
+    palette::Palette,
+    progress::Progress,
 };
-use std::{env, path::Path};
-
+use std::{env, fs, io, path::Path, time::Instant};
+
+mod animation;
+mod cell_queue;
+mod cli;
+mod config;
+mod frames;
+//The window is only built with the gui feature (on by default), without it
+//only headless generation is available
+#[cfg(feature = "gui")]
+mod gui;
 mod image_data;
+#[cfg(feature = "gui")]
+mod layout;
+mod manifest;
+mod palette;
+mod progress;
 mod wfc;
 
-const PIXEL_SIZE: f32 = 8.0;
 const SPEED: u32 = 16;
+//Cells where propagation failed are drawn in magenta until the next step and
+//cells with an id that is not a tile in yellow
+const RENDER_OPTIONS: wfc::RenderOptions = wfc::RenderOptions {
+    partial: wfc::PartialRender::Average,
+    contradiction_color: 0xffff00ff,
+    invalid_color: 0xff00ffff,
+};
+const WEIGHT_TRANSFORM: wfc::WeightTransform = wfc::WeightTransform::Identity;
+const GROW_FROM_COLLAPSED: bool = false;
+//Measured with stress_test over 200 seeds at 16x16 and 24x24 this lowers
+//the contradiction rate of inputimage1 and inputimage2 (by up to a fifth)
+//but raises it for inputimage6 and inputimage7, so it is off by default
+const PREFER_CONSTRAINED: bool = false;
+const START_POSITION: wfc::StartPosition = wfc::StartPosition::Random;
+//Tiles collapsed each step, raising this makes large grids fill in faster
+const COLLAPSES_PER_STEP: usize = 1;
+//Above 0 the next tile to collapse is picked at random, favoring low
+//entropy tiles less the higher this is
+const TEMPERATURE: f32 = 0.0;
+const MAX_ATTEMPTS: u64 = 16;
+//How strongly a guide image pulls the cells toward its colors
+const GUIDE_STRENGTH: f32 = 2.0;
+//Tiles in each row of the atlas saved by inspect
+const ATLAS_COLUMNS: usize = 16;
+
+fn load_parameters(data: &ImageData, input: &InputArgs) -> Result<wfc::WFCParameters, String> {
+    let mut wfc_parameters = wfc::WFCParameters::from_image_data_with_options(
+        data,
+        input.n(),
+        &input.extraction_options(),
+    )?;
+    wfc_parameters.wfc_boundary = input.output_boundary();
+    wfc_parameters.wfc_weight_transform = input.weight_transform().unwrap_or(WEIGHT_TRANSFORM);
+    wfc_parameters.wfc_grow_from_collapsed = GROW_FROM_COLLAPSED;
+    wfc_parameters.wfc_prefer_constrained = PREFER_CONSTRAINED;
+    wfc_parameters.wfc_start = START_POSITION;
+    wfc_parameters.wfc_collapses_per_step = COLLAPSES_PER_STEP;
+    wfc_parameters.wfc_temperature = TEMPERATURE;
+    Ok(wfc_parameters)
+}
 
-//Process events
-struct ProcessedEvents {
-    can_quit: bool,
+//Fails with every issue that would keep the rules from being satisfied
+fn check_parameters(wfc_parameters: &wfc::WFCParameters) -> Result<(), String> {
+    wfc_parameters.validate().map_err(|issues| {
+        let issues: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+        format!("the rules can not be satisfied:\n{}", issues.join("\n"))
+    })
 }
 
-fn process_events(event_pump: &mut EventPump) -> ProcessedEvents {
-    let mut processed = ProcessedEvents { can_quit: false };
+fn save_output(output_image: &ImageData, out_path: &str, manifest: &Manifest) {
+    //The solver works on a small grid, the image is only scaled up when saved
+    let scaled = manifest
+        .render_size
+        .map(|(w, h)| output_image.scale_nearest(w, h));
+    //The manifest is written next to the image so that it can be generated again
+    let result = scaled
+        .as_ref()
+        .unwrap_or(output_image)
+        .save_png(out_path)
+        .map_err(|e| e.to_string())
+        .and_then(|()| manifest.save(&manifest_path(out_path)));
+    match result {
+        Ok(()) => eprintln!("saved output to {out_path}"),
+        Err(msg) => eprintln!("failed to save {out_path}: {msg}"),
+    }
+}
 
-    for event in event_pump.poll_iter() {
-        if let Event::Quit { .. } = event {
-            processed.can_quit = true;
+//Generates an animation from an animated png input and saves it without
+//opening a window
+fn generate_animation(args: &GenerateArgs) -> Result<(), String> {
+    let out_path = args.out_path();
+    let frames = ImageData::load_png_frames(&args.input.input).map_err(|e| e.to_string())?;
+    let parameters = animation::AnimatedParameters::from_frames(&frames, args.input.n())?;
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut attempt = 0;
+    let generated = loop {
+        let (w, h) = (args.width as usize, args.height as usize);
+        match parameters.generate_seeded(w, h, frames.len(), seed.wrapping_add(attempt)) {
+            Ok(generated) => break generated,
+            Err(msg) if attempt < MAX_ATTEMPTS => {
+                eprintln!("{msg}");
+                attempt += 1;
+            }
+            Err(msg) => return Err(msg),
         }
+    };
+    image_data::save_apng(&generated, &out_path).map_err(|e| e.to_string())?;
+    eprintln!("saved animation to {out_path}");
+    Ok(())
+}
+
+//Generates the output and saves it without opening a window
+fn generate_headless(
+    wfc_parameters: &wfc::WFCParameters,
+    args: &GenerateArgs,
+    mask: Option<&ImageData>,
+    palette: &Palette,
+    timings: &mut wfc::Timings,
+) -> Result<(), String> {
+    let (w, h) = (args.width as usize, args.height as usize);
+    if let Some(band_height) = args.band_height {
+        return generate_streamed(wfc_parameters, args, band_height as usize, palette);
     }
+    let masked = mask.map(|mask| mask.transparent_mask(w, h));
+    let mut frame_writer = match &args.frames_dir {
+        Some(dir) => Some(FrameWriter::new(dir, SPEED as usize, w, h)?),
+        None => None,
+    };
+    let start_seed = args.seed.unwrap_or_else(rand::random);
+    for attempt in 0..MAX_ATTEMPTS {
+        let seed = start_seed.wrapping_add(attempt);
+        //Each attempt saves its own entropy map and csv so the files on disk
+        //match the attempt that succeeded, or the last one if they all fail
+        let mut entropy_map_result = None;
+        let mut entropy_csv_result = None;
+        let generated = match (
+            &masked,
+            &mut frame_writer,
+            &args.entropy_map,
+            &args.entropy_csv,
+            args.quiet,
+            args.timings,
+        ) {
+            (None, None, None, None, true, false) => {
+                wfc_parameters.generate_grid_seeded(w, h, seed)
+            }
+            (masked, frame_writer, entropy_map, entropy_csv, quiet, _) => {
+                let masked = masked.clone().unwrap_or_else(|| vec![false; w * h]);
+                let total = masked.iter().filter(|masked| !**masked).count();
+                let mut progress = (!quiet).then(|| Progress::new(io::stderr(), total));
+                //Frames of failed attempts are kept so the restarts can be seen
+                let mut on_step = |superpositions: &[wfc::Superposition], remaining: usize| {
+                    if let Some(progress) = progress.as_mut() {
+                        progress.step(remaining);
+                    }
+                    if let Some(frame_writer) = frame_writer.as_mut() {
+                        frame_writer.step(
+                            superpositions,
+                            remaining,
+                            &masked,
+                            wfc_parameters,
+                            (w, h),
+                            &RENDER_OPTIONS,
+                        );
+                    }
+
+                    let collapsed = (total - remaining) as f32 / total.max(1) as f32;
+                    if let Some(path) = entropy_map {
+                        if entropy_map_result.is_none() && collapsed >= args.entropy_at {
+                            let image = wfc::entropy_image(
+                                superpositions,
+                                &masked,
+                                &wfc_parameters.wfc_frequency,
+                                w,
+                                h,
+                            );
+                            entropy_map_result = Some(
+                                image
+                                    .save_png(path)
+                                    .map_err(|e| format!("failed to save {path}: {e}")),
+                            );
+                        }
+                    }
+                };
+                let (wfc_state, result) = wfc_parameters.generate_state_masked_seeded(
+                    w,
+                    h,
+                    &masked,
+                    seed,
+                    Some(&mut on_step),
+                );
+                if let Some(progress) = progress.as_mut() {
+                    progress.finish();
+                }
+                //The timings of the attempts that failed count as well
+                timings.add(wfc_state.timings());
+                if let Some(path) = entropy_csv {
+                    let csv = wfc::entropy_csv(
+                        wfc_state.superpositions(),
+                        wfc_state.masked(),
+                        &wfc_parameters.wfc_frequency,
+                        w,
+                    );
+                    entropy_csv_result = Some(
+                        fs::write(path, csv).map_err(|e| format!("failed to save {path}: {e}")),
+                    );
+                }
+                result.map(|()| {
+                    wfc::superpositions_to_image(
+                        wfc_state.superpositions(),
+                        wfc_state.masked(),
+                        &wfc_parameters.wfc_tiles,
+                        &wfc_parameters.wfc_frequency,
+                        w,
+                        h,
+                        &wfc::RenderOptions::default(),
+                    )
+                })
+            }
+        };
 
-    processed
-}
-
-fn texture_from_image<'a>(
-    data: &ImageData,
-    texture_creator: &'a TextureCreator<WindowContext>,
-) -> Result<Texture<'a>, String> {
-    //Create the texture
-    let mut texture = texture_creator
-        .create_texture_streaming(
-            PixelFormatEnum::BGRA8888,
-            data.width() as u32,
-            data.height() as u32,
-        )
-        .map_err(|e| e.to_string())?;
-
-    texture
-        .with_lock(None, |pixels: &mut [u8], _pitch: usize| {
-            for y in 0..data.height() {
-                for x in 0..data.width() {
-                    let pixel = data.get_pixel(x, y);
-                    let col = image_data::u32_to_color(pixel);
-                    pixels[y * data.width() * 4 + x * 4 + 1] = (col.0 * 255.0) as u8;
-                    pixels[y * data.width() * 4 + x * 4 + 2] = (col.1 * 255.0) as u8;
-                    pixels[y * data.width() * 4 + x * 4 + 3] = (col.2 * 255.0) as u8;
-                    pixels[y * data.width() * 4 + x * 4] = 0xff;
+        if let Some(frame_writer) = &frame_writer {
+            if let Some(msg) = frame_writer.error() {
+                return Err(msg.to_string());
+            }
+        }
+        if let Some(Err(msg)) = entropy_map_result {
+            return Err(msg);
+        }
+        if let Some(Err(msg)) = entropy_csv_result {
+            return Err(msg);
+        }
+
+        match generated {
+            Ok(mut output_image) => {
+                palette::remap(&mut output_image, palette);
+                if let Some(frame_writer) = &frame_writer {
+                    eprintln!("saved {} frames", frame_writer.count());
+                }
+                if let (Some(path), Some(Ok(()))) = (&args.entropy_map, &entropy_map_result) {
+                    eprintln!("saved entropy map to {path}");
+                }
+                if let Some(path) = &args.entropy_csv {
+                    eprintln!("saved entropy of each cell to {path}");
                 }
+                let manifest = create_manifest(args, wfc_parameters, seed, w, h);
+                save_output(&output_image, &args.out_path(), &manifest);
+                if let Some(path) = &args.tiled_preview {
+                    output_image
+                        .tiled(2, 2)
+                        .save_png(path)
+                        .map_err(|e| format!("failed to save {path}: {e}"))?;
+                    eprintln!("saved the output repeated 2x2 times to {path}");
+                }
+                return Ok(());
             }
-        })
-        .map_err(|e| e.to_string())?;
+            Err(msg) => eprintln!("{msg}"),
+        }
+    }
 
-    Ok(texture)
+    Err(format!("WFC Failed after {MAX_ATTEMPTS} attempts"))
 }
 
-fn display_loop(
-    canvas: &mut Canvas<Window>,
-    input_texture: &Texture,
-    output_texture: &Texture,
-) -> Result<(), String> {
-    canvas.set_draw_color(Color::RGB(255, 255, 255));
-    canvas.clear();
-
-    canvas.copy(
-        input_texture,
-        None,
-        Rect::new(
-            PIXEL_SIZE as i32,
-            PIXEL_SIZE as i32,
-            PIXEL_SIZE as u32 * input_texture.query().width,
-            PIXEL_SIZE as u32 * input_texture.query().height,
-        ),
-    )?;
+//Generates many seeds one after another and prints how many of them failed
+//next to the difficulty the rules were estimated to have
+fn stress(
+    wfc_parameters: &wfc::WFCParameters,
+    args: &GenerateArgs,
+    runs: u32,
+    mask: Option<&ImageData>,
+) {
+    let (w, h) = (args.width as usize, args.height as usize);
+    let masked = match mask {
+        Some(mask) => mask.transparent_mask(w, h),
+        None => vec![false; w * h],
+    };
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let report = wfc_parameters.stress_test(w, h, &masked, seed, runs);
+    println!(
+        "{} of {runs} seeds ({seed} to {}) ran into a contradiction, a failure rate of {:.1}%",
+        report.failures,
+        seed.wrapping_add(runs as u64 - 1),
+        report.failure_rate() * 100.0
+    );
+    if report.failures < runs {
+        println!(
+            "the seeds that finished took {:.1} steps on average and {} at most",
+            report.average_steps, report.worst_steps
+        );
+    }
+    println!(
+        "estimated difficulty: {:.2}",
+        wfc_parameters.difficulty_estimate().score
+    );
+}
 
-    canvas.copy(
-        output_texture,
-        None,
-        Rect::new(
-            PIXEL_SIZE as i32 * 2 + input_texture.query().width as i32 * PIXEL_SIZE as i32,
-            PIXEL_SIZE as i32,
-            PIXEL_SIZE as u32 * output_texture.query().width,
-            PIXEL_SIZE as u32 * output_texture.query().height,
-        ),
-    )?;
+//Generates the output in bands and writes each row to the output as soon as
+//it is done, a band that fails is tried again but the rows above it stay
+fn generate_streamed(
+    wfc_parameters: &wfc::WFCParameters,
+    args: &GenerateArgs,
+    band_height: usize,
+    palette: &Palette,
+) -> Result<(), String> {
+    let (w, h) = (args.width as usize, args.height as usize);
+    let out_path = args.out_path();
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let mut stream = PngStream::create(&out_path, w, h)
+        .map_err(|e| format!("failed to save {out_path}: {e}"))?;
+    //The progress of a streamed output is counted in rows
+    let mut progress = (!args.quiet).then(|| Progress::new(io::stderr(), h));
+    let mut rows = 0;
+    let result =
+        wfc_parameters.generate_banded(w, h, band_height, seed, MAX_ATTEMPTS as u32, |ids| {
+            rows += 1;
+            if let Some(progress) = progress.as_mut() {
+                progress.step(h - rows);
+            }
+            let pixels: Vec<u32> = ids.iter().map(|id| wfc_parameters.wfc_tiles[*id]).collect();
+            let mut row = ImageData::from_pixels(&pixels, w, 1);
+            palette::remap(&mut row, palette);
+            stream
+                .write_row(row.pixels())
+                .map_err(|e| format!("failed to save {out_path}: {e}"))
+        });
+    if let Some(progress) = progress.as_mut() {
+        progress.finish();
+    }
+    result?;
+    stream
+        .finish()
+        .map_err(|e| format!("failed to save {out_path}: {e}"))?;
+
+    let mut manifest = create_manifest(args, wfc_parameters, seed, w, h);
+    //Bands never wrap around vertically
+    manifest.boundary = boundary_name(wfc_parameters.wfc_boundary.finite_y()).to_string();
+    manifest.save(&manifest_path(&out_path))?;
+    eprintln!("saved output to {out_path}");
+    Ok(())
+}
 
-    canvas.present();
+fn boundary_name(boundary: wfc::Boundary) -> &'static str {
+    match boundary {
+        wfc::Boundary::Wrap => "wrap",
+        wfc::Boundary::Finite => "finite",
+        wfc::Boundary::WrapY => "wrap_y",
+        wfc::Boundary::WrapX => "wrap_x",
+        wfc::Boundary::Frames(_) => "frames",
+    }
+}
 
-    Ok(())
+//The boundary is taken from the parameters the output was generated with
+fn create_manifest(
+    args: &GenerateArgs,
+    wfc_parameters: &wfc::WFCParameters,
+    seed: u64,
+    w: usize,
+    h: usize,
+) -> Manifest {
+    Manifest {
+        input: args.input.input.clone(),
+        mask: args.mask.clone(),
+        palette: args.palette.clone(),
+        guide: args.guide.clone(),
+        seed,
+        width: w,
+        height: h,
+        render_size: args.render_size(),
+        tile_size: args.input.n(),
+        color_tolerance: args.input.tolerance,
+        rotation_weight: args.input.rotations.then_some(args.input.rotation_weight),
+        hex: args.input.hex,
+        finite_input: args.input.finite_input,
+        frequency_alpha: args.input.frequency_alpha,
+        frequency_add: args.input.frequency_add,
+        fill_contradictions: args.fill_contradictions,
+        band_height: args.band_height,
+        symmetric: args.input.rotations,
+        boundary: boundary_name(wfc_parameters.wfc_boundary).to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    }
 }
 
-fn main_loop(data: &ImageData, wfc_parameters: &wfc::WFCParameters) -> Result<(), String> {
-    //Init sdl
-    let ctx = sdl2::init()?;
-    let video_subsystem = ctx.video()?;
-    let window = video_subsystem
-        .window("wave function collapse demo", 800, 640)
-        .position_centered()
-        .resizable()
-        .build()
-        .map_err(|e| e.to_string())?;
-    let mut canvas = window
-        .into_canvas()
-        .present_vsync()
-        .build()
-        .map_err(|e| e.to_string())?;
-    let texture_creator = canvas.texture_creator();
-    let mut event_pump = ctx.event_pump()?;
-
-    let mut events = ProcessedEvents { can_quit: false };
-
-    let input_texture = texture_from_image(data, &texture_creator)?;
-    let w = 64;
-    let h = 64;
-    let mut output_image = ImageData::new(w, h);
-    let mut output_texture = texture_from_image(&output_image, &texture_creator)?;
-    let mut current_frame = 0;
-
-    let mut rng = rand::thread_rng();
-    let mut wfc_state = wfc::WFCState::new(
-        w,
-        h,
-        &wfc_parameters.wfc_tiles,
-        &wfc_parameters.wfc_frequency,
+fn inspect(inspect_args: &InspectArgs, data: &ImageData) -> Result<(), String> {
+    let args = &inspect_args.input;
+    let options = wfc::ExtractionOptions {
+        keep_patterns: inspect_args.atlas.is_some(),
+        ..args.extraction_options()
+    };
+    let wfc_parameters =
+        wfc::WFCParameters::from_image_data_with_options(data, args.n(), &options)?;
+    let tile_count = wfc_parameters.wfc_tiles.len();
+    println!("input: {} ({}x{})", args.input, data.width(), data.height());
+    println!("tile size: {}", args.tile_size);
+    println!("tiles: {tile_count}");
+    println!(
+        "frequency: min {}, max {}",
+        wfc_parameters.wfc_frequency.iter().min().unwrap_or(&0),
+        wfc_parameters.wfc_frequency.iter().max().unwrap_or(&0)
     );
-    while !events.can_quit {
-        if current_frame % SPEED == 0 {
-            display_loop(&mut canvas, &input_texture, &output_texture)?;
-        }
-
-        if !wfc_state.done() {
-            match wfc_parameters.step(w, h, &mut wfc_state, &mut rng) {
-                Ok(()) => {}
-                Err(msg) => {
-                    eprintln!("{msg}");
-                    //Reset the state
-                    wfc_state = wfc::WFCState::new(
-                        w,
-                        h,
-                        &wfc_parameters.wfc_tiles,
-                        &wfc_parameters.wfc_frequency,
-                    );
+    for direction in 0..wfc_parameters.wfc_rules.directions() {
+        let mut allowed = 0;
+        for id1 in 0..tile_count {
+            for id2 in 0..tile_count {
+                if wfc_parameters.wfc_rules.okay(direction, id1, id2) {
+                    allowed += 1;
                 }
             }
-
-            if current_frame % SPEED == 0 {
-                wfc::copy_superpositions_to_grid(
-                    output_image.pixels_mut(),
-                    wfc_state.superpositions(),
-                    &wfc_parameters.wfc_tiles,
-                );
-
-                output_texture = texture_from_image(&output_image, &texture_creator)?;
+        }
+        println!(
+            "direction {direction}: {:.2} allowed neighbors per tile",
+            allowed as f32 / tile_count.max(1) as f32
+        );
+    }
+    let difficulty = wfc_parameters.difficulty_estimate();
+    println!(
+        "difficulty: {:.2} of 1 (rule density {:.2}, frequency evenness {:.2})",
+        difficulty.score, difficulty.rule_density, difficulty.frequency_evenness
+    );
+    match wfc_parameters.validate() {
+        Ok(()) => println!("no issues found"),
+        Err(issues) => {
+            for issue in issues {
+                println!("issue: {issue}");
             }
         }
+    }
+    if let Some(path) = &inspect_args.atlas {
+        wfc_parameters
+            .tile_atlas(ATLAS_COLUMNS)?
+            .save_png(path)
+            .map_err(|e| format!("failed to save {path}: {e}"))?;
+        println!("saved the tiles to {path}");
+    }
+    Ok(())
+}
 
-        current_frame += 1;
+fn dump_rules(args: &InputArgs, data: &ImageData) -> Result<(), String> {
+    let wfc_parameters = wfc::WFCParameters::from_image_data_with_options(
+        data,
+        args.n(),
+        &args.extraction_options(),
+    )?;
+    let graphs: Vec<Vec<Vec<usize>>> = (0..wfc_parameters.wfc_rules.directions())
+        .map(|direction| wfc_parameters.adjacency_graph(direction))
+        .collect();
+    for id1 in 0..wfc_parameters.wfc_tiles.len() {
+        println!(
+            "tile {id1}: color {:#010x}, frequency {}",
+            wfc_parameters.wfc_tiles[id1], wfc_parameters.wfc_frequency[id1]
+        );
+        for (direction, graph) in graphs.iter().enumerate() {
+            let allowed: Vec<String> = graph[id1].iter().map(|id2| id2.to_string()).collect();
+            println!("  direction {direction}: {}", allowed.join(" "));
+        }
+    }
+    Ok(())
+}
 
-        events = process_events(&mut event_pump);
+fn validate(args: &ValidateArgs, data: &ImageData) -> Result<(), String> {
+    let mut wfc_parameters = wfc::WFCParameters::from_image_data_with_options(
+        data,
+        args.input.n(),
+        &args.input.extraction_options(),
+    )?;
+    //The edges of the image are only checked against each other if the
+    //output wraps around
+    wfc_parameters.wfc_boundary = args.input.output_boundary();
+    let image = load_png(&args.image)?;
+    let violations = wfc_parameters.validate_image(&image);
+    for (x, y, direction) in &violations {
+        let (dx, dy) = wfc_parameters.wfc_rules.offsets()[*direction];
+        println!("({x}, {y}): not allowed next to the pixel at offset ({dx}, {dy})");
+    }
+    if !violations.is_empty() {
+        return Err(format!(
+            "{} does not follow the rules of {}, {} pairs of pixels are not allowed",
+            args.image,
+            args.input.input,
+            violations.len()
+        ));
     }
+    println!("{} follows the rules of {}", args.image, args.input.input);
+    Ok(())
+}
 
+fn diff(args: &DiffArgs) -> Result<(), String> {
+    let a = load_png(&args.a)?;
+    let b = load_png(&args.b)?;
+    let different = a
+        .diff(&b)
+        .map_err(|e| format!("{} and {}: {e}", args.a, args.b))?;
+    for (x, y) in &different {
+        println!(
+            "({x}, {y}): {:#010x} != {:#010x}",
+            a.get_pixel(*x, *y),
+            b.get_pixel(*x, *y)
+        );
+    }
+    if let Some(out_path) = &args.out {
+        a.highlight_diff(&different)
+            .save_png(out_path)
+            .map_err(|e| format!("failed to save {out_path}: {e}"))?;
+        eprintln!("saved the differences to {out_path}");
+    }
+    if !different.is_empty() {
+        return Err(format!(
+            "{} pixels of {} and {} are different",
+            different.len(),
+            args.a,
+            args.b
+        ));
+    }
+    println!("{} and {} are the same", args.a, args.b);
     Ok(())
 }
 
-fn parse_args(args: Vec<String>) -> (String, isize) {
-    let mut path = "".to_string();
-    let mut n = 3;
+fn check_file(path: &str) -> Result<(), String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("{path} does not exist!"));
+    }
+    Ok(())
+}
 
-    for arg in &args {
-        let tmp_n: isize = arg.parse().unwrap_or(-1);
+fn load_png(path: &str) -> Result<ImageData, String> {
+    check_file(path)?;
+    ImageData::load_png(path).map_err(|e| format!("failed to open file: {path}\n{e}"))
+}
 
-        if tmp_n > 4 {
-            continue;
-        }
+//The input can also be a .txt file where each character is a pixel
+fn load_input(path: &str) -> Result<ImageData, String> {
+    check_file(path)?;
+    ImageData::load(path).map_err(|e| format!("failed to open file: {path}\n{e}"))
+}
 
-        if tmp_n > 0 {
-            n = tmp_n;
-        } else {
-            path = (*arg).clone();
-        }
+fn generate(args: &GenerateArgs) -> Result<(), String> {
+    if args.animate {
+        return generate_animation(args);
     }
 
-    let file_path = Path::new(&path);
-    if path.is_empty() {
-        eprintln!("No input file specified!");
-        std::process::exit(1);
+    let start = Instant::now();
+    let data = load_input(&args.input.input)?;
+    //Transparent pixels in the mask are left blank in the output
+    let mask = match &args.mask {
+        Some(mask_path) => Some(load_png(mask_path)?),
+        None => None,
+    };
+    let extraction_start = Instant::now();
+    let mut wfc_parameters = load_parameters(&data, &args.input)?;
+    let extraction = extraction_start.elapsed();
+    wfc_parameters.wfc_measure_timings = args.timings;
+    if let Some(tile) = args.fill_contradictions {
+        wfc_parameters.wfc_contradiction_policy = wfc::ContradictionPolicy::Fill(tile);
     }
-    if !file_path.is_file() {
-        eprintln!("{path} does not exist!");
-        std::process::exit(1);
+    if let Some(guide_path) = &args.guide {
+        wfc_parameters.wfc_guide = Some(wfc::Guide {
+            image: load_png(guide_path)?,
+            strength: GUIDE_STRENGTH,
+        });
     }
+    check_parameters(&wfc_parameters)?;
 
-    (path, n)
-}
+    //Colors of the output are replaced using the palette before it is shown
+    let palette = match &args.palette {
+        Some(palette_path) => palette::load_palette(palette_path)?,
+        None => Palette::new(),
+    };
 
-fn main() -> Result<(), String> {
-    //Get command line arguments
-    let args: Vec<String> = env::args().collect();
-
-    //If we have no arguments, exit program
-    if args.len() == 1 {
-        eprintln!("usage: {} [input file] [n]", args[0]);
-        std::process::exit(1);
+    if let Some(runs) = args.stress {
+        stress(&wfc_parameters, args, runs, mask.as_ref());
+        return Ok(());
     }
 
-    //Otherwise, attempt to open the png file that was provided as an argument
-    let parsed_args = parse_args(args);
-    let img_data = ImageData::load_png(&parsed_args.0);
+    if args.headless {
+        let mut timings = wfc::Timings::default();
+        let result =
+            generate_headless(&wfc_parameters, args, mask.as_ref(), &palette, &mut timings);
+        if args.timings {
+            eprintln!(
+                "extraction {extraction:.1?}, collapse {:.1?} (propagate {:.1?}, reaching up to {} cells away), total {:.1?}",
+                timings.collapse,
+                timings.propagate,
+                timings.propagation_reach,
+                start.elapsed()
+            );
+        }
+        return result;
+    }
 
-    match img_data {
-        Ok(data) => {
-            let wfc_parameters = wfc::WFCParameters::from_image_data(&data, parsed_args.1);
+    #[cfg(feature = "gui")]
+    return gui::main_loop(&data, wfc_parameters, args, mask.as_ref(), &palette);
+    #[cfg(not(feature = "gui"))]
+    Err("built without the gui feature, use --headless".to_string())
+}
 
-            /*let start = ::std::time::Instant::now();
-            let _generated = wfc_parameters.generate_grid(64, 64).unwrap();
-            let seconds = start.elapsed().as_secs_f64();
-            eprintln!("Took {} sec to generate image", seconds);*/
+fn main() -> Result<(), String> {
+    //Get command line arguments
+    let cli = cli::parse(env::args().collect()).inspect_err(|msg| eprintln!("{msg}"))?;
 
-            main_loop(&data, &wfc_parameters)?;
+    let result = match &cli.command {
+        Command::Generate(args) => generate(args),
+        Command::Inspect(args) => {
+            load_input(&args.input.input).and_then(|data| inspect(args, &data))
         }
-        Err(msg) => {
-            eprintln!("failed to open file: {}", parsed_args.0);
-            eprintln!("{msg}");
-            return Err(msg);
+        Command::DumpRules(args) => {
+            load_input(&args.input).and_then(|data| dump_rules(args, &data))
         }
-    }
+        Command::Validate(args) => {
+            load_input(&args.input.input).and_then(|data| validate(args, &data))
+        }
+        Command::Diff(args) => diff(args),
+    };
 
-    Ok(())
+    if let Err(msg) = &result {
+        eprintln!("{msg}");
+    }
+    result
 }
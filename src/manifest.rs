@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+//Everything needed to generate an output again, saved next to the output
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Manifest {
+    pub input: String,
+    pub mask: Option<String>,
+    #[serde(default)]
+    pub palette: Option<String>,
+    #[serde(default)]
+    pub guide: Option<String>,
+    pub seed: u64,
+    pub width: usize,
+    pub height: usize,
+    //Size the output was scaled to when saved
+    #[serde(default)]
+    pub render_size: Option<(usize, usize)>,
+    pub tile_size: isize,
+    pub color_tolerance: u32,
+    //Weight of the rotated tiles if rotations were used
+    #[serde(default)]
+    pub rotation_weight: Option<u32>,
+    //Whether the input and output are grids of hexagons
+    #[serde(default)]
+    pub hex: bool,
+    //Whether only the tiles that fit inside the input were sampled
+    #[serde(default)]
+    pub finite_input: bool,
+    //Smoothing of the frequencies used when collapsing
+    #[serde(default)]
+    pub frequency_alpha: Option<f32>,
+    #[serde(default)]
+    pub frequency_add: Option<u32>,
+    //Tile that cells which ran out of tiles were filled with
+    #[serde(default)]
+    pub fill_contradictions: Option<usize>,
+    //Rows generated at a time if the output was streamed in bands
+    #[serde(default)]
+    pub band_height: Option<u32>,
+    //Whether the rotations of the tiles were added to the tiles
+    pub symmetric: bool,
+    //How the edges of the output were handled, wrap, finite, wrap_y, wrap_x
+    //or frames
+    pub boundary: String,
+    pub version: String,
+}
+
+//The manifest for output.png is output.json
+pub fn manifest_path(output_path: &str) -> String {
+    Path::new(output_path)
+        .with_extension("json")
+        .to_string_lossy()
+        .to_string()
+}
+
+impl Manifest {
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    #[allow(dead_code)]
+    pub fn from_manifest(path: &str) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| format!("{path}: {e}"))
+    }
+
+    //Command line arguments that generate the same output
+    #[allow(dead_code)]
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "generate".to_string(),
+            self.input.clone(),
+            self.tile_size.to_string(),
+            "--seed".to_string(),
+            self.seed.to_string(),
+            "--tolerance".to_string(),
+            self.color_tolerance.to_string(),
+            "--width".to_string(),
+            self.width.to_string(),
+            "--height".to_string(),
+            self.height.to_string(),
+        ];
+        if let Some((render_width, render_height)) = self.render_size {
+            args.push("--render-width".to_string());
+            args.push(render_width.to_string());
+            args.push("--render-height".to_string());
+            args.push(render_height.to_string());
+        }
+        if let Some(rotation_weight) = self.rotation_weight {
+            args.push("--rotations".to_string());
+            args.push("--rotation-weight".to_string());
+            args.push(rotation_weight.to_string());
+        }
+        if self.hex {
+            args.push("--hex".to_string());
+        }
+        if self.finite_input {
+            args.push("--finite-input".to_string());
+        }
+        if self.boundary == "finite" {
+            args.push("--finite-output".to_string());
+        }
+        if let Some(alpha) = self.frequency_alpha {
+            args.push("--frequency-alpha".to_string());
+            args.push(alpha.to_string());
+        }
+        if let Some(k) = self.frequency_add {
+            args.push("--frequency-add".to_string());
+            args.push(k.to_string());
+        }
+        if let Some(tile) = self.fill_contradictions {
+            args.push("--fill-contradictions".to_string());
+            args.push(tile.to_string());
+        }
+        //Streaming also needs --headless and --out
+        if let Some(band_height) = self.band_height {
+            args.push("--band-height".to_string());
+            args.push(band_height.to_string());
+        }
+        if let Some(palette) = &self.palette {
+            args.push("--palette".to_string());
+            args.push(palette.clone());
+        }
+        if let Some(guide) = &self.guide {
+            args.push("--guide".to_string());
+            args.push(guide.clone());
+        }
+        if let Some(mask) = &self.mask {
+            args.push("--mask".to_string());
+            args.push(mask.clone());
+        }
+        args
+    }
+}
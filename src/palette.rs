@@ -0,0 +1,59 @@
+use crate::image_data::ImageData;
+use std::{collections::HashMap, fs};
+
+//Maps colors in the output to replacement colors
+pub type Palette = HashMap<u32, u32>;
+
+//Colors in palette files are written as 0xAARRGGBB but pixels are stored
+//with red in the lowest byte, so red and blue are swapped
+fn argb_to_pixel(argb: u32) -> u32 {
+    (argb & 0xff00ff00) | ((argb >> 16) & 0xff) | ((argb & 0xff) << 16)
+}
+
+fn parse_color(color: &str) -> Result<u32, String> {
+    let hex = color
+        .strip_prefix("0x")
+        .or_else(|| color.strip_prefix("0X"))
+        .unwrap_or(color);
+    u32::from_str_radix(hex, 16)
+        .map(argb_to_pixel)
+        .map_err(|e| format!("invalid color {color}: {e}"))
+}
+
+//Each line of the file is a color followed by the color that replaces it,
+//blank lines and lines starting with # are ignored
+pub fn load_palette(path: &str) -> Result<Palette, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("failed to open {path}: {e}"))?;
+    let mut palette = Palette::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let colors: Vec<&str> = line.split_whitespace().collect();
+        if colors.len() != 2 {
+            return Err(format!(
+                "{path}:{}: expected two colors but found {}",
+                line_number + 1,
+                colors.len()
+            ));
+        }
+
+        let from =
+            parse_color(colors[0]).map_err(|e| format!("{path}:{}: {e}", line_number + 1))?;
+        let to = parse_color(colors[1]).map_err(|e| format!("{path}:{}: {e}", line_number + 1))?;
+        palette.insert(from, to);
+    }
+    Ok(palette)
+}
+
+//Replaces every color in the image that is in the palette, other colors
+//are left unchanged
+pub fn remap(image: &mut ImageData, palette: &Palette) {
+    for pixel in image.pixels_mut() {
+        if let Some(color) = palette.get(pixel) {
+            *pixel = *color;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::{io::Write, time::Instant};
+
+//Width the line is padded to so a shorter line covers the one before it
+const LINE_WIDTH: usize = 40;
+
+//Shows how much of a headless generation is done as a percentage on a line
+//that is rewritten in place, along with a guess of how long is left
+pub struct Progress<W: Write> {
+    out: W,
+    //Number of cells (or rows) to finish
+    total: usize,
+    start: Instant,
+    //Last percentage shown, the line is only rewritten when it changes
+    shown: Option<usize>,
+}
+
+impl<W: Write> Progress<W> {
+    pub fn new(out: W, total: usize) -> Self {
+        Self {
+            out,
+            total,
+            start: Instant::now(),
+            shown: None,
+        }
+    }
+
+    //Called with the number of cells left each time one is done
+    pub fn step(&mut self, remaining: usize) {
+        let done = self.total.saturating_sub(remaining);
+        let percent = (done * 100).checked_div(self.total).unwrap_or(100);
+        if self.shown == Some(percent) {
+            return;
+        }
+        self.shown = Some(percent);
+
+        let line = if done == 0 || remaining == 0 {
+            format!("collapsing {percent:>3}%")
+        } else {
+            //Assumes the cells that are left take as long as the others did
+            let left = self.start.elapsed().mul_f64(remaining as f64 / done as f64);
+            format!("collapsing {percent:>3}% (about {}s left)", left.as_secs())
+        };
+        //Failing to show the progress is not worth stopping the generation
+        let _ = write!(self.out, "\r{line:<LINE_WIDTH$}");
+        let _ = self.out.flush();
+    }
+
+    //Ends the line so that the next message starts on a line of its own
+    pub fn finish(&mut self) {
+        if self.shown.is_some() {
+            let _ = writeln!(self.out);
+        }
+    }
+
+    //Last percentage that was shown
+    #[allow(dead_code)]
+    pub fn shown(&self) -> Option<usize> {
+        self.shown
+    }
+
+    #[allow(dead_code)]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
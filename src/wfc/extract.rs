@@ -0,0 +1,402 @@
+use super::{neighbors, Boundary, RuleTable, Tile, WFCParameters, HEX_OFFSETS, SQUARE_OFFSETS};
+use crate::image_data::ImageData;
+use std::collections::HashMap;
+
+//Rounds each color channel down to a multiple of tolerance + 1 so that
+//colors that are close to each other become the same, alpha is unchanged
+fn quantize_color(pixel: u32, tolerance: u32) -> u32 {
+    if tolerance == 0 {
+        return pixel;
+    }
+
+    let step = tolerance + 1;
+    let mut quantized = pixel & 0xff000000;
+    for shift in [0, 8, 16] {
+        let channel = (pixel >> shift) & 0xff;
+        quantized |= (channel / step * step) << shift;
+    }
+    quantized
+}
+
+pub fn sample_square(
+    data: &ImageData,
+    tile_sz: isize,
+    tile_x: isize,
+    tile_y: isize,
+    color_tolerance: u32,
+) -> Tile {
+    let mut tile = vec![];
+    sample_square_into(&mut tile, data, tile_sz, tile_x, tile_y, color_tolerance);
+    tile
+}
+
+//Same as sample_square but reuses the memory of tile
+fn sample_square_into(
+    tile: &mut Tile,
+    data: &ImageData,
+    tile_sz: isize,
+    tile_x: isize,
+    tile_y: isize,
+    color_tolerance: u32,
+) {
+    tile.clear();
+    tile.resize((tile_sz * tile_sz) as usize, 0);
+
+    for y in tile_y..(tile_y + tile_sz) {
+        for x in tile_x..(tile_x + tile_sz) {
+            let ind = ((x - tile_x) + (y - tile_y) * tile_sz) as usize;
+            tile[ind] = quantize_color(data.get_pixel_wrap(x, y), color_tolerance);
+        }
+    }
+}
+
+//Rotates a square tile by 90 degrees clockwise
+#[allow(dead_code)]
+pub fn rotate_tile(tile: &[u32], tile_sz: isize) -> Tile {
+    let mut rotated = vec![];
+    rotate_tile_into(tile, tile_sz, &mut rotated);
+    rotated
+}
+
+//Same as rotate_tile but reuses the memory of rotated
+fn rotate_tile_into(tile: &[u32], tile_sz: isize, rotated: &mut Tile) {
+    let n = tile_sz as usize;
+    rotated.clear();
+    rotated.resize(n * n, 0);
+    for y in 0..n {
+        for x in 0..n {
+            rotated[y * n + x] = tile[(n - 1 - x) * n + y];
+        }
+    }
+}
+
+//Pixels of a tile that overlap with its neighbor at the offset, if second is
+//true these are the pixels of the neighbor instead. Two tiles without
+//transparent pixels match exactly when their strips are equal
+fn edge_strip(
+    tile: &Tile,
+    offset_x: isize,
+    offset_y: isize,
+    tile_sz: isize,
+    second: bool,
+) -> Vec<u32> {
+    let mut strip = vec![];
+    for y in 0..tile_sz {
+        for x in 0..tile_sz {
+            let other_x = x - offset_x;
+            let other_y = y - offset_y;
+
+            if other_x < 0 || other_y < 0 || other_x >= tile_sz || other_y >= tile_sz {
+                continue;
+            }
+
+            let index = if second {
+                other_y * tile_sz + other_x
+            } else {
+                y * tile_sz + x
+            };
+            strip.push(tile[index as usize]);
+        }
+    }
+    strip
+}
+
+fn has_wildcard(strip: &[u32]) -> bool {
+    strip.iter().any(|color| color >> 24 == 0)
+}
+
+//Finds which tiles can be next to each other, the tiles are grouped by their
+//edges so only tiles with the same edge (or a transparent pixel in the edge)
+//have to be compared with tiles_match
+//Adds the rules for every direction of the table, the table starts out
+//with no tiles allowed next to each other
+fn find_rules(tiles: &[Tile], tile_sz: isize, rules: &mut RuleTable) {
+    let offsets = rules.offsets().to_vec();
+    for (direction, offset) in offsets.iter().enumerate() {
+        let mut edges = HashMap::<Vec<u32>, Vec<usize>>::new();
+        let mut wildcards = vec![];
+        for (id2, tile2) in tiles.iter().enumerate() {
+            let strip = edge_strip(tile2, offset.0, offset.1, tile_sz, true);
+            if has_wildcard(&strip) {
+                wildcards.push(id2);
+            } else {
+                edges.entry(strip).or_default().push(id2);
+            }
+        }
+
+        for (id1, tile1) in tiles.iter().enumerate() {
+            let strip = edge_strip(tile1, offset.0, offset.1, tile_sz, false);
+            if has_wildcard(&strip) {
+                for (id2, tile2) in tiles.iter().enumerate() {
+                    if tiles_match(tile1, tile2, offset.0, offset.1, tile_sz) {
+                        rules.add_rule(direction, id1, id2);
+                    }
+                }
+                continue;
+            }
+
+            let same_edge = edges.get(&strip).map(|ids| ids.as_slice()).unwrap_or(&[]);
+            for id2 in same_edge.iter().chain(wildcards.iter()) {
+                if tiles_match(tile1, &tiles[*id2], offset.0, offset.1, tile_sz) {
+                    rules.add_rule(direction, id1, *id2);
+                }
+            }
+        }
+    }
+}
+
+pub fn tiles_match(
+    tile1: &Tile,
+    tile2: &Tile,
+    offset_x: isize,
+    offset_y: isize,
+    tile_sz: isize,
+) -> bool {
+    for y in 0..tile_sz {
+        for x in 0..tile_sz {
+            let offset_x = x - offset_x;
+            let offset_y = y - offset_y;
+
+            if offset_x < 0 || offset_y < 0 || offset_x >= tile_sz || offset_y >= tile_sz {
+                continue;
+            }
+
+            let index = (y * tile_sz + x) as usize;
+            let offset_index = (offset_y * tile_sz + offset_x) as usize;
+
+            //Fully transparent pixels are wildcards that match any color
+            if tile1[index] >> 24 == 0 || tile2[offset_index] >> 24 == 0 {
+                continue;
+            }
+
+            if tile1[index] != tile2[offset_index] {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+//Options for how the tiles are sampled from the input image
+#[derive(Clone, Default, Debug)]
+pub struct ExtractionOptions {
+    //Colors whose channels differ by at most about this much are treated as
+    //the same color, useful for noisy inputs that would otherwise have a lot
+    //of almost identical tiles
+    pub color_tolerance: u32,
+    //Also add the 90, 180 and 270 degree rotations of every sampled tile
+    pub rotations: bool,
+    //Weight of a rotated tile in percent of the tile it was rotated from, the
+    //tiles that were actually in the input get a weight of 100 so lowering
+    //this makes the output prefer the orientation seen in the input while
+    //still allowing rotations. Since tiles are picked with a probability
+    //proportional to their frequency a rotated tile with a weight of 50 is
+    //picked half as often as the same tile would be if it was in the input
+    pub rotation_weight: u32,
+    //Keep every sampled tile and not only its top left color, so that
+    //tile_pattern can show what each tile looks like
+    pub keep_patterns: bool,
+    //Treat the input as a grid of hexagons in axial coordinates (see
+    //HEX_OFFSETS), each color is a tile and the tile size and rotations are
+    //ignored
+    pub hex: bool,
+    //With Finite only the tiles that fit inside the input are sampled (and
+    //with hex the pixels on opposite edges are not neighbors), so patterns
+    //that only appear where the input wraps around are not learned
+    pub boundary: Boundary,
+}
+
+//Weight of a tile sampled from the input when rotations are added
+const ORIGINAL_WEIGHT: u32 = 100;
+
+impl WFCParameters {
+    //Sample all possible tile_sz x tile_sz square regions of the image
+    //and count their frequency and what they are adjacent to,
+    //also assign a usize id to each one
+    #[allow(dead_code)]
+    pub fn from_image_data(data: &ImageData, tile_sz: isize) -> Result<Self, String> {
+        Self::from_image_data_with_options(data, tile_sz, &ExtractionOptions::default())
+    }
+
+    pub fn from_image_data_with_options(
+        data: &ImageData,
+        tile_sz: isize,
+        options: &ExtractionOptions,
+    ) -> Result<Self, String> {
+        Self::from_image_data_transformed(data, tile_sz, options, |_| {})
+    }
+
+    //Same as from_image_data_with_options but transform can change the
+    //pixels of each tile as it is sampled, before it is compared with the
+    //other tiles, so tiles that it makes the same become one tile. It has
+    //to leave tile_sz x tile_sz pixels in the tile, the rotations are made
+    //from the transformed tile and with hex each tile is a single pixel
+    pub fn from_image_data_transformed(
+        data: &ImageData,
+        tile_sz: isize,
+        options: &ExtractionOptions,
+        mut transform: impl FnMut(&mut Tile),
+    ) -> Result<Self, String> {
+        if options.hex {
+            return Self::from_hex_image(data, options, transform);
+        }
+
+        //Tiles larger than the input wrap around it so much that almost
+        //every tile is different and the rules become too strict to solve
+        let min_sz = data.width().min(data.height());
+        if tile_sz < 1 || tile_sz as usize > min_sz {
+            return Err(format!(
+                "tile size {tile_sz} does not fit in the {}x{} input, it must be between 1 and {min_sz}",
+                data.width(),
+                data.height()
+            ));
+        }
+
+        let variants = if options.rotations { 4 } else { 1 };
+        check_memory(
+            tile_bytes(data.width(), data.height(), tile_sz as usize, variants),
+            "sampling the tiles",
+        )?;
+
+        //Each position is sampled into the same buffers and a tile is only
+        //copied the first time it is seen, the map owns every tile until
+        //they are moved out in the order of their ids
+        let mut tile_ids = HashMap::<Tile, usize>::new();
+        let mut frequency = Vec::<u32>::new();
+        let mut tile = Tile::new();
+        let mut rotated = Tile::new();
+        let rotations = if options.rotations { 4 } else { 1 };
+        //A finite input is only sampled where the whole tile fits in it
+        let positions = |size: usize, wrap: bool| {
+            if wrap {
+                size
+            } else {
+                size + 1 - tile_sz as usize
+            }
+        };
+        let positions_x = positions(data.width(), options.boundary.wraps_x());
+        let positions_y = positions(data.height(), options.boundary.wraps_y());
+        for y in 0..positions_y {
+            for x in 0..positions_x {
+                sample_square_into(
+                    &mut tile,
+                    data,
+                    tile_sz,
+                    x as isize,
+                    y as isize,
+                    options.color_tolerance,
+                );
+                transform(&mut tile);
+
+                for rotation in 0..rotations {
+                    let weight = if !options.rotations {
+                        1
+                    } else if rotation == 0 {
+                        ORIGINAL_WEIGHT
+                    } else {
+                        rotate_tile_into(&tile, tile_sz, &mut rotated);
+                        std::mem::swap(&mut tile, &mut rotated);
+                        options.rotation_weight
+                    };
+
+                    match tile_ids.get(tile.as_slice()) {
+                        Some(i) => {
+                            frequency[*i] += weight;
+                        }
+                        None => {
+                            tile_ids.insert(tile.clone(), frequency.len());
+                            frequency.push(weight);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut tiles = vec![Tile::new(); tile_ids.len()];
+        for (tile, id) in tile_ids {
+            tiles[id] = tile;
+        }
+
+        check_memory(
+            RuleTable::bytes(tiles.len(), SQUARE_OFFSETS.len()),
+            &format!("the rules for {} tiles", tiles.len()),
+        )?;
+        let mut rules = RuleTable::new(tiles.len(), &SQUARE_OFFSETS)?;
+        find_rules(&tiles, tile_sz, &mut rules);
+
+        let colors = tiles.iter().map(|tile| tile[0]).collect();
+        Ok(Self {
+            wfc_patterns: options.keep_patterns.then_some(tiles),
+            ..Self::from_parts(colors, rules, frequency, tile_sz as usize)
+        })
+    }
+
+    //Each pixel of the input is a hexagon and each color is a tile, two
+    //colors can be next to each other in a direction if they are next to
+    //each other that way somewhere in the input (wrapping around the edges
+    //unless the boundary of the options is finite)
+    fn from_hex_image(
+        data: &ImageData,
+        options: &ExtractionOptions,
+        mut transform: impl FnMut(&mut Tile),
+    ) -> Result<Self, String> {
+        let mut tile_ids = HashMap::<u32, usize>::new();
+        let mut tiles = vec![];
+        let mut frequency = vec![];
+        let mut ids = Vec::with_capacity(data.pixels().len());
+        let mut tile = Tile::new();
+        for pixel in data.pixels() {
+            tile.clear();
+            tile.push(quantize_color(*pixel, options.color_tolerance));
+            transform(&mut tile);
+            let color = tile.first().copied().unwrap_or(0);
+            let id = *tile_ids.entry(color).or_insert_with(|| {
+                tiles.push(color);
+                frequency.push(0);
+                tiles.len() - 1
+            });
+            frequency[id] += 1;
+            ids.push(id);
+        }
+
+        check_memory(
+            RuleTable::bytes(tiles.len(), HEX_OFFSETS.len()),
+            &format!("the rules for {} tiles", tiles.len()),
+        )?;
+        let (w, h) = (data.width(), data.height());
+        let mut rules = RuleTable::new(tiles.len(), &HEX_OFFSETS)?;
+        for (index, id) in ids.iter().enumerate() {
+            for (direction, adj_x, adj_y) in
+                neighbors(&HEX_OFFSETS, index % w, index / w, w, h, options.boundary)
+            {
+                rules.add_rule(direction, *id, ids[adj_x + adj_y * w]);
+            }
+        }
+
+        Ok(Self::from_parts(tiles, rules, frequency, 1))
+    }
+}
+
+//Extracting the tiles or the rules fails instead of allocating more than this
+const MAX_EXTRACTION_BYTES: u128 = 1 << 30;
+
+//Most bytes the tiles sampled from a w x h input can need, each position can
+//give a different tile (per variant) and every tile is stored twice, once in
+//the list of tiles and once as the key that finds its id
+pub fn tile_bytes(w: usize, h: usize, tile_sz: usize, variants: usize) -> u128 {
+    let tiles = (w as u128 * h as u128).saturating_mul(variants as u128);
+    tiles.saturating_mul(tile_sz as u128 * tile_sz as u128 * 4 * 2)
+}
+
+//Fails with a description of what needs too much memory
+pub fn check_memory(bytes: u128, what: &str) -> Result<(), String> {
+    if bytes > MAX_EXTRACTION_BYTES {
+        return Err(format!(
+            "{what} would need about {} MB, more than the limit of {} MB, try a smaller crop of the input or a smaller tile size",
+            bytes >> 20,
+            MAX_EXTRACTION_BYTES >> 20
+        ));
+    }
+    Ok(())
+}
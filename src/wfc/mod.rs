@@ -0,0 +1,1638 @@
+use crate::image_data::{u32_to_color, wrap_value, ImageData};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+mod extract;
+mod random;
+mod render;
+mod rules;
+mod rules_bin;
+mod state;
+
+pub use extract::*;
+pub use random::*;
+pub use render::*;
+pub use rules::*;
+pub use state::*;
+
+pub type Tile = Vec<u32>;
+//Called with the superpositions and the number of tiles left to collapse
+pub type StepCallback<'a> = &'a mut dyn FnMut(&[Superposition], usize);
+//Called each time generating runs into a contradiction, decides what is done
+//about it
+pub type ContradictionCallback<'a> = &'a mut dyn FnMut(&Contradiction) -> ContradictionAction;
+//Directions of the four neighbors of a square grid that rules are learned
+//for, everything after learning uses the offsets of the rule table instead
+pub const SQUARE_OFFSETS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+//Directions of the six neighbors of a hexagon in axial coordinates, where x
+//is q and y is r so each row is shifted half a hexagon to the right of the
+//row above it. The solver only follows the offsets of the rules so a rule
+//table with these offsets generates a hexagonal grid
+pub const HEX_OFFSETS: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+//Error returned by the generations that can be cancelled, so that a
+//cancelled generation can be told apart from one that failed
+#[derive(PartialEq, Eq, Debug)]
+pub enum GenerateError {
+    //The cancel flag was set before the output was finished
+    Cancelled,
+    //Generation ran into a contradiction or could not be started
+    Failed(String),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::Cancelled => write!(f, "WFC Cancelled"),
+            GenerateError::Failed(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl From<String> for GenerateError {
+    fn from(msg: String) -> Self {
+        GenerateError::Failed(msg)
+    }
+}
+
+impl From<GenerateError> for String {
+    fn from(error: GenerateError) -> Self {
+        error.to_string()
+    }
+}
+
+//How the edges of an image or the output are handled
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Boundary {
+    //Tiles on opposite edges are next to each other
+    #[default]
+    Wrap,
+    //Tiles on the edges have fewer neighbors
+    Finite,
+    //Only the top and bottom edges are next to each other
+    WrapY,
+    //Only the left and right edges are next to each other
+    WrapX,
+    //The rows are this many frames of an animation stacked on top of each
+    //other, each frame wraps around on its own and an offset of a whole
+    //frame's height moves to the same cell of another frame, from the last
+    //frame back to the first
+    Frames(usize),
+}
+
+impl Boundary {
+    pub fn wraps_x(self) -> bool {
+        matches!(self, Boundary::Wrap | Boundary::WrapX | Boundary::Frames(_))
+    }
+
+    pub fn wraps_y(self) -> bool {
+        matches!(self, Boundary::Wrap | Boundary::WrapY | Boundary::Frames(_))
+    }
+
+    //The same boundary except that the left and right edges are finite
+    pub fn finite_x(self) -> Self {
+        if self.wraps_y() {
+            Boundary::WrapY
+        } else {
+            Boundary::Finite
+        }
+    }
+
+    //The same boundary except that the top and bottom edges are finite
+    pub fn finite_y(self) -> Self {
+        if self.wraps_x() {
+            Boundary::WrapX
+        } else {
+            Boundary::Finite
+        }
+    }
+}
+
+//Wraps value around size if wrap is set, otherwise values outside of
+//0..size are None
+fn wrap_axis(value: isize, size: usize, wrap: bool) -> Option<usize> {
+    if wrap {
+        Some(wrap_value(value, size))
+    } else if value < 0 || value >= size as isize {
+        None
+    } else {
+        Some(value as usize)
+    }
+}
+
+//Yields (direction, adj_x, adj_y) for each neighbor of (x, y), directions
+//index into offsets. Neighbors past an edge that doesn't wrap are skipped
+pub fn neighbors(
+    offsets: &[(isize, isize)],
+    x: usize,
+    y: usize,
+    w: usize,
+    h: usize,
+    boundary: Boundary,
+) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+    offsets
+        .iter()
+        .enumerate()
+        .filter_map(move |(direction, offset)| {
+            let adj_x = wrap_axis(x as isize + offset.0, w, boundary.wraps_x())?;
+            let adj_y = match boundary {
+                Boundary::Frames(frames) => frame_row(y, offset.1, h / frames, frames),
+                _ => wrap_axis(y as isize + offset.1, h, boundary.wraps_y())?,
+            };
+            Some((direction, adj_x, adj_y))
+        })
+}
+
+//Row offset_y rows away from y when the rows are frames of frame_h rows,
+//offsets of a frame_h or more move to another frame and the rest of the
+//offset wraps around inside of the frame
+fn frame_row(y: usize, offset_y: isize, frame_h: usize, frames: usize) -> usize {
+    let frame_h = frame_h.max(1);
+    let frame = (y / frame_h) as isize + offset_y / frame_h as isize;
+    let row = (y % frame_h) as isize + offset_y % frame_h as isize;
+    wrap_value(frame, frames) * frame_h + wrap_value(row, frame_h)
+}
+
+//How the tile frequencies are turned into weights when collapsing a tile,
+//the entropy of a cell is still worked out from the frequencies themselves
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WeightTransform {
+    //Weight is the frequency of the tile
+    Identity,
+    //Weight is max_frequency - frequency + 1, so rare tiles are favored
+    Inverse,
+    //Weight is the square root of the frequency (scaled by 16 to keep
+    //some precision), this flattens the differences between tiles
+    Sqrt,
+    //Weight is frequency^alpha (scaled by 16 like Sqrt), an alpha between 0
+    //and 1 flattens the frequencies toward uniform the closer it is to 0 so
+    //one very common tile doesn't take over the output
+    Power(f32),
+    //Weight is the frequency plus k (add-k smoothing), the larger k is the
+    //closer rare tiles get to the common ones
+    AddK(u32),
+}
+
+impl WeightTransform {
+    pub fn apply(&self, frequency: u32, max_frequency: u32) -> u32 {
+        match self {
+            WeightTransform::Identity => frequency,
+            WeightTransform::Inverse => max_frequency - frequency + 1,
+            WeightTransform::Sqrt => ((frequency as f32).sqrt() * 16.0).round() as u32,
+            WeightTransform::Power(alpha) => {
+                ((frequency as f32).powf(*alpha) * 16.0).round() as u32
+            }
+            WeightTransform::AddK(k) => frequency.saturating_add(*k),
+        }
+    }
+}
+
+//Where the first tile is collapsed, starting at the center makes the output
+//grow outwards from the middle of the image instead of a random spot
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StartPosition {
+    Random,
+    Center,
+    At(usize, usize),
+}
+
+impl StartPosition {
+    pub fn index(&self, w: usize, h: usize) -> Option<usize> {
+        match self {
+            StartPosition::Random => None,
+            StartPosition::Center => Some(w / 2 + (h / 2) * w),
+            StartPosition::At(x, y) => Some((x % w) + (y % h) * w),
+        }
+    }
+}
+
+//Returned by WFCParameters::stress_test
+#[derive(Clone, Copy, Default, Debug)]
+pub struct StressReport {
+    pub runs: u32,
+    //Runs that ran into a contradiction
+    pub failures: u32,
+    //Average number of steps of the runs that finished
+    pub average_steps: f32,
+    //Most steps a run that finished took
+    pub worst_steps: usize,
+}
+
+impl StressReport {
+    //Fraction of the runs that ran into a contradiction, between 0 and 1
+    pub fn failure_rate(&self) -> f32 {
+        self.failures as f32 / self.runs.max(1) as f32
+    }
+}
+
+//What is done after a contradiction, only used by the window
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RetryStrategy {
+    //Start over from an empty output
+    #[default]
+    Restart,
+    //Clear the cells around the contradiction and generate them again
+    Reroll,
+    //Go back to the state before the step that failed, failing again goes
+    //back another step
+    Backtrack,
+    //Allow some of the forbidden pairs of tiles and start over
+    Relax,
+}
+
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+impl RetryStrategy {
+    pub const ALL: [RetryStrategy; 4] = [
+        RetryStrategy::Restart,
+        RetryStrategy::Reroll,
+        RetryStrategy::Backtrack,
+        RetryStrategy::Relax,
+    ];
+
+    //The strategy after this one, wrapping around to the first
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|strategy| *strategy == self);
+        Self::ALL[(i.unwrap_or(0) + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for RetryStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Restart => "restart",
+            Self::Reroll => "re-roll",
+            Self::Backtrack => "backtrack",
+            Self::Relax => "relax rules",
+        };
+        write!(f, "{name}")
+    }
+}
+
+//Where a contradiction happened and how far along the generation was
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Contradiction {
+    //First cell that has no tiles left
+    pub x: usize,
+    pub y: usize,
+    //Tiles that have not been collapsed yet
+    pub remaining: usize,
+    //Contradictions so far in this generation, including this one
+    pub count: u32,
+}
+
+//What a ContradictionCallback decides to do about a contradiction
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContradictionAction {
+    //Recover with the strategy and keep generating
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    Retry(RetryStrategy),
+    //Stop generating and return an error
+    //Only returned by callers of generate_grid_handled
+    #[allow(dead_code)]
+    Abort,
+}
+
+//What propagating does when a cell runs out of tiles
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ContradictionPolicy {
+    //The step fails, what happens then is up to the caller
+    #[default]
+    Fail,
+    //The cell is collapsed to this tile and generating keeps going. The
+    //tile is not checked against the neighbors of the cell and they are not
+    //changed to fit it, so the output always finishes but can break the
+    //rules around the filled cells
+    Fill(usize),
+}
+
+//A low resolution image that biases each cell toward the tiles whose color is
+//close to the guide pixel over it, the guide is stretched to cover the whole
+//output. Transparent pixels don't change the weights
+#[derive(Clone)]
+pub struct Guide {
+    pub image: ImageData,
+    //How quickly the weight of a tile falls off as its color gets further
+    //from the guide color, 0 turns the guide off
+    pub strength: f32,
+}
+
+impl Guide {
+    //Multiplier for the weight of a tile with the given color at (x, y) in a
+    //w x h output
+    pub fn weight(&self, color: u32, x: usize, y: usize, w: usize, h: usize) -> f32 {
+        let guide_x = x * self.image.width() / w.max(1);
+        let guide_y = y * self.image.height() / h.max(1);
+        let guide_color = self.image.get_pixel(guide_x, guide_y);
+        if guide_color >> 24 == 0 {
+            return 1.0;
+        }
+
+        let (r1, g1, b1) = u32_to_color(guide_color);
+        let (r2, g2, b2) = u32_to_color(color);
+        let distance = ((r1 - r2).powi(2) + (g1 - g2).powi(2) + (b1 - b2).powi(2)).sqrt();
+        (-self.strength * distance).exp()
+    }
+}
+
+//Scales the weights of some of the tiles in a rectangle of cells, x and y are
+//the top left cell. Tiles are still only picked where the rules allow them,
+//so a large multiplier makes them more common there without forcing them.
+//The multipliers of overlapping regions are multiplied together
+#[allow(dead_code)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct RegionBias {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<usize>,
+    pub multiplier: f32,
+}
+
+impl RegionBias {
+    //Multiplier for the weight of the tile at (x, y)
+    pub fn weight(&self, tile: usize, x: usize, y: usize) -> f32 {
+        let inside = (self.x..self.x + self.width).contains(&x)
+            && (self.y..self.y + self.height).contains(&y);
+        if inside && self.tiles.contains(&tile) {
+            self.multiplier
+        } else {
+            1.0
+        }
+    }
+}
+
+//Time spent by the solver, collapse is the time spent in step and includes
+//the time spent propagating
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Timings {
+    pub collapse: Duration,
+    pub propagate: Duration,
+    //Furthest any propagation spread from the cell it started at, in steps
+    pub propagation_reach: usize,
+}
+
+impl Timings {
+    //Adds the time of another generation, the reach is the furthest of both
+    pub fn add(&mut self, other: Timings) {
+        self.collapse += other.collapse;
+        self.propagate += other.propagate;
+        self.propagation_reach = self.propagation_reach.max(other.propagation_reach);
+    }
+}
+
+#[derive(Clone)]
+pub struct WFCParameters {
+    pub wfc_tiles: Vec<u32>,
+    pub wfc_rules: RuleTable,
+    pub wfc_frequency: Vec<u32>,
+    //N of the overlapping model: each cell of the output is one of the N x N
+    //patterns sampled from the input and neighboring patterns have to agree
+    //on the pixels they share when shifted by one pixel. Only the top left
+    //pixel of each cell's pattern is drawn, so the output is one pixel per
+    //cell no matter what N is
+    pub wfc_tile_sz: usize,
+    //The tile_sz x tile_sz pixels of each tile, only kept if asked for when
+    //the tiles are sampled
+    pub wfc_patterns: Option<Vec<Tile>>,
+    pub wfc_weight_transform: WeightTransform,
+    //Only collapse tiles that are next to an already collapsed tile (unless
+    //there are none), the output then grows outwards from the first tile as
+    //one connected region instead of appearing in patches all over the grid
+    pub wfc_grow_from_collapsed: bool,
+    //Break ties in entropy by picking the tile with the most collapsed
+    //neighbors, which tends to lead to fewer contradictions
+    pub wfc_prefer_constrained: bool,
+    pub wfc_start: StartPosition,
+    //Number of tiles collapsed by each step, the extra tiles are only picked
+    //if they are far from the others so that their changes are unlikely to
+    //run into each other. Each collapse is still propagated before the next
+    pub wfc_collapses_per_step: usize,
+    //How many cells apart the tiles collapsed in the same step have to be at
+    //least, horizontally or vertically. Tiles closer than twice the tile
+    //size can change each other's options as soon as one of them is
+    //collapsed, which is the default
+    pub wfc_collapse_separation: usize,
+    //Biases the tiles of each cell toward the colors of a guide image
+    pub wfc_guide: Option<Guide>,
+    //Regions of the output that prefer some of the tiles
+    pub wfc_regions: Vec<RegionBias>,
+    //Above 0 the next tile to collapse is picked at random with a
+    //probability proportional to exp(-entropy / temperature) instead of
+    //always picking the lowest entropy, higher temperatures pick tiles with
+    //more options left more often which can make the output less clumped
+    pub wfc_temperature: f32,
+    //Changes are only propagated to cells at most this many steps away from
+    //the cell that changed, None propagates as far as they go. Capping this
+    //makes each collapse cheaper on very large grids, but the cells further
+    //away can keep tiles that are no longer allowed, so generating runs into
+    //more contradictions and can even finish with pairs the rules forbid
+    pub wfc_max_propagation_depth: Option<usize>,
+    //Whether the output wraps around, this is independent of how the input
+    //was sampled. A finite output doesn't have to tile, the cells on its
+    //edges have fewer neighbors
+    pub wfc_boundary: Boundary,
+    pub wfc_contradiction_policy: ContradictionPolicy,
+    //Time is only measured if this is set, the timings of a generation are
+    //kept in its WFCState
+    pub wfc_measure_timings: bool,
+    //Check that the queue agrees with the superpositions after every step
+    //and panic if it doesn't, this goes over the whole grid each step so it
+    //is only meant for tracking down bugs
+    pub wfc_check_invariants: bool,
+}
+
+impl WFCParameters {
+    //Parameters for tiles and rules that were built some other way than by
+    //sampling an image, everything else is set to the same defaults as
+    //from_image_data
+    pub fn from_parts(
+        tiles: Vec<u32>,
+        rules: RuleTable,
+        frequency: Vec<u32>,
+        tile_sz: usize,
+    ) -> Self {
+        Self {
+            wfc_tiles: tiles,
+            wfc_rules: rules,
+            wfc_frequency: frequency,
+            wfc_tile_sz: tile_sz,
+            wfc_patterns: None,
+            wfc_weight_transform: WeightTransform::Identity,
+            wfc_grow_from_collapsed: false,
+            wfc_prefer_constrained: false,
+            wfc_start: StartPosition::Random,
+            wfc_collapses_per_step: 1,
+            wfc_collapse_separation: 2 * tile_sz,
+            wfc_guide: None,
+            wfc_regions: vec![],
+            wfc_temperature: 0.0,
+            wfc_max_propagation_depth: None,
+            wfc_boundary: Boundary::Wrap,
+            wfc_contradiction_policy: ContradictionPolicy::Fail,
+            wfc_measure_timings: false,
+            wfc_check_invariants: false,
+        }
+    }
+
+    //Ids of every tile whose color (the color of its top left pixel, which
+    //is the color it is drawn with) is color, several different tiles can
+    //share a color
+    pub fn tiles_with_color(&self, color: u32) -> Vec<usize> {
+        (0..self.wfc_tiles.len())
+            .filter(|id| self.wfc_tiles[*id] == color)
+            .collect()
+    }
+
+    //Frequencies of the tiles as probabilities that sum to 1, useful when
+    //combining the tiles of several inputs or building rules by hand
+    #[allow(dead_code)]
+    pub fn normalized_frequencies(&self) -> Vec<f32> {
+        normalize_frequencies(&self.wfc_frequency)
+    }
+
+    pub fn step<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        wfc_state: &mut WFCState,
+        rng: &mut R,
+    ) -> Result<(), String> {
+        let start = self.wfc_measure_timings.then(Instant::now);
+        let result = self.collapse_next(w, h, wfc_state, rng);
+        add_time(wfc_state, start, |timings| &mut timings.collapse);
+        result
+    }
+
+    fn propagate_timed(
+        &self,
+        wfc_state: &mut WFCState,
+        x: isize,
+        y: isize,
+        w: usize,
+        h: usize,
+    ) -> Result<usize, String> {
+        let start = self.wfc_measure_timings.then(Instant::now);
+        let result = propagate(wfc_state, self, x, y, w, h);
+        add_time(wfc_state, start, |timings| &mut timings.propagate);
+        if let (true, Ok(reach)) = (self.wfc_measure_timings, &result) {
+            let timings = &mut wfc_state.timings;
+            timings.propagation_reach = timings.propagation_reach.max(*reach);
+        }
+        result
+    }
+
+    fn collapse_next<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        wfc_state: &mut WFCState,
+        rng: &mut R,
+    ) -> Result<(), String> {
+        //Find the tile with the lowest "entropy"
+        let next = if self.wfc_grow_from_collapsed {
+            wfc_state.pop_next_to_collapsed(self.wfc_rules.offsets(), w, h, self.wfc_boundary)
+        } else if self.wfc_prefer_constrained {
+            wfc_state.pop_most_constrained(self.wfc_rules.offsets(), w, h, self.wfc_boundary)
+        } else if self.wfc_temperature > 0.0 {
+            wfc_state.pop_tempered(self.wfc_temperature, rng)
+        } else {
+            wfc_state.tile_queue.pop()
+        };
+        let mut chosen = vec![next.unwrap_or(0)];
+        if self.wfc_collapses_per_step > 1 {
+            wfc_state.pop_separated(
+                &mut chosen,
+                self.wfc_collapses_per_step,
+                self.wfc_collapse_separation,
+                w,
+                h,
+                rng,
+            );
+        }
+
+        for index in chosen {
+            //Collapsing an earlier tile may have already collapsed this one
+            if wfc_state.superpositions[index].len() <= 1 {
+                continue;
+            }
+            self.collapse(w, h, wfc_state, index, rng)?;
+        }
+
+        if self.wfc_check_invariants {
+            if let Err(msg) = wfc_state.check_invariants() {
+                panic!("WFC state is out of sync after a step: {msg}");
+            }
+        }
+
+        Ok(())
+    }
+
+    //Collapses a tile into a random state that is allowed and propagates it
+    fn collapse<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        wfc_state: &mut WFCState,
+        index: usize,
+        rng: &mut R,
+    ) -> Result<(), String> {
+        let max_frequency = self.wfc_frequency.iter().copied().max().unwrap_or(0);
+        //The buffers are taken out of the state while it is borrowed below
+        let mut options = std::mem::take(&mut wfc_state.options);
+        options.clear();
+        options.extend(wfc_state.superpositions[index].iter());
+        let mut weights = std::mem::take(&mut wfc_state.weights);
+        weights.clear();
+        weights.extend(options.iter().map(|tile| {
+            self.wfc_weight_transform
+                .apply(self.wfc_frequency[*tile], max_frequency)
+        }));
+
+        let collapsed = if self.wfc_rules.has_weights()
+            || self.wfc_guide.is_some()
+            || !self.wfc_regions.is_empty()
+        {
+            let mut soft_weights = self.soft_weights(wfc_state, index, w, h, &weights);
+            if let Some(guide) = &self.wfc_guide {
+                for (weight, tile) in soft_weights.iter_mut().zip(&options) {
+                    *weight *= guide.weight(self.wfc_tiles[*tile], index % w, index / w, w, h);
+                }
+            }
+            for region in &self.wfc_regions {
+                for (weight, tile) in soft_weights.iter_mut().zip(&options) {
+                    *weight *= region.weight(*tile, index % w, index / w);
+                }
+            }
+            options[generate_weighted_f32(rng, &soft_weights)]
+        } else {
+            random_element(&options, rng, Some(&weights)).unwrap_or(0)
+        };
+        wfc_state.options = options;
+        wfc_state.weights = weights;
+        let was = wfc_state.superpositions[index].len();
+        wfc_state.superpositions[index].collapse(collapsed);
+        wfc_state.count_change(was, 1);
+        //Update surrounding tiles to only have valid tiles in the superposition
+        let x = (index % w) as isize;
+        let y = (index / w) as isize;
+        //Propagate
+        self.propagate_timed(wfc_state, x, y, w, h)?;
+
+        Ok(())
+    }
+
+    //Scales the weight of each option of a tile by the adjacency weights
+    //with its collapsed neighbors
+    fn soft_weights(
+        &self,
+        wfc_state: &WFCState,
+        index: usize,
+        w: usize,
+        h: usize,
+        weights: &[u32],
+    ) -> Vec<f32> {
+        let mut soft_weights: Vec<f32> = weights.iter().map(|weight| *weight as f32).collect();
+        for (direction, adj_x, adj_y) in neighbors(
+            self.wfc_rules.offsets(),
+            index % w,
+            index / w,
+            w,
+            h,
+            self.wfc_boundary,
+        ) {
+            let neighbor = &wfc_state.superpositions[adj_x + adj_y * w];
+            let Some(neighbor_tile) = neighbor.first().filter(|_| neighbor.len() == 1) else {
+                continue;
+            };
+
+            for (i, tile) in wfc_state.superpositions[index].iter().enumerate() {
+                soft_weights[i] *= self.wfc_rules.weight(direction, tile, neighbor_tile);
+            }
+        }
+        soft_weights
+    }
+
+    #[allow(dead_code)]
+    pub fn generate_grid(&self, w: usize, h: usize) -> Result<ImageData, String> {
+        self.generate_grid_with_rng(w, h, &vec![false; w * h], &mut StdRng::from_entropy(), None)
+    }
+
+    //Same as generate_grid but on_step is called after each tile is
+    //collapsed with the superpositions and the number of tiles that have
+    //not been collapsed yet
+    #[allow(dead_code)]
+    pub fn generate_grid_streaming(
+        &self,
+        w: usize,
+        h: usize,
+        on_step: StepCallback,
+    ) -> Result<ImageData, String> {
+        self.generate_grid_with_rng(
+            w,
+            h,
+            &vec![false; w * h],
+            &mut StdRng::from_entropy(),
+            Some(on_step),
+        )
+    }
+
+    //Same as generate_grid but the output only depends on the seed
+    pub fn generate_grid_seeded(&self, w: usize, h: usize, seed: u64) -> Result<ImageData, String> {
+        self.generate_grid_with_rng(
+            w,
+            h,
+            &vec![false; w * h],
+            &mut StdRng::seed_from_u64(seed),
+            None,
+        )
+    }
+
+    //Same as generate_grid_seeded but tiles where masked is true are left
+    //blank, on_step is called after each collapse like in
+    //generate_grid_streaming
+    #[allow(dead_code)]
+    pub fn generate_grid_masked_seeded(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        seed: u64,
+        on_step: Option<StepCallback>,
+    ) -> Result<ImageData, String> {
+        self.generate_grid_with_rng(w, h, masked, &mut StdRng::seed_from_u64(seed), on_step)
+    }
+
+    //Same as generate_grid_masked_seeded but returns the state the generation
+    //ended in instead of an image, which is only partly collapsed if the
+    //generation failed
+    pub fn generate_state_masked_seeded(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        seed: u64,
+        on_step: Option<StepCallback>,
+    ) -> (WFCState, Result<(), String>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut wfc_state = self.start_state(w, h, masked, &mut rng);
+        let result = self.run(w, h, &mut wfc_state, &mut rng, None, on_step);
+        (wfc_state, result.map_err(String::from))
+    }
+
+    //Same as generate_grid_seeded but stops with GenerateError::Cancelled
+    //before the next step once cancel is set, which can be done from another
+    //thread
+    #[allow(dead_code)]
+    pub fn generate_grid_cancellable(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        cancel: &AtomicBool,
+    ) -> Result<ImageData, GenerateError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let wfc_state = self.solve(w, h, &vec![false; w * h], &mut rng, Some(cancel), None)?;
+        Ok(self.state_to_image(&wfc_state, w, h))
+    }
+
+    //Generates an image into target, reusing the memory of both target and
+    //wfc_state so that repeated generations don't need to reallocate
+    #[allow(dead_code)]
+    pub fn generate_into(
+        &self,
+        target: &mut ImageData,
+        wfc_state: &mut WFCState,
+        seed: u64,
+    ) -> Result<(), String> {
+        let (w, h) = (target.width(), target.height());
+        if wfc_state.superpositions.len() != w * h {
+            return Err(format!(
+                "WFC state has {} tiles but the image is {w}x{h}",
+                wfc_state.superpositions.len()
+            ));
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        wfc_state.reset(&self.wfc_tiles, &self.wfc_frequency, &mut rng);
+        while !wfc_state.done() {
+            self.step(w, h, wfc_state, &mut rng)?;
+        }
+
+        copy_superpositions_to_grid(
+            target.pixels_mut(),
+            &wfc_state.superpositions,
+            &wfc_state.masked,
+            (w, h),
+            &self.wfc_tiles,
+            &self.wfc_frequency,
+            &RenderOptions::default(),
+        );
+
+        Ok(())
+    }
+
+    //Keeps trying seed, seed + 1, seed + 2, ... until the generation succeeds,
+    //returns the image along with the number of attempts that were needed,
+    //only fails if all max_attempts attempts fail
+    #[allow(dead_code)]
+    pub fn generate_grid_until_success(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        max_attempts: u32,
+    ) -> Result<(ImageData, u32), String> {
+        for attempt in 0..max_attempts {
+            if let Ok(image) = self.generate_grid_seeded(w, h, seed.wrapping_add(attempt as u64)) {
+                return Ok((image, attempt + 1));
+            }
+        }
+
+        Err(format!("WFC Failed after {max_attempts} attempts"))
+    }
+
+    //Generates runs outputs with the seeds seed, seed + 1, ... without
+    //starting over after a contradiction, to measure how often the rules run
+    //into one. Steps that don't collapse a cell are not counted
+    pub fn stress_test(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        seed: u64,
+        runs: u32,
+    ) -> StressReport {
+        let mut report = StressReport {
+            runs,
+            ..StressReport::default()
+        };
+        let mut total_steps = 0;
+        for run in 0..runs {
+            let mut steps = 0;
+            let (_, result) = self.generate_state_masked_seeded(
+                w,
+                h,
+                masked,
+                seed.wrapping_add(run as u64),
+                Some(&mut |_, _| steps += 1),
+            );
+            if result.is_err() {
+                report.failures += 1;
+                continue;
+            }
+            total_steps += steps;
+            report.worst_steps = report.worst_steps.max(steps);
+        }
+        let finished = runs - report.failures;
+        report.average_steps = total_steps as f32 / finished.max(1) as f32;
+        report
+    }
+
+    //Same as generate_grid_until_success but when every attempt fails the
+    //rules are relaxed by allowing some of the forbidden pairs and the
+    //attempts start over, up to max_relaxations times. This always gives an
+    //output if enough rules are relaxed, at the cost of breaking some of the
+    //rules of the input. Returns the image along with the number of pairs
+    //that had to be allowed
+    #[allow(dead_code)]
+    pub fn generate_grid_relaxed(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        max_attempts: u32,
+        max_relaxations: u32,
+    ) -> Result<(ImageData, usize), String> {
+        let mut relaxed = self.clone();
+        let mut allowed = 0;
+        for _ in 0..=max_relaxations {
+            if let Ok((image, _)) = relaxed.generate_grid_until_success(w, h, seed, max_attempts) {
+                return Ok((image, allowed));
+            }
+
+            let newly_allowed = relaxed.relax(self.wfc_tiles.len().max(1));
+            if newly_allowed == 0 {
+                break;
+            }
+            allowed += newly_allowed;
+        }
+
+        Err(format!(
+            "WFC Failed after relaxing {allowed} rules {max_relaxations} times"
+        ))
+    }
+
+    //Same as generate_grid_seeded but on_contradiction decides what is done
+    //each time the generation runs into a contradiction, either recovering
+    //with one of the retry strategies or giving up with an error. The states
+    //before the last backtrack_depth steps are kept for backtracking. Rules
+    //are only relaxed for this generation, the image is returned along with
+    //the number of pairs that had to be allowed
+    #[allow(dead_code)]
+    pub fn generate_grid_handled(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        backtrack_depth: usize,
+        on_contradiction: ContradictionCallback,
+    ) -> Result<(ImageData, usize), String> {
+        let mut relaxed = self.clone();
+        let mut allowed = 0;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut wfc_state = relaxed.start_state(w, h, &vec![false; w * h], &mut rng);
+        let mut history = vec![];
+        let mut count = 0;
+        while !wfc_state.done() {
+            if backtrack_depth > 0 {
+                history.push(wfc_state.clone());
+                if history.len() > backtrack_depth {
+                    history.remove(0);
+                }
+            }
+            let Err(msg) = relaxed.step(w, h, &mut wfc_state, &mut rng) else {
+                continue;
+            };
+
+            count += 1;
+            let Some(contradiction) = wfc_state.contradiction(w, count) else {
+                return Err(msg);
+            };
+            match on_contradiction(&contradiction) {
+                ContradictionAction::Retry(strategy) => {
+                    allowed += relaxed.retry(strategy, w, h, &mut wfc_state, &mut history, &mut rng)
+                }
+                ContradictionAction::Abort => {
+                    let (x, y) = (contradiction.x, contradiction.y);
+                    let explanation = relaxed.explain_contradiction(&wfc_state, x, y, w, h);
+                    return Err(format!(
+                        "{msg}, aborted after {count} contradictions\n{explanation}"
+                    ));
+                }
+            }
+        }
+
+        let ids = ids_from_state(&wfc_state, w)?;
+        let pixels: Vec<u32> = ids.iter().map(|id| self.wfc_tiles[*id]).collect();
+        Ok((ImageData::from_pixels(&pixels, w, h), allowed))
+    }
+
+    //Gets the state out of a contradiction with the strategy. history has
+    //the states before the last few steps, most recent last, and is only
+    //used when backtracking. Strategies that can't recover start over.
+    //Returns how many pairs relaxing allowed, relaxing changes the rules of
+    //these parameters for every later generation
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub fn retry<R: Rng>(
+        &mut self,
+        strategy: RetryStrategy,
+        w: usize,
+        h: usize,
+        wfc_state: &mut WFCState,
+        history: &mut Vec<WFCState>,
+        rng: &mut R,
+    ) -> usize {
+        let mut allowed = 0;
+        let recovered = match strategy {
+            RetryStrategy::Restart => false,
+            RetryStrategy::Reroll => self.reroll(w, h, wfc_state).is_ok(),
+            RetryStrategy::Backtrack => match history.pop() {
+                Some(previous) => {
+                    *wfc_state = previous;
+                    true
+                }
+                None => false,
+            },
+            RetryStrategy::Relax => {
+                allowed = self.relax(self.wfc_tiles.len().max(1));
+                false
+            }
+        };
+
+        if !recovered {
+            history.clear();
+            wfc_state.reset(&self.wfc_tiles, &self.wfc_frequency, rng);
+        }
+        allowed
+    }
+
+    //Clears every cell near a cell with no tiles left and restricts the
+    //cleared cells again by propagating from the cells around them. Fails if
+    //that propagation runs into a contradiction
+    fn reroll(&self, w: usize, h: usize, wfc_state: &mut WFCState) -> Result<(), String> {
+        let failed: Vec<usize> = (0..w * h)
+            .filter(|i| !wfc_state.masked[*i] && wfc_state.superpositions[*i].is_empty())
+            .collect();
+        //Cells this close to the contradiction may have been narrowed down
+        //because of it
+        let radius = 2 * self.wfc_tile_sz;
+        let cleared: Vec<bool> = (0..w * h)
+            .map(|i| {
+                !wfc_state.masked[i]
+                    && failed
+                        .iter()
+                        .any(|failed| wrapped_distance(*failed, i, w, h) <= radius)
+            })
+            .collect();
+
+        for i in (0..w * h).filter(|i| cleared[*i]) {
+            let was = wfc_state.superpositions[i].len();
+            wfc_state.superpositions[i].fill(self.wfc_tiles.len());
+            wfc_state.count_change(was, self.wfc_tiles.len());
+            wfc_state.update_queue(i);
+        }
+
+        let border: Vec<usize> = (0..w * h)
+            .filter(|i| !cleared[*i] && !wfc_state.masked[*i])
+            .filter(|i| {
+                neighbors(
+                    self.wfc_rules.offsets(),
+                    i % w,
+                    i / w,
+                    w,
+                    h,
+                    self.wfc_boundary,
+                )
+                .any(|(_, adj_x, adj_y)| cleared[adj_x + adj_y * w])
+            })
+            .collect();
+        for i in border {
+            self.propagate_timed(wfc_state, (i % w) as isize, (i / w) as isize, w, h)?;
+        }
+
+        Ok(())
+    }
+
+    //Generates an image that is mirrored across the vertical axis: only the
+    //left half is generated with WFC and the right half is a mirror of it.
+    //The half is generated with finite left and right edges since its
+    //columns are only next to each other where the halves meet. The cells on
+    //either side of the seam in the middle (and the seam where the output
+    //wraps around, if it does) end up next to a copy of themselves, so they
+    //are restricted to tiles that are allowed to be next to themselves
+    //horizontally. This only works if the input has enough horizontally
+    //symmetric tiles to fill those columns, otherwise generation fails.
+    //The width must be even.
+    #[allow(dead_code)]
+    pub fn generate_symmetric_grid_seeded(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+    ) -> Result<ImageData, String> {
+        if !w.is_multiple_of(2) {
+            return Err(format!("width must be even for symmetric output, got {w}"));
+        }
+        let (Some(right), Some(left)) = (
+            self.wfc_rules.direction((1, 0)),
+            self.wfc_rules.direction((-1, 0)),
+        ) else {
+            return Err("symmetric output needs rules for left and right neighbors".to_string());
+        };
+
+        let half = WFCParameters {
+            wfc_boundary: self.wfc_boundary.finite_x(),
+            ..self.clone()
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let half_w = w / 2;
+        let mut wfc_state =
+            WFCState::new(half_w, h, &self.wfc_tiles, &self.wfc_frequency, &mut rng);
+        for y in 0..h {
+            //Right of the last column is its mirror
+            half.restrict_cell(&mut wfc_state, y * half_w + half_w - 1, half_w, h, |tile| {
+                self.wfc_rules.okay(right, tile, tile)
+            })?;
+            //Left of the first column is its mirror if the output wraps
+            if self.wfc_boundary.wraps_x() {
+                half.restrict_cell(&mut wfc_state, y * half_w, half_w, h, |tile| {
+                    self.wfc_rules.okay(left, tile, tile)
+                })?;
+            }
+        }
+
+        while !wfc_state.done() {
+            half.step(half_w, h, &mut wfc_state, &mut rng)?;
+        }
+
+        let half_ids = ids_from_state(&wfc_state, half_w)?;
+        let mut ids = vec![0; w * h];
+        for y in 0..h {
+            for x in 0..half_w {
+                ids[y * w + x] = half_ids[y * half_w + x];
+                ids[y * w + w - 1 - x] = half_ids[y * half_w + x];
+            }
+        }
+
+        //Check the pairs across the seams against the rules
+        let mut seams = vec![half_w - 1];
+        if self.wfc_boundary.wraps_x() {
+            seams.push(w - 1);
+        }
+        for y in 0..h {
+            for x in seams.iter().copied() {
+                let (id1, id2) = (ids[y * w + x], ids[y * w + (x + 1) % w]);
+                if !self.wfc_rules.okay(right, id1, id2) {
+                    return Err(format!(
+                        "tile {id2} is not allowed to the right of tile {id1} at ({x}, {y})"
+                    ));
+                }
+            }
+        }
+
+        let grid: Vec<u32> = ids.iter().map(|id| self.wfc_tiles[*id]).collect();
+        Ok(ImageData::from_pixels(&grid, w, h))
+    }
+
+    //Removes the tiles that are not allowed from a cell and propagates the
+    //change to the rest of the grid, returns how far the change spread
+    pub fn restrict_cell(
+        &self,
+        wfc_state: &mut WFCState,
+        index: usize,
+        w: usize,
+        h: usize,
+        allowed: impl Fn(usize) -> bool,
+    ) -> Result<usize, String> {
+        let was = wfc_state.superpositions[index].len();
+        wfc_state.superpositions[index].retain(allowed);
+        wfc_state.count_change(was, wfc_state.superpositions[index].len());
+        if wfc_state.superpositions[index].is_empty() {
+            return Err("WFC Failed".to_string());
+        }
+        wfc_state.update_queue(index);
+
+        propagate(
+            wfc_state,
+            self,
+            (index % w) as isize,
+            (index / w) as isize,
+            w,
+            h,
+        )
+    }
+
+    //Collapses a cell into the tile even if the tile is not one of its
+    //options. With learn the pairs the tile makes with its collapsed
+    //neighbors are allowed first if the rules forbid them, so later
+    //generations can place them too. Without it placing a tile that is not
+    //allowed next to its neighbors fails. Returns how many pairs were learned
+    #[cfg_attr(not(feature = "gui"), allow(dead_code))]
+    pub fn place_tile(
+        &mut self,
+        wfc_state: &mut WFCState,
+        index: usize,
+        tile: usize,
+        w: usize,
+        h: usize,
+        learn: bool,
+    ) -> Result<usize, String> {
+        let mut learned = 0;
+        if learn {
+            let adjacent: Vec<(usize, usize, usize)> = neighbors(
+                self.wfc_rules.offsets(),
+                index % w,
+                index / w,
+                w,
+                h,
+                self.wfc_boundary,
+            )
+            .collect();
+            for (direction, adj_x, adj_y) in adjacent {
+                let adj = &wfc_state.superpositions[adj_x + adj_y * w];
+                let Some(adj_tile) = adj.first().filter(|_| adj.len() == 1) else {
+                    continue;
+                };
+                if !self.wfc_rules.okay(direction, tile, adj_tile) {
+                    self.allow_symmetric(direction, tile, adj_tile);
+                    learned += 1;
+                }
+            }
+        }
+
+        let was = wfc_state.superpositions[index].len();
+        wfc_state.superpositions[index].fill(self.wfc_tiles.len());
+        wfc_state.count_change(was, self.wfc_tiles.len());
+        self.restrict_cell(wfc_state, index, w, h, |id| id == tile)?;
+        Ok(learned)
+    }
+
+    fn generate_grid_with_rng<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        rng: &mut R,
+        on_step: Option<StepCallback>,
+    ) -> Result<ImageData, String> {
+        let wfc_state = self.solve(w, h, masked, rng, None, on_step)?;
+        Ok(self.state_to_image(&wfc_state, w, h))
+    }
+
+    fn state_to_image(&self, wfc_state: &WFCState, w: usize, h: usize) -> ImageData {
+        superpositions_to_image(
+            &wfc_state.superpositions,
+            &wfc_state.masked,
+            &self.wfc_tiles,
+            &self.wfc_frequency,
+            w,
+            h,
+            &RenderOptions::default(),
+        )
+    }
+
+    //Returns the id of the tile each cell collapsed into instead of its color,
+    //the ids index into wfc_tiles and wfc_frequency
+    #[allow(dead_code)]
+    pub fn generate_ids(&self, w: usize, h: usize) -> Result<Vec<usize>, String> {
+        self.generate_ids_with_rng(w, h, &mut StdRng::from_entropy())
+    }
+
+    //Same as generate_ids but the output only depends on the seed, this
+    //fails if any cell is left with more than one state
+    #[allow(dead_code)]
+    pub fn generate_ids_seeded(&self, w: usize, h: usize, seed: u64) -> Result<Vec<usize>, String> {
+        self.generate_ids_with_rng(w, h, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn generate_ids_with_rng<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        rng: &mut R,
+    ) -> Result<Vec<usize>, String> {
+        let wfc_state = self.solve(w, h, &vec![false; w * h], rng, None, None)?;
+        ids_from_state(&wfc_state, w)
+    }
+
+    //Same as generate_ids_seeded but each cell starts with only the tiles
+    //that allowed(x, y) returns instead of every tile, which can constrain
+    //the output in any way. The constraints are propagated before the first
+    //collapse. Fails if a cell is given no tiles or an id that is not a
+    //tile, or if the constraints contradict each other
+    #[allow(dead_code)]
+    pub fn generate_ids_constrained(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        allowed: impl Fn(usize, usize) -> Vec<usize>,
+    ) -> Result<Vec<usize>, String> {
+        self.constrained_ids_with_rng(w, h, &mut StdRng::seed_from_u64(seed), allowed)
+    }
+
+    //Generates the rest of a finished output again, the cells in keep stay
+    //the tile they have in ids (the output of generate_ids) and every other
+    //cell starts over with every tile. The kept cells are propagated before
+    //the first collapse so the new tiles fit next to them. Fails if a kept
+    //cell is outside of the output or the new tiles run into a contradiction
+    #[allow(dead_code)]
+    pub fn regenerate_except(
+        &self,
+        ids: &[usize],
+        w: usize,
+        h: usize,
+        keep: &[(usize, usize)],
+        seed: u64,
+    ) -> Result<Vec<usize>, String> {
+        if ids.len() != w * h {
+            return Err(format!("{} tile ids can't be a {w}x{h} output", ids.len()));
+        }
+        let mut kept = vec![None; w * h];
+        for (x, y) in keep {
+            if *x >= w || *y >= h {
+                return Err(format!(
+                    "kept cell ({x}, {y}) is outside of the {w}x{h} output"
+                ));
+            }
+            kept[x + y * w] = Some(ids[x + y * w]);
+        }
+
+        self.constrained_ids_with_rng(w, h, &mut StdRng::seed_from_u64(seed), |x, y| {
+            match kept[x + y * w] {
+                Some(tile) => vec![tile],
+                None => (0..self.wfc_tiles.len()).collect(),
+            }
+        })
+    }
+
+    fn constrained_ids_with_rng<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        rng: &mut R,
+        allowed: impl Fn(usize, usize) -> Vec<usize>,
+    ) -> Result<Vec<usize>, String> {
+        let mut wfc_state = self.start_state(w, h, &vec![false; w * h], rng);
+        for index in 0..w * h {
+            let (x, y) = (index % w, index / w);
+            let tiles = allowed(x, y);
+            if tiles.is_empty() {
+                return Err(format!("cell ({x}, {y}) is given no tiles"));
+            }
+            let mut keep = vec![false; self.wfc_tiles.len()];
+            for tile in tiles {
+                if tile >= keep.len() {
+                    return Err(format!(
+                        "cell ({x}, {y}) is given tile {tile} but there are only {} tiles",
+                        keep.len()
+                    ));
+                }
+                keep[tile] = true;
+            }
+            if keep.iter().all(|keep| *keep) {
+                continue;
+            }
+            self.restrict_cell(&mut wfc_state, index, w, h, |tile| keep[tile])
+                .map_err(|e| format!("{e}, the constraint on cell ({x}, {y}) can't be met"))?;
+        }
+
+        self.run(w, h, &mut wfc_state, rng, None, None)?;
+        ids_from_state(&wfc_state, w)
+    }
+
+    //Generates a w x h output band_h rows at a time and passes the tile ids
+    //of each row to on_row from top to bottom, so only one band is in memory
+    //at a time. Every band after the first starts from the last row of the
+    //band above it so the rules hold across the seams, but a band that fails
+    //can't change the rows that were already passed on: each band gets
+    //max_attempts tries before this fails. The top and bottom edges of the
+    //output are always finite
+    pub fn generate_banded(
+        &self,
+        w: usize,
+        h: usize,
+        band_h: usize,
+        seed: u64,
+        max_attempts: u32,
+        mut on_row: impl FnMut(&[usize]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if band_h == 0 {
+            return Err("the band height must be at least 1".to_string());
+        }
+        //The last row of a band must not wrap around onto its first row
+        let band = WFCParameters {
+            wfc_boundary: self.wfc_boundary.finite_y(),
+            ..self.clone()
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut last_row: Option<Vec<usize>> = None;
+        let mut y = 0;
+        while y < h {
+            let rows = band_h.min(h - y);
+            //The row from the band above is generated again as the first row
+            let fixed = usize::from(last_row.is_some());
+            let ids = band
+                .generate_band(w, rows + fixed, last_row.as_deref(), max_attempts, &mut rng)
+                .map_err(|e| format!("{e} in the band starting at row {y}"))?;
+            for row in ids.chunks(w).skip(fixed) {
+                on_row(row)?;
+            }
+            last_row = Some(ids[ids.len() - w..].to_vec());
+            y += rows;
+        }
+        Ok(())
+    }
+
+    //Generates a w x h band whose first row is first_row if it is given
+    fn generate_band<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        first_row: Option<&[usize]>,
+        max_attempts: u32,
+        rng: &mut R,
+    ) -> Result<Vec<usize>, String> {
+        let allowed = |x: usize, y: usize| match first_row {
+            Some(row) if y == 0 => vec![row[x]],
+            _ => (0..self.wfc_tiles.len()).collect(),
+        };
+        let mut result = Err(format!("WFC Failed after {max_attempts} attempts"));
+        for _ in 0..max_attempts {
+            result = self.constrained_ids_with_rng(w, h, rng, allowed);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    //Same as generate_ids_constrained but returns the image
+    #[allow(dead_code)]
+    pub fn generate_grid_constrained(
+        &self,
+        w: usize,
+        h: usize,
+        seed: u64,
+        allowed: impl Fn(usize, usize) -> Vec<usize>,
+    ) -> Result<ImageData, String> {
+        let ids = self.generate_ids_constrained(w, h, seed, allowed)?;
+        let pixels: Vec<u32> = ids.iter().map(|id| self.wfc_tiles[*id]).collect();
+        Ok(ImageData::from_pixels(&pixels, w, h))
+    }
+
+    fn solve<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        rng: &mut R,
+        cancel: Option<&AtomicBool>,
+        on_step: Option<StepCallback>,
+    ) -> Result<WFCState, GenerateError> {
+        let mut wfc_state = self.start_state(w, h, masked, rng);
+        self.run(w, h, &mut wfc_state, rng, cancel, on_step)?;
+        Ok(wfc_state)
+    }
+
+    //A state where nothing is collapsed yet and the first tile to collapse
+    //is picked
+    pub fn start_state<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        masked: &[bool],
+        rng: &mut R,
+    ) -> WFCState {
+        let mut wfc_state =
+            WFCState::new_masked(w, h, &self.wfc_tiles, &self.wfc_frequency, masked, rng);
+        wfc_state.set_start(
+            self.wfc_start.index(w, h),
+            &self.wfc_tiles,
+            &self.wfc_frequency,
+            rng,
+        );
+        wfc_state
+    }
+
+    //Steps until every tile is collapsed, on failure wfc_state is left as it
+    //was when the generation stopped
+    fn run<R: Rng>(
+        &self,
+        w: usize,
+        h: usize,
+        wfc_state: &mut WFCState,
+        rng: &mut R,
+        cancel: Option<&AtomicBool>,
+        mut on_step: Option<StepCallback>,
+    ) -> Result<(), GenerateError> {
+        let mut remaining = wfc_state.remaining();
+        //Repeat until we have collapsed each tile into a single state
+        while !wfc_state.done() {
+            if cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+                return Err(GenerateError::Cancelled);
+            }
+            self.step(w, h, wfc_state, rng)?;
+
+            //Steps that pop a tile that was already collapsed don't count
+            if let Some(on_step) = &mut on_step {
+                let now_remaining = wfc_state.remaining();
+                if now_remaining < remaining {
+                    on_step(&wfc_state.superpositions, now_remaining);
+                }
+                remaining = now_remaining;
+            }
+        }
+        Ok(())
+    }
+}
+
+//Adds the time since start to the timing of the state returned by field,
+//start is None when the time is not measured
+fn add_time(
+    wfc_state: &mut WFCState,
+    start: Option<Instant>,
+    field: fn(&mut Timings) -> &mut Duration,
+) {
+    if let Some(start) = start {
+        *field(&mut wfc_state.timings) += start.elapsed();
+    }
+}
+
+//Id of the tile each cell collapsed into, fails if a cell has not collapsed
+fn ids_from_state(wfc_state: &WFCState, w: usize) -> Result<Vec<usize>, String> {
+    let mut ids = Vec::with_capacity(wfc_state.superpositions.len());
+    for (i, superposition) in wfc_state.superpositions.iter().enumerate() {
+        match superposition.first() {
+            Some(tile) if superposition.len() == 1 => ids.push(tile),
+            _ => {
+                return Err(format!(
+                    "tile ({}, {}) did not collapse into a single state",
+                    i % w,
+                    i / w
+                ))
+            }
+        }
+    }
+
+    Ok(ids)
+}
+
+//Narrows the neighbors of the cell at index down to the tiles its options
+//allow next to them, returns how many neighbors went from more than one
+//tile to one or none
+pub fn update_adjacent_tiles(
+    superpositions: &mut [Superposition],
+    index: usize,
+    w: usize,
+    h: usize,
+    rules: &RuleTable,
+    masked: &[bool],
+    boundary: Boundary,
+) -> usize {
+    let mut collapsed = 0;
+    for (direction, adj_x, adj_y) in
+        neighbors(rules.offsets(), index % w, index / w, w, h, boundary)
+    {
+        let adj_index = adj_x + adj_y * w;
+        //Masked out tiles are left blank and never change
+        if masked[adj_index] {
+            continue;
+        }
+
+        //Combine the neighbors allowed by every option of this tile
+        let mut allowed = vec![0u64; rules.words()];
+        for tile in superpositions[index].iter() {
+            for (word, neighbors) in allowed
+                .iter_mut()
+                .zip(rules.allowed_neighbors(direction, tile))
+            {
+                *word |= neighbors;
+            }
+        }
+
+        let was = superpositions[adj_index].len();
+        superpositions[adj_index].intersect(&allowed);
+        if was > 1 && superpositions[adj_index].len() <= 1 {
+            collapsed += 1;
+        }
+    }
+    collapsed
+}
+
+//Removes the tiles that are no longer allowed from the cells around (x, y),
+//spreading outward in order of distance for as long as cells change (or up
+//to wfc_max_propagation_depth steps away). Returns how many steps away from
+//(x, y) the furthest cell that changed is, fails on a contradiction
+fn propagate(
+    wfc_state: &mut WFCState,
+    parameters: &WFCParameters,
+    x: isize,
+    y: isize,
+    w: usize,
+    h: usize,
+) -> Result<usize, String> {
+    let WFCState {
+        superpositions,
+        tile_queue,
+        entropy_weights,
+        masked,
+        filled,
+        is_filled,
+        remaining,
+        ..
+    } = wfc_state;
+    //Cells whose changes still have to be passed on to their neighbors,
+    //along with how many steps away from (x, y) they are
+    let mut queue = VecDeque::<(usize, usize)>::new();
+    let mut reach = 0;
+    //Keep the previous options of each neighbor so that a change in which
+    //tiles are allowed is detected even if the number of tiles stays the same
+    let mut prev_superpositions = vec![Superposition::default(); parameters.wfc_rules.directions()];
+    //Propagate the tile's properties
+    queue.push_back((x as usize + y as usize * w, 0));
+    while let Some((pos, depth)) = queue.pop_front() {
+        let (posx, posy) = (pos % w, pos / w);
+        for (direction, adj_x, adj_y) in neighbors(
+            parameters.wfc_rules.offsets(),
+            posx,
+            posy,
+            w,
+            h,
+            parameters.wfc_boundary,
+        ) {
+            prev_superpositions[direction].clone_from(&superpositions[adj_x + adj_y * w]);
+        }
+
+        *remaining -= update_adjacent_tiles(
+            superpositions,
+            pos,
+            w,
+            h,
+            &parameters.wfc_rules,
+            masked,
+            parameters.wfc_boundary,
+        );
+
+        for (direction, adj_x, adj_y) in neighbors(
+            parameters.wfc_rules.offsets(),
+            posx,
+            posy,
+            w,
+            h,
+            parameters.wfc_boundary,
+        ) {
+            let index = adj_x + adj_y * w;
+
+            if masked[index] {
+                continue;
+            }
+
+            if superpositions[index].is_empty() {
+                match parameters.wfc_contradiction_policy {
+                    ContradictionPolicy::Fail => return Err("WFC Failed".to_string()),
+                    ContradictionPolicy::Fill(tile) => {
+                        if tile >= parameters.wfc_tiles.len() {
+                            return Err(format!(
+                                "there is no tile {tile} to fill contradictions with, there are only {} tiles",
+                                parameters.wfc_tiles.len()
+                            ));
+                        }
+                        //The filled cell is frozen, nothing is propagated
+                        //from it. It can run out of tiles again when another
+                        //neighbor changes but is filled with the same tile
+                        superpositions[index].collapse(tile);
+                        tile_queue.remove(index);
+                        if !is_filled[index] {
+                            is_filled[index] = true;
+                            filled.push(index);
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            let changed = superpositions[index] != prev_superpositions[direction];
+            if superpositions[index].len() <= 1 {
+                tile_queue.remove(index);
+            } else if changed || !tile_queue.contains(index) {
+                //Only changed tiles need their entropy updated, this is a
+                //decrease-key since tiles only lose options
+                tile_queue.push(index, entropy_weights.entropy(&superpositions[index]));
+            }
+
+            if changed {
+                reach = reach.max(depth + 1);
+                //Cells at the maximum depth are updated but their changes
+                //are not passed on
+                if parameters
+                    .wfc_max_propagation_depth
+                    .is_none_or(|max_depth| depth + 1 < max_depth)
+                {
+                    queue.push_back((index, depth + 1));
+                }
+            }
+        }
+    }
+
+    Ok(reach)
+}
@@ -0,0 +1,131 @@
+use rand::Rng;
+
+//Index of a random entropy, each is picked with a probability proportional
+//to exp(-entropy / temperature). The lowest entropy is subtracted first so
+//that the weights don't all round down to 0, a temperature of 0 or less
+//always picks the lowest entropy. NaN entropies are never picked unless
+//every entropy is NaN or infinite, then the pick is uniform
+pub fn pick_tempered<R: Rng>(entropies: &[f32], temperature: f32, rng: &mut R) -> Option<usize> {
+    let entropies: Vec<f32> = entropies
+        .iter()
+        .map(|entropy| {
+            if entropy.is_nan() {
+                f32::INFINITY
+            } else {
+                *entropy
+            }
+        })
+        .collect();
+    let lowest = (0..entropies.len()).min_by(|a, b| entropies[*a].total_cmp(&entropies[*b]))?;
+    if temperature <= 0.0 {
+        return Some(lowest);
+    }
+
+    //Infinity minus infinity is NaN when every entropy is infinite
+    let weights: Vec<f32> = entropies
+        .iter()
+        .map(|entropy| (-(entropy - entropies[lowest]) / temperature).exp())
+        .map(|weight| if weight.is_nan() { 0.0 } else { weight })
+        .collect();
+    Some(generate_weighted_f32(rng, &weights))
+}
+
+pub fn generate_weighted<R: Rng>(rng: &mut R, weights: &[u32]) -> usize {
+    if weights.is_empty() {
+        return 0;
+    }
+
+    //Summed as u64 so that large weights (after AddK or Power) can't overflow
+    let total: u64 = weights.iter().map(|v| *v as u64).sum();
+    //If all of the weights are zero, pick uniformly
+    if total == 0 {
+        return (rng.gen::<u64>() % weights.len() as u64) as usize;
+    }
+    //A u32 is drawn whenever it can cover the total so that seeded output
+    //stays the same as when the total was a u32
+    let rand_value = if total <= u32::MAX as u64 {
+        rng.gen::<u32>() as u64 % total
+    } else {
+        rng.gen::<u64>() % total
+    };
+
+    let mut current_total = 0;
+    for (i, v) in weights.iter().enumerate() {
+        current_total += *v as u64;
+        if rand_value < current_total {
+            return i;
+        }
+    }
+
+    weights.len() - 1
+}
+
+//Same as generate_weighted but with fractional weights
+pub fn generate_weighted_f32<R: Rng>(rng: &mut R, weights: &[f32]) -> usize {
+    if weights.is_empty() {
+        return 0;
+    }
+
+    let total: f32 = weights.iter().sum();
+    //If all of the weights are zero, pick uniformly
+    if total <= 0.0 {
+        return (rng.gen::<u64>() % weights.len() as u64) as usize;
+    }
+    let rand_value = rng.gen::<f32>() * total;
+
+    let mut current_total = 0.0;
+    for (i, v) in weights.iter().enumerate() {
+        current_total += v;
+        if rand_value < current_total {
+            return i;
+        }
+    }
+
+    weights.len() - 1
+}
+
+//Picks up to k different candidates like random_element would pick one,
+//each pick is weighted by the weights of the candidates that are left so
+//there has to be one weight for each candidate
+pub fn sample_without_replacement<T: Copy, R: Rng>(
+    candidates: &[T],
+    k: usize,
+    weights: Option<&[u32]>,
+    rng: &mut R,
+) -> Vec<T> {
+    let mut left = candidates.to_vec();
+    let mut left_weights = weights.map(|weights| weights.to_vec());
+    let mut picked = Vec::with_capacity(k.min(left.len()));
+    while picked.len() < k && !left.is_empty() {
+        let index = match &mut left_weights {
+            Some(weights) => {
+                let index = generate_weighted(rng, weights);
+                weights.remove(index);
+                index
+            }
+            None => (rng.gen::<u64>() % left.len() as u64) as usize,
+        };
+        picked.push(left.remove(index));
+    }
+    picked
+}
+
+pub fn random_element<T: Copy, R: Rng>(
+    vec: &[T],
+    rng: &mut R,
+    weights: Option<&[u32]>,
+) -> Option<T> {
+    if vec.is_empty() {
+        return None;
+    }
+
+    match weights {
+        Some(weight_list) => Some(vec[generate_weighted(rng, weight_list)]),
+        _ => {
+            //A u64 is used instead of a usize so that a seed gives the same
+            //output on 32 bit platforms
+            let index = (rng.gen::<u64>() % vec.len() as u64) as usize;
+            Some(vec[index])
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use super::{entropy, Superposition, WFCParameters};
+use crate::image_data::{u32_to_color, ImageData};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//Transparent pixels between the tiles of the atlas
+const ATLAS_SPACING: usize = 1;
+
+impl WFCParameters {
+    //The pixels of a tile as a tile_sz x tile_sz image, None if the tiles
+    //were sampled without keep_patterns or there is no tile with that id
+    #[allow(dead_code)]
+    pub fn tile_pattern(&self, id: usize) -> Option<ImageData> {
+        let pattern = self.wfc_patterns.as_ref()?.get(id)?;
+        Some(ImageData::from_pixels(
+            pattern,
+            self.wfc_tile_sz,
+            self.wfc_tile_sz,
+        ))
+    }
+
+    //Every tile side by side in rows of columns tiles in the order of their
+    //ids, with a transparent pixel between neighboring tiles. Fails if the
+    //tiles were sampled without keep_patterns
+    pub fn tile_atlas(&self, columns: usize) -> Result<ImageData, String> {
+        let patterns = self
+            .wfc_patterns
+            .as_ref()
+            .ok_or("the patterns of the tiles were not kept when they were sampled")?;
+        let columns = columns.clamp(1, patterns.len().max(1));
+        let rows = patterns.len().div_ceil(columns);
+        let step = self.wfc_tile_sz + ATLAS_SPACING;
+        let width = (columns * step).saturating_sub(ATLAS_SPACING);
+        let mut atlas = ImageData::new(width, (rows * step).saturating_sub(ATLAS_SPACING));
+        for (id, pattern) in patterns.iter().enumerate() {
+            let (left, top) = ((id % columns) * step, (id / columns) * step);
+            for (row, pixels) in pattern.chunks(self.wfc_tile_sz).enumerate() {
+                let start = left + (top + row) * width;
+                atlas.pixels_mut()[start..start + pixels.len()].copy_from_slice(pixels);
+            }
+        }
+        Ok(atlas)
+    }
+}
+
+//How cells that have not been collapsed yet are drawn
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PartialRender {
+    //Blend the colors of all remaining tiles
+    Average,
+    //Blend the colors weighted by their alpha so that transparent tiles
+    //don't darken the opaque ones, the alpha is the average of the tiles
+    PremultipliedAverage,
+    //Use the color of the most frequent remaining tile
+    MostLikely,
+    //Leave the cell empty
+    Blank,
+}
+
+//How the output is drawn
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RenderOptions {
+    pub partial: PartialRender,
+    //Color of cells that have no tiles left after a contradiction, masked out
+    //cells are always left empty
+    pub contradiction_color: u32,
+    //Color of cells with an id that is not one of the tiles, which rules or
+    //constraints that were edited by hand can leave behind
+    pub invalid_color: u32,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            partial: PartialRender::Average,
+            contradiction_color: 0,
+            invalid_color: 0,
+        }
+    }
+}
+
+//Set once a cell with an invalid id was reported so that redrawing the same
+//state doesn't report it again every frame
+static REPORTED_INVALID_ID: AtomicBool = AtomicBool::new(false);
+
+//Grayscale image of the entropy of each cell, a cell with every tile left is
+//white and collapsed or masked out cells are black. Removing the common
+//tiles from a cell can raise its entropy so brightness is capped at white
+pub fn entropy_image(
+    superpositions: &[Superposition],
+    masked: &[bool],
+    frequencies: &[u32],
+    w: usize,
+    h: usize,
+) -> ImageData {
+    let all_tiles: Vec<usize> = (0..frequencies.len()).collect();
+    let max_entropy = entropy(all_tiles.iter().copied(), frequencies);
+    let pixels: Vec<u32> = superpositions
+        .iter()
+        .zip(masked)
+        .map(|(superposition, masked)| {
+            let brightness = if *masked || max_entropy <= 0.0 {
+                0.0
+            } else {
+                (entropy(superposition.iter(), frequencies) / max_entropy).min(1.0)
+            };
+            let value = (brightness * 255.0).round() as u32;
+            0xff000000 | value << 16 | value << 8 | value
+        })
+        .collect();
+    ImageData::from_pixels(&pixels, w, h)
+}
+
+//One x,y,remaining,entropy row for each cell after a header, remaining is the
+//number of tiles the cell has left (0 for masked cells) and entropy is in
+//bits so collapsed cells have an entropy of 0
+pub fn entropy_csv(
+    superpositions: &[Superposition],
+    masked: &[bool],
+    frequencies: &[u32],
+    w: usize,
+) -> String {
+    let mut csv = String::from("x,y,remaining,entropy\n");
+    for (i, superposition) in superpositions.iter().enumerate() {
+        let (remaining, cell_entropy) = if masked[i] {
+            (0, 0.0)
+        } else if superposition.len() <= 1 {
+            //Avoids writing -0 for collapsed cells
+            (superposition.len(), 0.0)
+        } else {
+            (
+                superposition.len(),
+                entropy(superposition.iter(), frequencies),
+            )
+        };
+        csv += &format!("{},{},{remaining},{cell_entropy}\n", i % w, i / w);
+    }
+    csv
+}
+
+//The cell at (x, y) is stored at index y * w + x in both the grid and
+//the superpositions
+pub fn copy_superpositions_to_grid(
+    grid: &mut [u32],
+    superpositions: &[Superposition],
+    masked: &[bool],
+    (w, h): (usize, usize),
+    wfc_tiles: &[u32],
+    frequencies: &[u32],
+    render_options: &RenderOptions,
+) {
+    debug_assert_eq!(grid.len(), w * h);
+    debug_assert_eq!(superpositions.len(), w * h);
+    debug_assert_eq!(masked.len(), w * h);
+
+    let tiles = wfc_tiles.len().min(frequencies.len());
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let superposition = &superpositions[i];
+            let invalid = superposition.last().filter(|tile| *tile >= tiles);
+            grid[i] = if masked[i] {
+                0
+            } else if let Some(tile) = invalid {
+                if !REPORTED_INVALID_ID.swap(true, Ordering::Relaxed) {
+                    eprintln!("cell ({x}, {y}) has tile {tile} but there are only {tiles} tiles");
+                }
+                render_options.invalid_color
+            } else if superposition.is_empty() {
+                //No tile is allowed here so propagation failed at this cell
+                render_options.contradiction_color
+            } else if superposition.len() > 1 {
+                match render_options.partial {
+                    PartialRender::Average => average_color(superposition, wfc_tiles),
+                    PartialRender::PremultipliedAverage => {
+                        premultiplied_average_color(superposition, wfc_tiles)
+                    }
+                    PartialRender::MostLikely => {
+                        most_likely_color(superposition, wfc_tiles, frequencies)
+                    }
+                    PartialRender::Blank => 0,
+                }
+            } else {
+                superposition.first().map_or(0, |tile| wfc_tiles[tile])
+            };
+        }
+    }
+}
+
+//Same as copy_superpositions_to_grid but returns a new image instead of
+//writing to an existing grid, useful for keeping snapshots of the state
+pub fn superpositions_to_image(
+    superpositions: &[Superposition],
+    masked: &[bool],
+    wfc_tiles: &[u32],
+    frequencies: &[u32],
+    w: usize,
+    h: usize,
+    render_options: &RenderOptions,
+) -> ImageData {
+    let mut image = ImageData::new(w, h);
+    copy_superpositions_to_grid(
+        image.pixels_mut(),
+        superpositions,
+        masked,
+        (w, h),
+        wfc_tiles,
+        frequencies,
+        render_options,
+    );
+    image
+}
+
+//Converts a channel in 0.0..=1.0 to 0..=255, rounding to the nearest value
+fn channel_to_u32(channel: f32) -> u32 {
+    (channel * 255.0 + 0.5).clamp(0.0, 255.0) as u32
+}
+
+fn average_color(superposition: &Superposition, wfc_tiles: &[u32]) -> u32 {
+    let (mut r, mut g, mut b) = (0.0f32, 0.0f32, 0.0f32);
+    let mut count = 0.0f32;
+    for val in superposition.iter() {
+        let col = u32_to_color(wfc_tiles[val]);
+        r += col.0;
+        g += col.1;
+        b += col.2;
+        count += 1.0;
+    }
+    let (avg_r, avg_g, avg_b) = (r / count, g / count, b / count);
+    let (avg_r, avg_g, avg_b) = (
+        channel_to_u32(avg_r),
+        channel_to_u32(avg_g),
+        channel_to_u32(avg_b),
+    );
+    avg_b << 16 | avg_g << 8 | avg_r | 0xff << 24
+}
+
+fn premultiplied_average_color(superposition: &Superposition, wfc_tiles: &[u32]) -> u32 {
+    let (mut r, mut g, mut b, mut a) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+    for val in superposition.iter() {
+        let col = u32_to_color(wfc_tiles[val]);
+        let alpha = (wfc_tiles[val] >> 24) as f32 / 255.0;
+        r += col.0 * alpha;
+        g += col.1 * alpha;
+        b += col.2 * alpha;
+        a += alpha;
+    }
+    //Every tile is fully transparent
+    if a == 0.0 {
+        return 0;
+    }
+
+    //Dividing by the total alpha instead of the count un-premultiplies
+    let count = superposition.len() as f32;
+    let (avg_r, avg_g, avg_b, avg_a) = (
+        channel_to_u32(r / a),
+        channel_to_u32(g / a),
+        channel_to_u32(b / a),
+        channel_to_u32(a / count),
+    );
+    avg_a << 24 | avg_b << 16 | avg_g << 8 | avg_r
+}
+
+//If multiple tiles share the highest frequency, the first one is used
+fn most_likely_color(superposition: &Superposition, wfc_tiles: &[u32], frequencies: &[u32]) -> u32 {
+    let mut most_likely = superposition.first().unwrap_or(0);
+    for tile in superposition.iter() {
+        if frequencies[tile] > frequencies[most_likely] {
+            most_likely = tile;
+        }
+    }
+    wfc_tiles[most_likely]
+}
@@ -0,0 +1,580 @@
+use super::{entropy, neighbors, ContradictionPolicy, WFCParameters, WFCState};
+use crate::image_data::ImageData;
+use std::{collections::HashMap, fmt};
+
+//How make_symmetric repairs a pair that is only allowed one way around
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SymmetryPolicy {
+    //The pair is forbidden both ways
+    And,
+    //The pair is allowed both ways
+    Or,
+}
+
+//Index of the direction pointing the opposite way for each offset
+fn opposites(offsets: &[(isize, isize)]) -> Result<Vec<usize>, String> {
+    offsets
+        .iter()
+        .map(|(dx, dy)| {
+            offsets
+                .iter()
+                .position(|offset| *offset == (-dx, -dy))
+                .ok_or_else(|| format!("offset ({dx}, {dy}) has no opposite offset"))
+        })
+        .collect()
+}
+
+#[derive(Clone, PartialEq)]
+pub struct RuleTable {
+    pub(super) rules: Vec<bool>,
+    //How likely each allowed pair is, indexed the same way as rules. This is
+    //only allocated once a weight is set, until then every pair has weight 1
+    pub(super) weights: Option<Vec<f32>>,
+    //The same rules stored as a bitset of the allowed neighbors for each
+    //tile and direction so they can be combined with bitwise or
+    pub(super) neighbors: Vec<u64>,
+    pub(super) tile_count: usize,
+    //(x, y) offset to the neighbor in each direction
+    pub(super) offsets: Vec<(isize, isize)>,
+    //Index of the direction pointing the opposite way for each direction
+    pub(super) opposites: Vec<usize>,
+}
+
+impl RuleTable {
+    //Creates a table where nothing is allowed, every offset needs to have
+    //its opposite in the list as well
+    pub fn new(count: usize, offsets: &[(isize, isize)]) -> Result<Self, String> {
+        let opposites = opposites(offsets)?;
+        Ok(Self {
+            rules: vec![false; count * count * offsets.len()],
+            weights: None,
+            neighbors: vec![0; count * offsets.len() * count.div_ceil(64)],
+            tile_count: count,
+            offsets: offsets.to_vec(),
+            opposites,
+        })
+    }
+
+    //Bytes needed for the rules of count tiles without weights, a bool for
+    //every pair in every direction plus the bitsets of allowed neighbors
+    pub fn bytes(count: usize, directions: usize) -> u128 {
+        let (count, directions) = (count as u128, directions as u128);
+        let rules = count.saturating_mul(count).saturating_mul(directions);
+        let neighbors = (count * directions).saturating_mul(count.div_ceil(64) * 8);
+        rules.saturating_add(neighbors)
+    }
+
+    pub(super) fn index(&self, direction: usize, id1: usize, id2: usize) -> usize {
+        id1 * self.tile_count * self.offsets.len() + direction * self.tile_count + id2
+    }
+
+    pub fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+
+    //The same rules with each direction moved to a new offset, the new
+    //offsets have to pair up into opposites the same way as the old ones
+    pub fn with_offsets(&self, offsets: &[(isize, isize)]) -> Result<Self, String> {
+        if opposites(offsets)? != self.opposites {
+            return Err(format!(
+                "offsets {offsets:?} don't pair up the same way as {:?}",
+                self.offsets
+            ));
+        }
+        Ok(Self {
+            offsets: offsets.to_vec(),
+            ..self.clone()
+        })
+    }
+
+    //The direction pointing the opposite way
+    pub fn opposite(&self, direction: usize) -> usize {
+        self.opposites[direction]
+    }
+
+    //Number of u64s in the bitset of one tile and direction
+    pub(super) fn words(&self) -> usize {
+        self.tile_count.div_ceil(64)
+    }
+
+    //Bitset of the tiles that are allowed in the given direction from id1
+    pub fn allowed_neighbors(&self, direction: usize, id1: usize) -> &[u64] {
+        let start = (id1 * self.offsets.len() + direction) * self.words();
+        &self.neighbors[start..(start + self.words())]
+    }
+
+    fn set_neighbor(&mut self, direction: usize, id1: usize, id2: usize, allowed: bool) {
+        let index = (id1 * self.offsets.len() + direction) * self.words() + id2 / 64;
+        if allowed {
+            self.neighbors[index] |= 1 << (id2 % 64);
+        } else {
+            self.neighbors[index] &= !(1 << (id2 % 64));
+        }
+    }
+
+    fn set_weight(&mut self, direction: usize, id1: usize, id2: usize, weight: f32) {
+        let index = self.index(direction, id1, id2);
+        let weights = self
+            .weights
+            .get_or_insert_with(|| vec![1.0; self.rules.len()]);
+        weights[index] = weight;
+    }
+
+    pub fn weight(&self, direction: usize, id1: usize, id2: usize) -> f32 {
+        match &self.weights {
+            Some(weights) => weights[self.index(direction, id1, id2)],
+            None => 1.0,
+        }
+    }
+
+    pub fn has_weights(&self) -> bool {
+        self.weights.is_some()
+    }
+
+    pub fn add_rule(&mut self, direction: usize, id1: usize, id2: usize) {
+        let index = self.index(direction, id1, id2);
+        self.rules[index] = true;
+        self.set_neighbor(direction, id1, id2, true);
+    }
+
+    pub fn directions(&self) -> usize {
+        self.offsets.len()
+    }
+
+    //The direction with the given offset, if there is one
+    pub fn direction(&self, offset: (isize, isize)) -> Option<usize> {
+        self.offsets
+            .iter()
+            .position(|direction| *direction == offset)
+    }
+
+    pub fn remove_rule(&mut self, direction: usize, id1: usize, id2: usize) {
+        let index = self.index(direction, id1, id2);
+        self.rules[index] = false;
+        self.set_neighbor(direction, id1, id2, false);
+    }
+
+    pub fn okay(&self, direction: usize, id1: usize, id2: usize) -> bool {
+        self.rules[self.index(direction, id1, id2)]
+    }
+
+    //Lists the (direction, id1, id2) rules that allow id2 in the direction
+    //from id1 without allowing id1 in the opposite direction from id2,
+    //propagation assumes that this never happens
+    pub fn check_symmetry(&self) -> Vec<(usize, usize, usize)> {
+        let mut asymmetric = vec![];
+        for id1 in 0..self.tile_count {
+            for direction in 0..self.directions() {
+                for id2 in 0..self.tile_count {
+                    if self.okay(direction, id1, id2)
+                        && !self.okay(self.opposite(direction), id2, id1)
+                    {
+                        asymmetric.push((direction, id1, id2));
+                    }
+                }
+            }
+        }
+        asymmetric
+    }
+
+    //Repairs the rules found by check_symmetry, returns how many were changed
+    #[allow(dead_code)]
+    pub fn make_symmetric(&mut self, policy: SymmetryPolicy) -> usize {
+        let asymmetric = self.check_symmetry();
+        for &(direction, id1, id2) in &asymmetric {
+            match policy {
+                SymmetryPolicy::And => self.remove_rule(direction, id1, id2),
+                SymmetryPolicy::Or => self.add_rule(self.opposite(direction), id2, id1),
+            }
+        }
+        asymmetric.len()
+    }
+}
+
+//Returned by WFCParameters::difficulty_estimate
+#[allow(dead_code)]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Difficulty {
+    pub tiles: usize,
+    //Average number of tiles allowed next to a tile in one direction
+    pub average_neighbors: f32,
+    //average_neighbors as a fraction of the tiles, 1 if every pair is allowed
+    pub rule_density: f32,
+    //Entropy of the frequencies divided by the largest possible entropy, 1
+    //if every tile is as common as the others
+    pub frequency_evenness: f32,
+    //0 for rules that never fail up to 1 for rules that almost always do
+    pub score: f32,
+}
+
+//A problem with the rules that makes them impossible to satisfy
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Issue {
+    //The tile can not have anything next to it in the given direction
+    DeadEnd {
+        tile: usize,
+        direction: usize,
+    },
+    //id2 is allowed in the direction from id1 but id1 is not allowed in the
+    //opposite direction from id2
+    Asymmetric {
+        direction: usize,
+        id1: usize,
+        id2: usize,
+    },
+    //The tile of ContradictionPolicy::Fill is not one of the tiles
+    UnknownFillTile {
+        tile: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::DeadEnd { tile, direction } => {
+                write!(
+                    f,
+                    "tile {tile} has no allowed neighbor in direction {direction}"
+                )
+            }
+            Self::Asymmetric {
+                direction,
+                id1,
+                id2,
+            } => {
+                write!(
+                    f,
+                    "tile {id2} is allowed in direction {direction} from tile {id1} but not the other way around"
+                )
+            }
+            Self::UnknownFillTile { tile } => {
+                write!(f, "there is no tile {tile} to fill contradictions with")
+            }
+        }
+    }
+}
+
+//A collapsed neighbor of a cell with no tiles left and the tiles the rules
+//allow in the cell next to it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct NeighborConstraint {
+    //Direction from the cell to the neighbor
+    pub direction: usize,
+    pub x: usize,
+    pub y: usize,
+    pub tile: usize,
+    pub allowed: Vec<usize>,
+}
+
+//Why a cell ran out of tiles, the tiles left are the ones that every
+//collapsed neighbor allows. If none are left the collapsed neighbors ruled
+//out every tile between them, otherwise cells that are not collapsed yet
+//ruled out the rest
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Explanation {
+    pub x: usize,
+    pub y: usize,
+    pub constraints: Vec<NeighborConstraint>,
+    pub remaining: Vec<usize>,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn ids(ids: &[usize]) -> String {
+            let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+            ids.join(" ")
+        }
+
+        write!(f, "cell ({}, {}) has no tiles left", self.x, self.y)?;
+        for constraint in &self.constraints {
+            write!(
+                f,
+                "\n  tile {} at ({}, {}) (direction {}) allows: {}",
+                constraint.tile,
+                constraint.x,
+                constraint.y,
+                constraint.direction,
+                ids(&constraint.allowed)
+            )?;
+        }
+        if self.remaining.is_empty() {
+            write!(f, "\n  no tile is allowed by all of them")
+        } else {
+            write!(
+                f,
+                "\n  {} are allowed by all of them, the cells that are not collapsed ruled them out",
+                ids(&self.remaining)
+            )
+        }
+    }
+}
+
+impl WFCParameters {
+    //Advisory guess of how likely generating is to run into contradictions,
+    //without generating anything. Contradictions happen when a cell runs out
+    //of tiles, which is more likely the fewer pairs of tiles the rules allow:
+    // - sparsity (1 - rule density) scales the whole score, rules that allow
+    //   every pair never fail
+    // - few allowed neighbors per tile weigh the most, every collapse then
+    //   removes most of the options of the cells around it
+    // - evenly spread frequencies add a little, rare tiles (which tend to
+    //   have the strictest rules) are picked more often then
+    //The score is sparsity * (0.25 + 0.5 / average neighbors + 0.25 *
+    //evenness), between 0 (never fails) and 1
+    pub fn difficulty_estimate(&self) -> Difficulty {
+        let tiles = self.wfc_tiles.len();
+        let directions = self.wfc_rules.directions();
+        if tiles == 0 || directions == 0 {
+            return Difficulty::default();
+        }
+
+        let mut allowed = 0;
+        for direction in 0..directions {
+            for id1 in 0..tiles {
+                allowed += self
+                    .wfc_rules
+                    .allowed_neighbors(direction, id1)
+                    .iter()
+                    .map(|word| word.count_ones() as usize)
+                    .sum::<usize>();
+            }
+        }
+        let average_neighbors = allowed as f32 / (tiles * directions) as f32;
+        let rule_density = average_neighbors / tiles as f32;
+        let frequency_evenness = if tiles > 1 {
+            entropy(0..tiles, &self.wfc_frequency) / (tiles as f32).log2()
+        } else {
+            1.0
+        };
+
+        let sparsity = 1.0 - rule_density;
+        let branching = 1.0 / average_neighbors.max(1.0);
+        Difficulty {
+            tiles,
+            average_neighbors,
+            rule_density,
+            frequency_evenness,
+            score: sparsity * (0.25 + 0.5 * branching + 0.25 * frequency_evenness),
+        }
+    }
+
+    //Checks an image (a generated output or an edited one) against the
+    //rules. Each pixel can be any of the tiles of its color and every pair
+    //of neighboring pixels has to allow at least one pair of those tiles,
+    //the edges wrap around if they do when generating. Returns the (x, y,
+    //direction) of each pair that doesn't, a pixel whose color is not the
+    //color of any tile breaks every pair it is in
+    pub fn validate_image(&self, image: &ImageData) -> Vec<(usize, usize, usize)> {
+        let (w, h) = (image.width(), image.height());
+        let directions = self.wfc_rules.directions();
+        let words = self.wfc_tiles.len().div_ceil(64);
+        //Bitsets of the tiles of each color and of the tiles allowed next to
+        //any of them in each direction
+        let mut colors = HashMap::<u32, (Vec<u64>, Vec<u64>)>::new();
+        for pixel in image.pixels() {
+            if colors.contains_key(pixel) {
+                continue;
+            }
+            let mut tiles = vec![0; words];
+            let mut allowed = vec![0; words * directions];
+            for id in self.tiles_with_color(*pixel) {
+                tiles[id / 64] |= 1 << (id % 64);
+                for direction in 0..directions {
+                    let neighbors = self.wfc_rules.allowed_neighbors(direction, id);
+                    for (word, neighbor) in allowed[direction * words..].iter_mut().zip(neighbors) {
+                        *word |= neighbor;
+                    }
+                }
+            }
+            colors.insert(*pixel, (tiles, allowed));
+        }
+
+        let mut violations = vec![];
+        for y in 0..h {
+            for x in 0..w {
+                let (_, allowed) = &colors[&image.get_pixel(x, y)];
+                for (direction, adj_x, adj_y) in
+                    neighbors(self.wfc_rules.offsets(), x, y, w, h, self.wfc_boundary)
+                {
+                    let (adj_tiles, _) = &colors[&image.get_pixel(adj_x, adj_y)];
+                    let okay = allowed[direction * words..]
+                        .iter()
+                        .zip(adj_tiles)
+                        .any(|(allowed, tile)| allowed & tile != 0);
+                    if !okay {
+                        violations.push((x, y, direction));
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+
+    //Lists the collapsed neighbors of the cell at (x, y) and the tiles each of
+    //them allows in it, used to explain why the cell ran out of tiles
+    pub fn explain_contradiction(
+        &self,
+        wfc_state: &WFCState,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+    ) -> Explanation {
+        let tiles = self.wfc_tiles.len();
+        let mut constraints = vec![];
+        let mut remaining: Vec<usize> = (0..tiles).collect();
+        for (direction, adj_x, adj_y) in
+            neighbors(self.wfc_rules.offsets(), x, y, w, h, self.wfc_boundary)
+        {
+            let neighbor = &wfc_state.superpositions[adj_x + adj_y * w];
+            let Some(tile) = neighbor.first().filter(|_| neighbor.len() == 1) else {
+                continue;
+            };
+
+            //The cell is in the opposite direction from the neighbor
+            let opposite = self.wfc_rules.opposite(direction);
+            let allowed: Vec<usize> = (0..tiles)
+                .filter(|id| self.wfc_rules.okay(opposite, tile, *id))
+                .collect();
+            remaining.retain(|id| allowed.contains(id));
+            constraints.push(NeighborConstraint {
+                direction,
+                x: adj_x,
+                y: adj_y,
+                tile,
+                allowed,
+            });
+        }
+
+        Explanation {
+            x,
+            y,
+            constraints,
+            remaining,
+        }
+    }
+
+    //Checks that the rules can be satisfied before generating, every tile
+    //needs at least one neighbor that is allowed in each direction and the
+    //rules need to agree in both directions. In a finite output a tile
+    //without neighbors in a direction can still be on that edge, and when
+    //contradictions are filled the cells next to it are filled instead
+    pub fn validate(&self) -> Result<(), Vec<Issue>> {
+        let tile_count = self.wfc_tiles.len();
+        let mut issues = vec![];
+        //Only the directions that cross edges that wrap always have a
+        //neighbor in them
+        let boundary = self.wfc_boundary;
+        let dead_end_directions: Vec<usize> = match self.wfc_contradiction_policy {
+            ContradictionPolicy::Fail => (0..self.wfc_rules.directions())
+                .filter(|direction| {
+                    let (dx, dy) = self.wfc_rules.offsets()[*direction];
+                    (dx == 0 || boundary.wraps_x()) && (dy == 0 || boundary.wraps_y())
+                })
+                .collect(),
+            ContradictionPolicy::Fill(_) => vec![],
+        };
+        for tile in 0..tile_count {
+            for direction in dead_end_directions.iter().copied() {
+                if !(0..tile_count).any(|id2| self.wfc_rules.okay(direction, tile, id2)) {
+                    issues.push(Issue::DeadEnd { tile, direction });
+                }
+            }
+        }
+        if let ContradictionPolicy::Fill(tile) = self.wfc_contradiction_policy {
+            if tile >= tile_count {
+                issues.push(Issue::UnknownFillTile { tile });
+            }
+        }
+        for (direction, id1, id2) in self.wfc_rules.check_symmetry() {
+            issues.push(Issue::Asymmetric {
+                direction,
+                id1,
+                id2,
+            });
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    //For each tile, the ids of the tiles that are allowed next to it in the
+    //given direction
+    pub fn adjacency_graph(&self, direction: usize) -> Vec<Vec<usize>> {
+        let tile_count = self.wfc_tiles.len();
+        (0..tile_count)
+            .map(|id1| {
+                (0..tile_count)
+                    .filter(|id2| self.wfc_rules.okay(direction, id1, *id2))
+                    .collect()
+            })
+            .collect()
+    }
+
+    //Makes id2 less likely (or more likely if weight > 1) to be placed in the
+    //given direction from id1 without forbidding it, when a tile is collapsed
+    //the weight of each option is multiplied by the weights of the pairs it
+    //would make with its collapsed neighbors
+    #[allow(dead_code)]
+    pub fn discourage(&mut self, direction: usize, id1: usize, id2: usize, weight: f32) {
+        self.wfc_rules.set_weight(direction, id1, id2, weight);
+        self.wfc_rules
+            .set_weight(self.wfc_rules.opposite(direction), id2, id1, weight);
+    }
+
+    //Prevents id2 from being placed in the given direction from id1, and
+    //id1 in the opposite direction from id2 since propagation assumes that
+    //the rules agree in both directions
+    #[allow(dead_code)]
+    pub fn forbid(&mut self, direction: usize, id1: usize, id2: usize) {
+        self.wfc_rules.remove_rule(direction, id1, id2);
+        self.wfc_rules
+            .remove_rule(self.wfc_rules.opposite(direction), id2, id1);
+    }
+
+    //Allows id2 in the given direction from id1 and id1 in the opposite
+    //direction from id2
+    pub fn allow_symmetric(&mut self, direction: usize, id1: usize, id2: usize) {
+        self.wfc_rules.add_rule(direction, id1, id2);
+        self.wfc_rules
+            .add_rule(self.wfc_rules.opposite(direction), id2, id1);
+    }
+
+    //Allows up to count forbidden pairs (in both directions), the pairs of
+    //the most frequent tiles are allowed first since they are the most
+    //likely to be needed. Returns how many pairs were allowed
+    pub(super) fn relax(&mut self, count: usize) -> usize {
+        let tile_count = self.wfc_tiles.len();
+        let mut forbidden = vec![];
+        for id1 in 0..tile_count {
+            for direction in 0..self.wfc_rules.directions() {
+                for id2 in 0..tile_count {
+                    if !self.wfc_rules.okay(direction, id1, id2) {
+                        let score = self.wfc_frequency[id1] as u64 * self.wfc_frequency[id2] as u64;
+                        forbidden.push((score, direction, id1, id2));
+                    }
+                }
+            }
+        }
+        forbidden.sort_by_key(|pair| std::cmp::Reverse(pair.0));
+
+        let mut allowed = 0;
+        for (_, direction, id1, id2) in forbidden {
+            if allowed >= count {
+                break;
+            }
+            //The other direction of a pair that was just allowed
+            if self.wfc_rules.okay(direction, id1, id2) {
+                continue;
+            }
+            self.allow_symmetric(direction, id1, id2);
+            allowed += 1;
+        }
+        allowed
+    }
+}
@@ -0,0 +1,190 @@
+use super::{check_memory, RuleTable, WFCParameters};
+use std::fs;
+
+impl WFCParameters {
+    //Writes the tiles and the rules in the binary format read by
+    //load_rules_bin, which is much smaller and faster to load than sampling
+    //a large input again
+    #[allow(dead_code)]
+    pub fn save_rules_bin(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_rules_bin()).map_err(|e| format!("failed to save {path}: {e}"))
+    }
+
+    //Reads tiles and rules saved with save_rules_bin, everything else is set
+    //to the same defaults as from_image_data
+    #[allow(dead_code)]
+    pub fn load_rules_bin(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("failed to open {path}: {e}"))?;
+        Self::from_rules_bin(&bytes).map_err(|e| format!("{path}: {e}"))
+    }
+
+    //The binary rule format, every number is little endian:
+    // - RULES_MAGIC and RULES_VERSION (u32)
+    // - tile size, tile count and number of directions (u32 each)
+    // - the (x, y) offset of each direction (i32 each)
+    // - the color and then the frequency of each tile (u32 each)
+    // - 1 if the rules have weights, 0 if not (u8)
+    // - the bitsets of allowed neighbors exactly as RuleTable stores them,
+    //   for each tile and direction ceil(tiles / 64) u64s
+    // - if there are weights, one f32 for every tile, direction and tile
+    pub fn to_rules_bin(&self) -> Vec<u8> {
+        let rules = &self.wfc_rules;
+        let mut bytes = RULES_MAGIC.to_vec();
+        let header = [
+            RULES_VERSION,
+            self.wfc_tile_sz as u32,
+            self.wfc_tiles.len() as u32,
+            rules.directions() as u32,
+        ];
+        for value in header {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for (dx, dy) in rules.offsets() {
+            bytes.extend_from_slice(&(*dx as i32).to_le_bytes());
+            bytes.extend_from_slice(&(*dy as i32).to_le_bytes());
+        }
+        for value in self.wfc_tiles.iter().chain(&self.wfc_frequency) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.push(rules.has_weights() as u8);
+        for word in &rules.neighbors {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        for weight in rules.weights.iter().flatten() {
+            bytes.extend_from_slice(&weight.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_rules_bin(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader { bytes };
+        if reader.take(RULES_MAGIC.len())? != RULES_MAGIC {
+            return Err("not a rules file".to_string());
+        }
+        let version = reader.u32()?;
+        if version != RULES_VERSION {
+            return Err(format!(
+                "rules file version {version} is not supported, expected {RULES_VERSION}"
+            ));
+        }
+        let tile_sz = reader.u32()? as usize;
+        let count = reader.u32()? as usize;
+        let directions = reader.u32()? as usize;
+        check_memory(
+            RuleTable::bytes(count, directions),
+            &format!("the rules for {count} tiles"),
+        )?;
+
+        //The lists grow as they are read so that a header with huge counts
+        //fails at the end of the file instead of allocating for them
+        let mut offsets = vec![];
+        for _ in 0..directions {
+            offsets.push((reader.u32()? as i32 as isize, reader.u32()? as i32 as isize));
+        }
+        let mut tiles = vec![];
+        for _ in 0..count {
+            tiles.push(reader.u32()?);
+        }
+        let mut frequency = vec![];
+        for _ in 0..count {
+            frequency.push(reader.u32()?);
+        }
+        let has_weights = match reader.take(1)?[0] {
+            0 => false,
+            1 => true,
+            flag => return Err(format!("invalid weight flag {flag}")),
+        };
+
+        //The bitsets are the largest part of the file, so check that they
+        //are all there before allocating for them
+        let words = count.div_ceil(64);
+        let bitset_bytes = count
+            .checked_mul(directions)
+            .and_then(|len| len.checked_mul(words))
+            .and_then(|len| len.checked_mul(8));
+        if bitset_bytes.is_none_or(|len| len > reader.bytes.len()) {
+            return Err("the rules file is truncated".to_string());
+        }
+
+        let mut rules = RuleTable::new(count, &offsets)?;
+        for word in rules.neighbors.iter_mut() {
+            *word = reader.u64()?;
+        }
+        //Bits past the last tile in the last word of each bitset must be 0
+        if !count.is_multiple_of(64) {
+            let unused_bits = !0u64 << (count % 64);
+            for (i, bitset) in rules.neighbors.chunks(words).enumerate() {
+                if bitset[words - 1] & unused_bits != 0 {
+                    return Err(format!(
+                        "tile {} has neighbors past the last tile {} in direction {}",
+                        i / directions,
+                        count - 1,
+                        i % directions
+                    ));
+                }
+            }
+        }
+        for id1 in 0..count {
+            for direction in 0..directions {
+                for id2 in 0..count {
+                    let index = (id1 * directions + direction) * rules.words() + id2 / 64;
+                    if rules.neighbors[index] & (1 << (id2 % 64)) != 0 {
+                        let index = rules.index(direction, id1, id2);
+                        rules.rules[index] = true;
+                    }
+                }
+            }
+        }
+        if has_weights {
+            let mut weights = vec![1.0; rules.rules.len()];
+            for weight in weights.iter_mut() {
+                *weight = f32::from_bits(reader.u32()?);
+            }
+            rules.weights = Some(weights);
+        }
+        if !reader.bytes.is_empty() {
+            return Err(format!(
+                "{} unexpected bytes at the end of the rules file",
+                reader.bytes.len()
+            ));
+        }
+
+        Ok(Self::from_parts(tiles, rules, frequency, tile_sz))
+    }
+}
+
+//First bytes of a binary rules file and the version of the format, the
+//version changes whenever the layout does
+const RULES_MAGIC: &[u8; 4] = b"WFCR";
+const RULES_VERSION: u32 = 1;
+
+//Reads little endian numbers from the start of bytes, failing instead of
+//reading past the end
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err("the rules file is truncated".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+}
@@ -0,0 +1,634 @@
+use super::{
+    neighbors, pick_tempered, random_element, sample_without_replacement, Boundary, Contradiction,
+    Timings,
+};
+use crate::cell_queue::CellQueue;
+use rand::Rng;
+
+//How many tiles pop_separated looks at for each tile it is asked for
+const SEPARATED_SEARCH: usize = 8;
+
+//The tiles that are still allowed in a cell, stored as a bitset so that a
+//cell takes one bit per tile instead of a usize
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Superposition {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Superposition {
+    //Allows every tile in 0..count, the memory is reused if the bitset is
+    //already large enough
+    pub fn fill(&mut self, count: usize) {
+        self.bits.clear();
+        //Without this the push below can double the capacity
+        self.bits.reserve_exact(count.div_ceil(64));
+        self.bits.resize(count / 64, u64::MAX);
+        if !count.is_multiple_of(64) {
+            self.bits.push((1 << (count % 64)) - 1);
+        }
+        self.len = count;
+    }
+
+    //Removes every tile, the memory is kept
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        self.len = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, tile: usize) -> bool {
+        self.bits
+            .get(tile / 64)
+            .is_some_and(|word| word & (1 << (tile % 64)) != 0)
+    }
+
+    //The tiles in increasing order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + Clone + '_ {
+        self.bits.iter().enumerate().flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    pub fn first(&self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    //The largest tile, without going through the others
+    pub fn last(&self) -> Option<usize> {
+        let (i, word) = self
+            .bits
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, word)| **word != 0)?;
+        Some(i * 64 + 63 - word.leading_zeros() as usize)
+    }
+
+    //Only keeps the given tile
+    pub fn collapse(&mut self, tile: usize) {
+        self.clear();
+        self.bits[tile / 64] |= 1 << (tile % 64);
+        self.len = 1;
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        for (i, word) in self.bits.iter_mut().enumerate() {
+            let mut remaining = *word;
+            while remaining != 0 {
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                if !keep(i * 64 + bit) {
+                    *word &= !(1 << bit);
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    //Bytes allocated for the bitset
+    #[allow(dead_code)]
+    pub fn heap_bytes(&self) -> usize {
+        self.bits.capacity() * std::mem::size_of::<u64>()
+    }
+
+    //Only keeps the tiles that are set in the allowed bitset
+    pub fn intersect(&mut self, allowed: &[u64]) {
+        self.len = 0;
+        for (word, allowed) in self.bits.iter_mut().zip(allowed) {
+            *word &= allowed;
+            self.len += word.count_ones() as usize;
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WFCState {
+    pub(super) superpositions: Vec<Superposition>,
+    pub(super) tile_queue: CellQueue,
+    //Built from the frequencies the state was last reset with
+    pub(super) entropy_weights: EntropyWeights,
+    //Masked out tiles have no options and are skipped when propagating
+    pub(super) masked: Vec<bool>,
+    //Index of the first tile to collapse, picked randomly if None
+    pub(super) start: Option<usize>,
+    //Scratch buffers for the options of the tile being collapsed and their
+    //weights, kept so that collapsing doesn't allocate every step
+    pub(super) options: Vec<usize>,
+    pub(super) weights: Vec<u32>,
+    //Cells that ran out of tiles and were filled with the tile of
+    //ContradictionPolicy::Fill, in the order they were filled
+    pub(super) filled: Vec<usize>,
+    //Whether each cell is in filled, so that checking doesn't search the list
+    pub(super) is_filled: Vec<bool>,
+    //Number of cells with more than one tile left, kept up to date as the
+    //cells change so it doesn't have to be counted every step
+    pub(super) remaining: usize,
+    //Time spent solving since the last reset, only measured if the
+    //parameters ask for it
+    pub(super) timings: Timings,
+}
+
+impl WFCState {
+    pub fn new<R: Rng>(
+        w: usize,
+        h: usize,
+        tiles: &[u32],
+        frequencies: &[u32],
+        rng: &mut R,
+    ) -> Self {
+        Self::new_masked(w, h, tiles, frequencies, &vec![false; w * h], rng)
+    }
+
+    //Same as new but tiles where masked is true are left blank
+    pub fn new_masked<R: Rng>(
+        w: usize,
+        h: usize,
+        tiles: &[u32],
+        frequencies: &[u32],
+        masked: &[bool],
+        rng: &mut R,
+    ) -> Self {
+        let mut wfc_state = Self {
+            superpositions: vec![Superposition::default(); w * h],
+            tile_queue: CellQueue::default(),
+            entropy_weights: EntropyWeights::default(),
+            masked: Vec::from(masked),
+            start: None,
+            options: vec![],
+            weights: vec![],
+            filled: vec![],
+            is_filled: vec![],
+            remaining: 0,
+            timings: Timings::default(),
+        };
+        wfc_state.reset(tiles, frequencies, rng);
+        wfc_state
+    }
+
+    //Puts every tile that isn't masked out back into its initial state,
+    //reusing the memory that was already allocated
+    pub fn reset<R: Rng>(&mut self, tiles: &[u32], frequencies: &[u32], rng: &mut R) {
+        for (i, superposition) in self.superpositions.iter_mut().enumerate() {
+            if self.masked[i] {
+                superposition.clear();
+            } else {
+                superposition.fill(tiles.len());
+            }
+        }
+
+        self.remaining = self
+            .superpositions
+            .iter()
+            .filter(|superposition| superposition.len() > 1)
+            .count();
+        self.tile_queue.reset(self.superpositions.len(), rng);
+        self.timings = Timings::default();
+        self.filled.clear();
+        self.is_filled.clear();
+        self.is_filled.resize(self.superpositions.len(), false);
+        self.entropy_weights = EntropyWeights::new(frequencies);
+        let start = match self.start {
+            Some(index) if !self.masked[index] => Some(index),
+            _ => {
+                let unmasked: Vec<usize> = (0..self.superpositions.len())
+                    .filter(|i| !self.masked[*i])
+                    .collect();
+                random_element(&unmasked, rng, None)
+            }
+        };
+        if let Some(start) = start {
+            let start_entropy = self.entropy_weights.entropy(&self.superpositions[start]);
+            self.tile_queue.push(start, start_entropy);
+        }
+    }
+
+    //Sets which tile is collapsed first and resets the state
+    pub fn set_start<R: Rng>(
+        &mut self,
+        start: Option<usize>,
+        tiles: &[u32],
+        frequencies: &[u32],
+        rng: &mut R,
+    ) {
+        self.start = start;
+        self.reset(tiles, frequencies, rng);
+    }
+
+    pub fn superpositions(&self) -> &[Superposition] {
+        &self.superpositions
+    }
+
+    //Lets the tiles of a cell be edited without updating the queue or the
+    //count, only meant for checking that check_invariants notices
+    #[allow(dead_code)]
+    pub fn superposition_mut(&mut self, index: usize) -> &mut Superposition {
+        &mut self.superpositions[index]
+    }
+
+    //Bytes allocated for the superpositions of the cells, which is most of
+    //the memory a state uses
+    #[allow(dead_code)]
+    pub fn superposition_bytes(&self) -> usize {
+        self.superpositions.capacity() * std::mem::size_of::<Superposition>()
+            + self
+                .superpositions
+                .iter()
+                .map(Superposition::heap_bytes)
+                .sum::<usize>()
+    }
+
+    pub fn masked(&self) -> &[bool] {
+        &self.masked
+    }
+
+    pub fn done(&self) -> bool {
+        self.tile_queue.is_empty()
+    }
+
+    //The first cell that has no tiles left, None if there is no
+    //contradiction. count is the number of contradictions so far
+    pub fn contradiction(&self, w: usize, count: u32) -> Option<Contradiction> {
+        let index = (0..self.superpositions.len())
+            .find(|i| !self.masked[*i] && self.superpositions[*i].is_empty())?;
+        Some(Contradiction {
+            x: index % w,
+            y: index / w,
+            remaining: self.remaining(),
+            count,
+        })
+    }
+
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+
+    //Number of tiles that have not been collapsed into a single state
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    //Updates the number of remaining cells after a cell went from was
+    //tiles to now tiles
+    pub(super) fn count_change(&mut self, was: usize, now: usize) {
+        self.remaining = self.remaining + (now > 1) as usize - (was > 1) as usize;
+    }
+
+    //Cells that were filled because they ran out of tiles, their neighbors
+    //can break the rules
+    #[allow(dead_code)]
+    pub fn filled(&self) -> &[usize] {
+        &self.filled
+    }
+
+    //Checks that the queue and the superpositions agree with each other, if
+    //the queue runs out while tiles are still not collapsed the generation
+    //would stop without finishing or failing
+    pub fn check_invariants(&self) -> Result<(), String> {
+        for index in self.tile_queue.iter() {
+            if self.masked[index] {
+                return Err(format!("masked tile {index} is in the queue"));
+            }
+            if self.superpositions[index].len() <= 1 {
+                return Err(format!("collapsed tile {index} is in the queue"));
+            }
+        }
+        if self.tile_queue.peek() != self.tile_queue.lowest() {
+            return Err(format!(
+                "the queue would pop tile {:?} but the lowest entropy tile is {:?}",
+                self.tile_queue.peek(),
+                self.tile_queue.lowest()
+            ));
+        }
+
+        for (i, superposition) in self.superpositions.iter().enumerate() {
+            if self.masked[i] && !superposition.is_empty() {
+                return Err(format!(
+                    "masked tile {i} has {} options",
+                    superposition.len()
+                ));
+            }
+        }
+
+        //Cells are only queued once propagation takes away some of their
+        //tiles, so the tiles that are not collapsed are exactly the queued
+        //tiles along with the tiles that still have every tile
+        let tile_count = self.entropy_weights.tile_count();
+        let unqueued = (0..self.superpositions.len()).find(|i| {
+            let len = self.superpositions[*i].len();
+            len > 1 && len < tile_count && !self.tile_queue.contains(*i)
+        });
+        if let Some(index) = unqueued {
+            return Err(format!(
+                "tile {index} has {} of the {tile_count} options but is not in the queue",
+                self.superpositions[index].len()
+            ));
+        }
+
+        if self.done() {
+            let stalled: Vec<usize> = (0..self.superpositions.len())
+                .filter(|i| self.superpositions[*i].len() > 1)
+                .collect();
+            if let Some(first) = stalled.first() {
+                return Err(format!(
+                    "the queue is empty but {} tiles are not collapsed (first is tile {first} with {} options)",
+                    stalled.len(),
+                    self.superpositions[*first].len()
+                ));
+            }
+        }
+
+        let counted = self
+            .superpositions
+            .iter()
+            .filter(|superposition| superposition.len() > 1)
+            .count();
+        if counted != self.remaining {
+            return Err(format!(
+                "{counted} tiles are not collapsed but the count is {}",
+                self.remaining
+            ));
+        }
+
+        Ok(())
+    }
+
+    //Neighbors are found with the offsets of the rules, so hexagons and
+    //frames of an animation count their own neighbors
+    fn collapsed_neighbors(
+        &self,
+        offsets: &[(isize, isize)],
+        index: usize,
+        w: usize,
+        h: usize,
+        boundary: Boundary,
+    ) -> usize {
+        neighbors(offsets, index % w, index / w, w, h, boundary)
+            .filter(|(_, adj_x, adj_y)| self.superpositions[adj_x + adj_y * w].len() == 1)
+            .count()
+    }
+
+    fn next_to_collapsed(
+        &self,
+        offsets: &[(isize, isize)],
+        index: usize,
+        w: usize,
+        h: usize,
+        boundary: Boundary,
+    ) -> bool {
+        self.collapsed_neighbors(offsets, index, w, h, boundary) > 0
+    }
+
+    //Pops the lowest entropy tile, if several tiles share the lowest entropy
+    //then the one with the most collapsed neighbors is chosen
+    pub(super) fn pop_most_constrained(
+        &mut self,
+        offsets: &[(isize, isize)],
+        w: usize,
+        h: usize,
+        boundary: Boundary,
+    ) -> Option<usize> {
+        let first = self.tile_queue.pop()?;
+        let mut tied = vec![first];
+        while let Some(index) = self.tile_queue.peek() {
+            if self.tile_queue.entropy_key(index) != self.tile_queue.entropy_key(first) {
+                break;
+            }
+            tied.extend(self.tile_queue.pop());
+        }
+
+        let best = (0..tied.len())
+            .max_by_key(|i| self.collapsed_neighbors(offsets, tied[*i], w, h, boundary))?;
+        let found = tied.swap_remove(best);
+        for index in tied {
+            self.tile_queue.requeue(index);
+        }
+        Some(found)
+    }
+
+    //Pops the lowest entropy tiles that are at least min_distance away from
+    //every tile in chosen (wrapping around the edges) until chosen has count
+    //tiles. Only a few tiles are looked at so that this stays cheap, the
+    //tiles that are not chosen are put back into the queue. Tiles with the
+    //same entropy are picked in a random order that favors the tiles far
+    //from the ones already chosen
+    pub(super) fn pop_separated<R: Rng>(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        count: usize,
+        min_distance: usize,
+        w: usize,
+        h: usize,
+        rng: &mut R,
+    ) {
+        let mut popped = vec![];
+        while popped.len() < SEPARATED_SEARCH * count {
+            let Some(index) = self.tile_queue.pop() else {
+                break;
+            };
+            popped.push(index);
+        }
+
+        let separated = |chosen: &[usize], index: usize| {
+            chosen
+                .iter()
+                .all(|chosen| wrapped_distance(*chosen, index, w, h) >= min_distance)
+        };
+        //The tiles were popped lowest entropy first so tied tiles are next
+        //to each other
+        let mut start = 0;
+        while chosen.len() < count && start < popped.len() {
+            let key = self.tile_queue.entropy_key(popped[start]);
+            let end = popped[start..]
+                .iter()
+                .position(|index| self.tile_queue.entropy_key(*index) != key)
+                .map_or(popped.len(), |len| start + len);
+            let tied: Vec<usize> = popped[start..end]
+                .iter()
+                .copied()
+                .filter(|index| separated(chosen, *index))
+                .collect();
+            let weights: Vec<u32> = tied
+                .iter()
+                .map(|index| {
+                    chosen
+                        .iter()
+                        .map(|chosen| wrapped_distance(*chosen, *index, w, h))
+                        .min()
+                        .unwrap_or(1) as u32
+                })
+                .collect();
+            for index in sample_without_replacement(&tied, tied.len(), Some(&weights), rng) {
+                //An earlier tile of the same group can be too close
+                if chosen.len() < count && separated(chosen, index) {
+                    chosen.push(index);
+                }
+            }
+            start = end;
+        }
+
+        for index in popped {
+            if !chosen.contains(&index) {
+                self.tile_queue.requeue(index);
+            }
+        }
+    }
+
+    //Pops a random tile, each queued tile is picked with a probability
+    //proportional to exp(-entropy / temperature). This looks at every queued
+    //tile so it is slower than popping the lowest entropy tile
+    pub(super) fn pop_tempered<R: Rng>(&mut self, temperature: f32, rng: &mut R) -> Option<usize> {
+        let queued: Vec<usize> = self.tile_queue.iter().collect();
+        let entropies: Vec<f32> = queued
+            .iter()
+            .map(|index| self.entropy_weights.entropy(&self.superpositions[*index]))
+            .collect();
+        let found = queued[pick_tempered(&entropies, temperature, rng)?];
+        self.tile_queue.remove(found);
+        Some(found)
+    }
+
+    //Pops the lowest entropy tile that is next to a collapsed tile, if there
+    //is no such tile then the lowest entropy tile is popped instead
+    pub(super) fn pop_next_to_collapsed(
+        &mut self,
+        offsets: &[(isize, isize)],
+        w: usize,
+        h: usize,
+        boundary: Boundary,
+    ) -> Option<usize> {
+        let mut skipped = vec![];
+        let mut found = None;
+        while let Some(index) = self.tile_queue.pop() {
+            if self.next_to_collapsed(offsets, index, w, h, boundary) {
+                found = Some(index);
+                break;
+            }
+            skipped.push(index);
+        }
+
+        let mut skipped = skipped.into_iter();
+        let found = found.or_else(|| skipped.next());
+        for index in skipped {
+            self.tile_queue.requeue(index);
+        }
+        found
+    }
+
+    //Keeps the queue in sync after the options of a tile changed, collapsed
+    //tiles are taken out and the entropy of the others is updated
+    pub(super) fn update_queue(&mut self, index: usize) {
+        if self.superpositions[index].len() > 1 {
+            let entropy = self.entropy_weights.entropy(&self.superpositions[index]);
+            self.tile_queue.push(index, entropy);
+        } else {
+            self.tile_queue.remove(index);
+        }
+    }
+}
+
+//Chebyshev distance between two cells of a w x h grid that wraps around
+pub(super) fn wrapped_distance(a: usize, b: usize, w: usize, h: usize) -> usize {
+    let dx = (a % w).abs_diff(b % w);
+    let dy = (a / w).abs_diff(b / w);
+    dx.min(w - dx).max(dy.min(h - dy))
+}
+
+pub fn entropy(superposition: impl Iterator<Item = usize> + Clone, frequencies: &[u32]) -> f32 {
+    let mut total = 0u64;
+    for value in superposition.clone() {
+        total += frequencies[value] as u64;
+    }
+
+    //Avoid dividing by zero if none of the tiles have a frequency
+    if total == 0 {
+        return 0.0;
+    }
+
+    let mut res = 0.0;
+    //Tiles that are never seen add nothing, 0 * log2(0) would be NaN
+    for value in superposition.filter(|value| frequencies[*value] > 0) {
+        let prob = frequencies[value] as f32 / total as f32;
+        res += prob * -prob.log2();
+    }
+    res
+}
+
+//Frequencies divided by their sum, all zero if every frequency is zero
+pub fn normalize_frequencies(frequencies: &[u32]) -> Vec<f32> {
+    let total: u64 = frequencies.iter().map(|frequency| *frequency as u64).sum();
+    if total == 0 {
+        return vec![0.0; frequencies.len()];
+    }
+    frequencies
+        .iter()
+        .map(|frequency| (*frequency as f64 / total as f64) as f32)
+        .collect()
+}
+
+//Normalized frequencies along with p * log2(p) of each tile. The entropy of
+//a cell whose tiles have probabilities summing to s is
+//log2(s) - sum(p * log2(p)) / s, so this only needs one log2 per cell
+//instead of one per tile like entropy
+#[derive(Clone, Default)]
+pub struct EntropyWeights {
+    probabilities: Vec<f32>,
+    plogp: Vec<f32>,
+}
+
+impl EntropyWeights {
+    pub fn new(frequencies: &[u32]) -> Self {
+        let probabilities = normalize_frequencies(frequencies);
+        //Tiles that are never seen add nothing to the entropy
+        let plogp = probabilities
+            .iter()
+            .map(|p| if *p > 0.0 { p * p.log2() } else { 0.0 })
+            .collect();
+        Self {
+            probabilities,
+            plogp,
+        }
+    }
+
+    //Number of tiles the weights were built for
+    pub fn tile_count(&self) -> usize {
+        self.probabilities.len()
+    }
+
+    pub fn entropy(&self, superposition: &Superposition) -> f32 {
+        let (mut total, mut plogp) = (0.0f32, 0.0f32);
+        for tile in superposition.iter() {
+            total += self.probabilities[tile];
+            plogp += self.plogp[tile];
+        }
+
+        //Avoid dividing by zero if none of the tiles have a frequency
+        if total <= 0.0 {
+            return 0.0;
+        }
+        //max would turn NaN into 0 and make the cell the next one collapsed,
+        //infinity puts it last instead
+        let entropy = total.log2() - plogp / total;
+        if entropy.is_nan() {
+            return f32::INFINITY;
+        }
+        //Rounding can make the entropy of a single tile slightly negative
+        entropy.max(0.0)
+    }
+}
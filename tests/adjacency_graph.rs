@@ -0,0 +1,58 @@
+//Checks the adjacency lists of the tiles built from the rules
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{load_input, OFFSETS};
+use wfc::{RuleTable, WFCParameters};
+
+const RIGHT: usize = 1;
+const LEFT: usize = 3;
+
+#[test]
+fn a_tiny_table_gives_the_expected_lists() {
+    //0 -> 1 -> 2 to the right and the opposite to the left, 2 can also be
+    //next to itself
+    let mut rules = RuleTable::new(3, &OFFSETS).unwrap();
+    for (id1, id2) in [(0, 1), (1, 2), (2, 2)] {
+        rules.add_rule(RIGHT, id1, id2);
+        rules.add_rule(LEFT, id2, id1);
+    }
+    let parameters = WFCParameters::from_parts(vec![0, 1, 2], rules, vec![1; 3], 1);
+    assert_eq!(
+        parameters.adjacency_graph(RIGHT),
+        vec![vec![1], vec![2], vec![2]]
+    );
+    assert_eq!(
+        parameters.adjacency_graph(LEFT),
+        vec![vec![], vec![0], vec![1, 2]]
+    );
+    assert_eq!(parameters.adjacency_graph(0), vec![Vec::<usize>::new(); 3]);
+}
+
+#[test]
+fn the_lists_match_the_rules() {
+    let parameters = load_input("inputimage1.png");
+    let tiles = parameters.wfc_tiles.len();
+    for direction in 0..parameters.wfc_rules.directions() {
+        let graph = parameters.adjacency_graph(direction);
+        assert_eq!(graph.len(), tiles);
+        for (id1, neighbors) in graph.iter().enumerate() {
+            assert!(neighbors.windows(2).all(|pair| pair[0] < pair[1]));
+            for id2 in 0..tiles {
+                assert_eq!(
+                    neighbors.contains(&id2),
+                    parameters.wfc_rules.okay(direction, id1, id2),
+                    "{direction} {id1} {id2}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//Checks that discouraged pairs of tiles are rarer but still allowed
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use wfc::WFCParameters;
+
+const TILES: usize = 3;
+//Direction of (1, 0)
+const RIGHT: usize = 1;
+const SIZE: usize = 8;
+const SEEDS: u64 = 32;
+const WEIGHT: f32 = 0.2;
+
+//How often tile 1 and tile 2 are to the right of tile 0
+fn pairs(parameters: &WFCParameters) -> (usize, usize) {
+    let (mut ones, mut twos) = (0, 0);
+    for seed in 0..SEEDS {
+        let ids = parameters.generate_ids_seeded(SIZE, SIZE, seed).unwrap();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if ids[y * SIZE + x] != 0 {
+                    continue;
+                }
+                match ids[y * SIZE + (x + 1) % SIZE] {
+                    1 => ones += 1,
+                    2 => twos += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+    (ones, twos)
+}
+
+#[test]
+fn discouraged_pairs_are_rarer_but_not_gone() {
+    let mut parameters = WFCParameters::from_parts(
+        (0..TILES as u32).collect(),
+        all_allowed(TILES, &OFFSETS),
+        vec![1; TILES],
+        1,
+    );
+    let (ones, twos) = pairs(&parameters);
+    assert!(ones * 2 > twos && twos * 2 > ones, "{ones} and {twos}");
+
+    parameters.discourage(RIGHT, 0, 1, WEIGHT);
+    assert!(parameters.wfc_rules.okay(RIGHT, 0, 1));
+    let (ones, twos) = pairs(&parameters);
+    assert!(ones > 0, "the discouraged pair never appeared");
+    assert!(ones * 2 < twos, "{ones} and {twos}");
+}
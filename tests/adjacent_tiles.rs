@@ -0,0 +1,97 @@
+//Checks that combining the allowed neighbors as bitsets narrows the
+//neighbors down to the same tiles as checking every pair of tiles
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wfc::{neighbors, update_adjacent_tiles, Boundary, RuleTable, Superposition};
+
+const INPUTS: [&str; 3] = ["inputimage1.png", "inputimage4.png", "brick.png"];
+const SIZE: (usize, usize) = (5, 4);
+const SEEDS: u64 = 8;
+
+//Every cell gets a random subset of the tiles, some with a single tile
+fn random_superpositions(tiles: usize, cells: usize, rng: &mut StdRng) -> Vec<Superposition> {
+    (0..cells)
+        .map(|_| {
+            let mut superposition = Superposition::default();
+            superposition.fill(tiles);
+            let keep = rng.gen_range(0.1..1.0);
+            superposition.retain(|_| rng.gen_bool(keep));
+            superposition
+        })
+        .collect()
+}
+
+//Keeps the tiles of each neighbor that at least one tile of the cell allows
+fn naive_update(
+    superpositions: &mut [Superposition],
+    index: usize,
+    (w, h): (usize, usize),
+    rules: &RuleTable,
+    boundary: Boundary,
+) -> usize {
+    let mut collapsed = 0;
+    let options: Vec<usize> = superpositions[index].iter().collect();
+    for (direction, adj_x, adj_y) in
+        neighbors(rules.offsets(), index % w, index / w, w, h, boundary)
+    {
+        let adj = &mut superpositions[adj_x + adj_y * w];
+        let was = adj.len();
+        adj.retain(|tile| {
+            options
+                .iter()
+                .any(|option| rules.okay(direction, *option, tile))
+        });
+        if was > 1 && adj.len() <= 1 {
+            collapsed += 1;
+        }
+    }
+    collapsed
+}
+
+#[test]
+fn bitsets_give_the_same_superpositions() {
+    let (w, h) = SIZE;
+    for input in INPUTS {
+        let parameters = load_input(input);
+        let tiles = parameters.wfc_tiles.len();
+        for boundary in [Boundary::Wrap, Boundary::Finite] {
+            for seed in 0..SEEDS {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let start = random_superpositions(tiles, w * h, &mut rng);
+                for index in 0..w * h {
+                    let mut expected = start.clone();
+                    let expected_collapsed = naive_update(
+                        &mut expected,
+                        index,
+                        (w, h),
+                        &parameters.wfc_rules,
+                        boundary,
+                    );
+                    let mut updated = start.clone();
+                    let collapsed = update_adjacent_tiles(
+                        &mut updated,
+                        index,
+                        w,
+                        h,
+                        &parameters.wfc_rules,
+                        &[false; SIZE.0 * SIZE.1],
+                        boundary,
+                    );
+                    assert_eq!(updated, expected, "{input} seed {seed} at {index}");
+                    assert_eq!(collapsed, expected_collapsed, "{input} seed {seed}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//Checks generating an animation from the frames of an animated png
+#![allow(dead_code)]
+
+#[path = "../src/animation.rs"]
+mod animation;
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use animation::AnimatedParameters;
+use image_data::ImageData;
+use wfc::Boundary;
+
+const RED: u32 = 0xff0000ff;
+const GREEN: u32 = 0xff00ff00;
+const BLUE: u32 = 0xffff0000;
+const SIZE: usize = 6;
+const SEEDS: u64 = 8;
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("animation_{}_{name}", std::process::id()));
+    path.to_string_lossy().into_owned()
+}
+
+//The left half of the frame is left and the right half is green
+fn frame(left: u32) -> ImageData {
+    let pixels: Vec<u32> = (0..SIZE * SIZE)
+        .map(|i| if i % SIZE < SIZE / 2 { left } else { GREEN })
+        .collect();
+    ImageData::from_pixels(&pixels, SIZE, SIZE)
+}
+
+#[test]
+fn frames_follow_each_other_like_in_the_input() {
+    //Red turns blue and blue turns red again, green stays green
+    let path = temp_path("input.png");
+    image_data::save_apng(&[frame(RED), frame(BLUE)], &path).unwrap();
+    let frames = ImageData::load_png_frames(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(frames.len(), 2);
+
+    let parameters = AnimatedParameters::from_frames(&frames, 1).unwrap();
+    for seed in 0..SEEDS {
+        let generated = parameters.generate_seeded(SIZE, SIZE, 2, seed).unwrap();
+        assert_eq!(generated.len(), 2);
+        for (first, second) in generated[0].pixels().iter().zip(generated[1].pixels()) {
+            assert!(
+                matches!(
+                    (*first, *second),
+                    (RED, BLUE) | (BLUE, RED) | (GREEN, GREEN)
+                ),
+                "seed {seed}: {first:#x} is followed by {second:#x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn frames_wrap_around_on_their_own() {
+    //Two frames of two rows each, moving a whole frame goes to the same
+    //row of the other frame
+    let offsets = [(0, 1), (0, -1), (0, 2), (0, -2)];
+    let rows: Vec<usize> = wfc::neighbors(&offsets, 0, 1, 1, 4, Boundary::Frames(2))
+        .map(|(_, _, y)| y)
+        .collect();
+    assert_eq!(rows, [0, 0, 3, 3]);
+    let rows: Vec<usize> = wfc::neighbors(&offsets, 0, 3, 1, 4, Boundary::Frames(2))
+        .map(|(_, _, y)| y)
+        .collect();
+    assert_eq!(rows, [2, 2, 1, 1]);
+}
+
+#[test]
+fn frames_have_to_be_two_pixels_high() {
+    let frames = [frame(RED), frame(BLUE)];
+    let parameters = AnimatedParameters::from_frames(&frames, 1).unwrap();
+    assert!(parameters.generate_seeded(SIZE, 1, 2, 0).is_err());
+    assert!(parameters.generate_seeded(SIZE, SIZE, 0, 0).is_err());
+}
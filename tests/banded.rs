@@ -0,0 +1,101 @@
+//Checks that an output generated in bands and streamed to a png is a valid
+//image whose rows follow the rules across the seams between bands
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::{ImageData, PngStream};
+
+const INPUT: &str = "inputimage3.png";
+const SIZE: (usize, usize) = (24, 30);
+const BAND_HEIGHT: usize = 8;
+const SEED: u64 = 3;
+const MAX_ATTEMPTS: u32 = 16;
+//Directions of (1, 0) and (0, 1) in the offsets the rules are learned with
+const RIGHT: usize = 1;
+const DOWN: usize = 0;
+
+#[test]
+fn banded_stream_is_a_valid_png_with_consistent_seams() {
+    let wfc_parameters = load_input(INPUT);
+    let (w, h) = SIZE;
+
+    let out_path = std::env::temp_dir().join(format!("wfc_banded_{}.png", std::process::id()));
+    let out_path = out_path.to_string_lossy().to_string();
+    let mut stream = PngStream::create(&out_path, w, h).unwrap();
+    let mut rows: Vec<Vec<usize>> = vec![];
+    wfc_parameters
+        .generate_banded(w, h, BAND_HEIGHT, SEED, MAX_ATTEMPTS, |ids| {
+            let pixels: Vec<u32> = ids.iter().map(|id| wfc_parameters.wfc_tiles[*id]).collect();
+            rows.push(ids.to_vec());
+            stream.write_row(&pixels).map_err(|e| e.to_string())
+        })
+        .unwrap();
+    stream.finish().unwrap();
+
+    let saved = ImageData::load_png(&out_path).unwrap();
+    std::fs::remove_file(&out_path).unwrap();
+    assert_eq!((saved.width(), saved.height()), (w, h));
+    assert_eq!(rows.len(), h);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, id) in row.iter().enumerate() {
+            assert_eq!(saved.get_pixel(x, y), wfc_parameters.wfc_tiles[*id]);
+        }
+    }
+
+    let rules = &wfc_parameters.wfc_rules;
+    for y in 0..h {
+        for x in 0..w {
+            let id = rows[y][x];
+            assert!(
+                rules.okay(RIGHT, id, rows[y][(x + 1) % w]),
+                "({x}, {y}) and the cell to its right"
+            );
+            //The output does not wrap around vertically
+            if y + 1 < h {
+                assert!(
+                    rules.okay(DOWN, id, rows[y + 1][x]),
+                    "({x}, {y}) and the cell below it, bands start every {BAND_HEIGHT} rows"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn bands_of_one_row_follow_the_rules_and_zero_rows_are_rejected() {
+    let wfc_parameters = load_input(INPUT);
+    let (w, h) = (12, 6);
+
+    let result = wfc_parameters.generate_banded(w, h, 0, SEED, MAX_ATTEMPTS, |_| Ok(()));
+    assert_eq!(
+        result,
+        Err("the band height must be at least 1".to_string())
+    );
+
+    let mut rows: Vec<Vec<usize>> = vec![];
+    wfc_parameters
+        .generate_banded(w, h, 1, SEED, MAX_ATTEMPTS, |ids| {
+            rows.push(ids.to_vec());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(rows.len(), h);
+    let rules = &wfc_parameters.wfc_rules;
+    for (y, pair) in rows.windows(2).enumerate() {
+        for (x, (id, below)) in pair[0].iter().zip(&pair[1]).enumerate() {
+            assert!(
+                rules.okay(DOWN, *id, *below),
+                "({x}, {y}) and the cell below it"
+            );
+        }
+    }
+}
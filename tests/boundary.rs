@@ -0,0 +1,78 @@
+//Checks that the input and the output can wrap around their edges
+//independently of each other
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::{Boundary, ExtractionOptions, WFCParameters};
+
+const RED: u32 = 0xffff0000;
+const GREEN: u32 = 0xff00ff00;
+const BLUE: u32 = 0xff0000ff;
+//Direction of (1, 0) in the offsets rules are learned for
+const RIGHT: usize = 1;
+const SEEDS: u64 = 8;
+
+//Columns of red, green and blue, only wrapping around the input puts red to
+//the right of blue
+fn columns() -> ImageData {
+    ImageData::from_pixels(&[RED, GREEN, BLUE].repeat(3), 3, 3)
+}
+
+fn extract(input: Boundary, output: Boundary) -> WFCParameters {
+    let options = ExtractionOptions {
+        boundary: input,
+        ..ExtractionOptions::default()
+    };
+    let mut parameters =
+        WFCParameters::from_image_data_with_options(&columns(), 2, &options).unwrap();
+    parameters.wfc_boundary = output;
+    parameters
+}
+
+fn tile_with_color(parameters: &WFCParameters, color: u32) -> Option<usize> {
+    parameters.wfc_tiles.iter().position(|tile| *tile == color)
+}
+
+#[test]
+fn finite_input_skips_the_wrapped_tiles() {
+    let wrapped = extract(Boundary::Wrap, Boundary::Wrap);
+    assert_eq!(wrapped.wfc_tiles.len(), 3);
+    let finite = extract(Boundary::Finite, Boundary::Wrap);
+    assert_eq!(finite.wfc_tiles, vec![RED, GREEN]);
+}
+
+#[test]
+fn wrapped_input_and_finite_output() {
+    let parameters = extract(Boundary::Wrap, Boundary::Finite);
+    //Red is only allowed to the right of blue because the input wraps around
+    let blue = tile_with_color(&parameters, BLUE).unwrap();
+    let red = tile_with_color(&parameters, RED).unwrap();
+    assert!(parameters.wfc_rules.okay(RIGHT, blue, red));
+    assert!(parameters.validate().is_ok());
+
+    //The colors repeat every 3 columns so a wrapped output 4 wide can't be
+    //generated but a finite one can, and its edges don't line up
+    let wrapped = extract(Boundary::Wrap, Boundary::Wrap);
+    for seed in 0..SEEDS {
+        assert!(wrapped.generate_grid_seeded(4, 3, seed).is_err());
+        let output = parameters.generate_grid_seeded(4, 3, seed).unwrap();
+        assert!(parameters.validate_image(&output).is_empty());
+        assert!(!wrapped.validate_image(&output).is_empty());
+    }
+}
+
+#[test]
+fn finite_output_allows_dead_ends() {
+    //Nothing is to the right of the green tile without wrapping
+    let parameters = extract(Boundary::Finite, Boundary::Wrap);
+    assert!(parameters.validate().is_err());
+    let parameters = extract(Boundary::Finite, Boundary::Finite);
+    assert!(parameters.validate().is_ok());
+}
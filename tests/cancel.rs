@@ -0,0 +1,45 @@
+//Checks that a cancelled generation stops and says it was cancelled
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use std::sync::atomic::AtomicBool;
+use wfc::GenerateError;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const SEED: u64 = 0;
+
+#[test]
+fn a_set_flag_stops_before_the_first_step() {
+    let parameters = load_input(INPUT);
+    let error = parameters
+        .generate_grid_cancellable(SIZE, SIZE, SEED, &AtomicBool::new(true))
+        .err()
+        .unwrap();
+    assert_eq!(error, GenerateError::Cancelled);
+    assert_eq!(String::from(error), "WFC Cancelled");
+}
+
+#[test]
+fn an_unset_flag_gives_the_seeded_output() {
+    let parameters = load_input(INPUT);
+    let cancellable =
+        parameters.generate_grid_cancellable(SIZE, SIZE, SEED, &AtomicBool::new(false));
+    match (
+        cancellable,
+        parameters.generate_grid_seeded(SIZE, SIZE, SEED),
+    ) {
+        (Ok(cancellable), Ok(seeded)) => assert_eq!(cancellable.pixels(), seeded.pixels()),
+        (Err(GenerateError::Failed(a)), Err(b)) => assert_eq!(a, b),
+        _ => panic!("the cancellable and seeded generations did not agree"),
+    }
+}
@@ -0,0 +1,109 @@
+//Randomized check that the cell queue pops cells in the same order as
+//scanning every queued cell for the lowest (entropy, noise) would
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+
+use cell_queue::{quantize_entropy, CellQueue};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const CASES: u64 = 200;
+const OPERATIONS: usize = 500;
+const MAX_CELLS: usize = 64;
+
+//The slow version of the queue, each cell keeps its key after it is removed
+//just like in the real queue
+struct Naive {
+    keys: Vec<(i64, u32)>,
+    queued: Vec<bool>,
+}
+
+impl Naive {
+    fn new<R: Rng>(cells: usize, rng: &mut R) -> Self {
+        Self {
+            keys: (0..cells).map(|_| (0, rng.gen())).collect(),
+            queued: vec![false; cells],
+        }
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        let first = (0..self.keys.len())
+            .filter(|cell| self.queued[*cell])
+            .min_by_key(|cell| (self.keys[*cell], *cell))?;
+        self.queued[first] = false;
+        Some(first)
+    }
+}
+
+#[test]
+fn pops_match_a_full_scan() {
+    for seed in 0..CASES {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let cells = rng.gen_range(1..=MAX_CELLS);
+        let noise_seed = rng.gen();
+        let mut queue = CellQueue::default();
+        queue.reset(cells, &mut StdRng::seed_from_u64(noise_seed));
+        let mut naive = Naive::new(cells, &mut StdRng::seed_from_u64(noise_seed));
+        let mut popped = vec![];
+
+        for _ in 0..OPERATIONS {
+            let cell = rng.gen_range(0..cells);
+            match rng.gen_range(0..4) {
+                0 => {
+                    //Few distinct entropies so that there are a lot of ties
+                    let entropy = rng.gen_range(0..4) as f32 * 0.5;
+                    queue.push(cell, entropy);
+                    naive.keys[cell].0 = quantize_entropy(entropy);
+                    naive.queued[cell] = true;
+                }
+                1 => {
+                    queue.requeue(cell);
+                    naive.queued[cell] = true;
+                }
+                2 => {
+                    queue.remove(cell);
+                    naive.queued[cell] = false;
+                }
+                _ => {
+                    let expected = naive.pop();
+                    popped.push(queue.pop());
+                    assert_eq!(
+                        *popped.last().unwrap(),
+                        expected,
+                        "seed {seed}: pops were {popped:?}"
+                    );
+                }
+            }
+
+            let queued = naive.queued.iter().filter(|queued| **queued).count();
+            assert_eq!(queue.len(), queued, "seed {seed}");
+            assert!(
+                (0..cells).all(|cell| queue.contains(cell) == naive.queued[cell]),
+                "seed {seed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn nan_entropies_are_popped_last() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut queue = CellQueue::default();
+    queue.reset(4, &mut rng);
+    //0 / 0 gives a NaN with the sign bit set on some platforms
+    let negative_nan = -f32::NAN;
+    queue.push(0, f32::NAN);
+    queue.push(1, 3.5);
+    queue.push(2, negative_nan);
+    queue.push(3, 0.0);
+    assert_eq!(quantize_entropy(f32::NAN), i64::MAX);
+    assert_eq!(quantize_entropy(negative_nan), i64::MAX);
+
+    assert_eq!(queue.pop(), Some(3));
+    assert_eq!(queue.pop(), Some(1));
+    let mut last = [queue.pop().unwrap(), queue.pop().unwrap()];
+    last.sort();
+    assert_eq!(last, [0, 2]);
+    assert_eq!(queue.pop(), None);
+}
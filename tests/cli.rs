@@ -0,0 +1,134 @@
+//Checks that the command line rejects flags that need another flag or can't
+//be used together, and that the error names both of them
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/cli.rs"]
+mod cli;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use cli::{Command, GenerateArgs};
+
+fn parse(args: &[&str]) -> Result<GenerateArgs, String> {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    args.insert(0, "wave-function-collapse".to_string());
+    match cli::parse(args)?.command {
+        Command::Generate(args) => Ok(*args),
+        _ => panic!("not the generate subcommand"),
+    }
+}
+
+//Flags that only work with the flag after them
+const REQUIRES: [(&[&str], &str); 11] = [
+    (&["--headless"], "--out"),
+    (&["--out", "o.png", "--frames-dir", "d"], "--headless"),
+    (&["--out", "o.png", "--entropy-map", "e.png"], "--headless"),
+    (&["--out", "o.png", "--entropy-csv", "e.csv"], "--headless"),
+    (
+        &["--out", "o.png", "--tiled-preview", "t.png"],
+        "--headless",
+    ),
+    (&["--out", "o.png", "--quiet"], "--headless"),
+    (&["--out", "o.png", "--timings"], "--headless"),
+    (&["--out", "o.png", "--band-height", "8"], "--headless"),
+    (
+        &["--headless", "--out", "o.png", "--entropy-at", "0.2"],
+        "--entropy-map",
+    ),
+    (&["--animate"], "--out"),
+    (&["--rotation-weight", "50"], "--rotations"),
+];
+
+//Flags that can't be used with each other, along with the flags they
+//require
+const CONFLICTS: [(&[&str], &str, &str); 8] = [
+    (
+        &[
+            "--headless",
+            "--out",
+            "o.png",
+            "--timings",
+            "--band-height",
+            "8",
+        ],
+        "--timings",
+        "--band-height",
+    ),
+    (
+        &[
+            "--headless",
+            "--out",
+            "o.png",
+            "--band-height",
+            "8",
+            "--mask",
+            "m.png",
+        ],
+        "--band-height",
+        "--mask",
+    ),
+    (
+        &[
+            "--headless",
+            "--out",
+            "o.png",
+            "--band-height",
+            "8",
+            "--frames-dir",
+            "d",
+        ],
+        "--band-height",
+        "--frames-dir",
+    ),
+    (
+        &["--stress", "4", "--headless", "--out", "o.png"],
+        "--stress",
+        "--headless",
+    ),
+    (
+        &["--stress", "4", "--animate", "--out", "o.png"],
+        "--stress",
+        "--animate",
+    ),
+    (
+        &["--animate", "--out", "o.png", "--guide", "g.png"],
+        "--animate",
+        "--guide",
+    ),
+    (&["--hex", "--rotations"], "--hex", "--rotations"),
+    (
+        &["--frequency-add", "4", "--frequency-alpha", "0.5"],
+        "--frequency-add",
+        "--frequency-alpha",
+    ),
+];
+
+#[test]
+fn missing_required_flags_are_named() {
+    for (flags, required) in REQUIRES {
+        let mut args = vec!["in.png"];
+        args.extend(flags);
+        let error = parse(&args)
+            .err()
+            .unwrap_or_else(|| panic!("{args:?} was accepted"));
+        assert!(error.contains(required), "{args:?}: {error}");
+    }
+}
+
+#[test]
+fn conflicting_flags_are_named() {
+    for (flags, a, b) in CONFLICTS {
+        let mut args = vec!["in.png"];
+        args.extend(flags);
+        let error = parse(&args)
+            .err()
+            .unwrap_or_else(|| panic!("{args:?} was accepted"));
+        assert!(error.contains(a) && error.contains(b), "{args:?}: {error}");
+    }
+}
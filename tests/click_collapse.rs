@@ -0,0 +1,129 @@
+//Checks collapsing a cell of the output that was clicked before the solver
+//carries on, and cycling through its tiles with further clicks
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/layout.rs"]
+mod layout;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use layout::Layout;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::WFCState;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const LAYOUT: Layout = Layout {
+    pixel_size: 4.0,
+    input_pos: (4.0, 4.0),
+    output_pos: (10.0, 20.0),
+};
+
+#[test]
+fn clicks_are_mapped_to_output_cells() {
+    let output = (SIZE, SIZE);
+    assert_eq!(LAYOUT.output_cell((10.0, 20.0), output), Some((0, 0)));
+    assert_eq!(LAYOUT.output_cell((13.9, 23.9), output), Some((0, 0)));
+    assert_eq!(LAYOUT.output_cell((14.0, 27.0), output), Some((1, 1)));
+    assert_eq!(LAYOUT.output_cell((57.9, 67.9), output), Some((11, 11)));
+    //Outside of the output
+    assert_eq!(LAYOUT.output_cell((9.9, 30.0), output), None);
+    assert_eq!(LAYOUT.output_cell((30.0, 19.9), output), None);
+    assert_eq!(LAYOUT.output_cell((58.0, 30.0), output), None);
+    assert_eq!(LAYOUT.output_cell((30.0, 68.0), output), None);
+}
+
+#[test]
+fn a_clicked_cell_keeps_its_tile_while_the_solver_carries_on() {
+    let mut parameters = load_input(INPUT);
+    let mut generated = 0;
+    for seed in 0..8 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut wfc_state = WFCState::new(
+            SIZE,
+            SIZE,
+            &parameters.wfc_tiles,
+            &parameters.wfc_frequency,
+            &mut rng,
+        );
+        parameters
+            .step(SIZE, SIZE, &mut wfc_state, &mut rng)
+            .unwrap();
+        let index = (0..SIZE * SIZE)
+            .find(|i| wfc_state.superpositions()[*i].len() > 2)
+            .expect("a cell with a few options left");
+        let before = wfc_state.clone();
+        let options: Vec<usize> = before.superpositions()[index].iter().collect();
+
+        //Each click on the same cell starts from the state before the first
+        //click and picks the next of its tiles
+        for (choice, tile) in options.iter().enumerate().take(3) {
+            wfc_state = before.clone();
+            parameters
+                .place_tile(&mut wfc_state, index, *tile, SIZE, SIZE, false)
+                .unwrap_or_else(|e| panic!("seed {seed} choice {choice}: {e}"));
+            assert_eq!(
+                wfc_state.superpositions()[index].iter().collect::<Vec<_>>(),
+                [*tile]
+            );
+            assert!(wfc_state.remaining() < before.remaining());
+        }
+
+        let tile = options[2];
+        let mut failed = false;
+        while !wfc_state.done() {
+            if parameters
+                .step(SIZE, SIZE, &mut wfc_state, &mut rng)
+                .is_err()
+            {
+                failed = true;
+                break;
+            }
+        }
+        if failed {
+            continue;
+        }
+        generated += 1;
+        assert_eq!(wfc_state.superpositions()[index].first(), Some(tile));
+        assert_eq!(wfc_state.remaining(), 0);
+    }
+    assert!(generated > 0);
+}
+
+#[test]
+fn clicking_a_collapsed_cell_keeps_its_tile() {
+    let parameters = load_input(INPUT);
+    let mut masked = vec![false; SIZE * SIZE];
+    masked[0] = true;
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut wfc_state = WFCState::new_masked(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &masked,
+        &mut rng,
+    );
+    parameters
+        .step(SIZE, SIZE, &mut wfc_state, &mut rng)
+        .unwrap();
+    assert!(wfc_state.superpositions()[0].is_empty());
+    let collapsed = (1..SIZE * SIZE)
+        .find(|i| wfc_state.superpositions()[*i].len() == 1)
+        .unwrap();
+    //Placing the tile a collapsed cell already has changes nothing
+    let tile = wfc_state.superpositions()[collapsed].first().unwrap();
+    let before = wfc_state.clone();
+    let mut parameters = parameters;
+    parameters
+        .place_tile(&mut wfc_state, collapsed, tile, SIZE, SIZE, false)
+        .unwrap();
+    assert_eq!(wfc_state.superpositions(), before.superpositions());
+}
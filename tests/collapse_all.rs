@@ -0,0 +1,39 @@
+//Checks that a seeded generation without a window collapses every cell
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 10;
+const SEED: u64 = 3;
+
+#[test]
+fn every_cell_is_collapsed() {
+    let parameters = load_input(INPUT);
+    let (wfc_state, result) =
+        parameters.generate_state_masked_seeded(SIZE, SIZE, &[false; SIZE * SIZE], SEED, None);
+    result.unwrap();
+    assert!(wfc_state.done());
+    assert_eq!(wfc_state.remaining(), 0);
+    for (i, superposition) in wfc_state.superpositions().iter().enumerate() {
+        assert_eq!(superposition.len(), 1, "cell {i}");
+    }
+
+    //The ids are the tiles the cells collapsed into
+    let ids = parameters.generate_ids_seeded(SIZE, SIZE, SEED).unwrap();
+    let collapsed: Vec<usize> = wfc_state
+        .superpositions()
+        .iter()
+        .map(|superposition| superposition.first().unwrap())
+        .collect();
+    assert_eq!(ids, collapsed);
+}
@@ -0,0 +1,168 @@
+//Checks the options that decide which cell is collapsed next
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{StartPosition, WFCParameters, WFCState, HEX_OFFSETS};
+
+const TILES: usize = 4;
+const SIZE: (usize, usize) = (7, 5);
+const SEEDS: u64 = 8;
+//The diagonals of a square output that wraps around are as long as a side
+const HEX_SIZE: (usize, usize) = (6, 6);
+
+//Tiles 0 and 1 and tiles 2 and 3 form two groups, horizontally a tile can
+//only be next to the tiles of its group. Collapsing a cell narrows its whole
+//row down to a group without collapsing it, so the lowest entropy cells are
+//spread along the row
+fn grouped() -> WFCParameters {
+    let mut rules = all_allowed(TILES, &OFFSETS);
+    for (direction, (dx, _)) in OFFSETS.iter().enumerate() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if *dx != 0 && id1 / 2 != id2 / 2 {
+                    rules.remove_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+//Index of each cell in the order the cells were collapsed
+fn collapse_order(parameters: &WFCParameters, seed: u64) -> Vec<usize> {
+    collapse_order_sized(parameters, seed, SIZE)
+}
+
+fn collapse_order_sized(parameters: &WFCParameters, seed: u64, size: (usize, usize)) -> Vec<usize> {
+    let (w, h) = size;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    wfc_state.set_start(
+        parameters.wfc_start.index(w, h),
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    let mut order = vec![];
+    while !wfc_state.done() {
+        parameters.step(w, h, &mut wfc_state, &mut rng).unwrap();
+        let collapsed: Vec<usize> = (0..w * h)
+            .filter(|i| wfc_state.superpositions()[*i].len() == 1 && !order.contains(i))
+            .collect();
+        order.extend(collapsed);
+    }
+    order
+}
+
+fn next_to(a: usize, b: usize) -> bool {
+    let (w, h) = SIZE;
+    let dx = (a % w).abs_diff(b % w);
+    let dy = (a / w).abs_diff(b / w);
+    let (dx, dy) = (dx.min(w - dx), dy.min(h - dy));
+    dx + dy == 1
+}
+
+#[test]
+fn growing_from_collapsed_keeps_one_region() {
+    let parameters = WFCParameters {
+        wfc_grow_from_collapsed: true,
+        ..grouped()
+    };
+    for seed in 0..SEEDS {
+        let order = collapse_order(&parameters, seed);
+        assert_eq!(order.len(), SIZE.0 * SIZE.1);
+        for (i, cell) in order.iter().enumerate().skip(1) {
+            assert!(
+                order[..i].iter().any(|earlier| next_to(*earlier, *cell)),
+                "seed {seed}: cell {cell} is not next to the cells collapsed before it"
+            );
+        }
+    }
+
+    //Without it the collapsed cells end up scattered
+    let scattered = (0..SEEDS).any(|seed| {
+        let order = collapse_order(&grouped(), seed);
+        (1..order.len()).any(|i| !order[..i].iter().any(|earlier| next_to(*earlier, order[i])))
+    });
+    assert!(scattered);
+}
+
+#[test]
+fn the_first_collapse_is_at_the_start_position() {
+    let (w, h) = SIZE;
+    let starts = [
+        (StartPosition::Center, w / 2 + (h / 2) * w),
+        (StartPosition::At(1, 3), 1 + 3 * w),
+        //Positions outside of the output wrap around
+        (StartPosition::At(w + 2, h), 2),
+    ];
+    for (start, index) in starts {
+        let parameters = WFCParameters {
+            wfc_start: start,
+            ..grouped()
+        };
+        for seed in 0..SEEDS {
+            assert_eq!(
+                collapse_order(&parameters, seed)[0],
+                index,
+                "{start:?} seed {seed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn growing_from_collapsed_follows_the_offsets_of_the_rules() {
+    //Hexagons where a tile can only be next to the tiles of its group along
+    //(1, -1), which is not a neighbor of a square grid. Collapsing a cell
+    //narrows that whole diagonal down to a group, so the next cell is the
+    //lowest entropy neighbor along the diagonal
+    let mut rules = all_allowed(TILES, &HEX_OFFSETS);
+    for (direction, offset) in HEX_OFFSETS.iter().enumerate() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if offset.0 == -offset.1 && id1 / 2 != id2 / 2 {
+                    rules.remove_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    let parameters = WFCParameters {
+        wfc_grow_from_collapsed: true,
+        ..WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+    };
+
+    let (w, h) = HEX_SIZE;
+    for seed in 0..SEEDS {
+        let order = collapse_order_sized(&parameters, seed, HEX_SIZE);
+        let (first, second) = (order[0], order[1]);
+        let diagonal_neighbor = HEX_OFFSETS
+            .iter()
+            .filter(|(dx, dy)| *dx == -dy)
+            .any(|(dx, dy)| {
+                let x = (first % w) as isize + dx;
+                let y = (first / w) as isize + dy;
+                second == x.rem_euclid(w as isize) as usize + y.rem_euclid(h as isize) as usize * w
+            });
+        assert!(
+            diagonal_neighbor,
+            "seed {seed}: cell {second} is not next to the first cell {first} along the diagonal"
+        );
+    }
+}
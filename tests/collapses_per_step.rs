@@ -0,0 +1,97 @@
+//Checks collapsing several separated tiles in each step
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{WFCParameters, WFCState};
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 24;
+const K: usize = 4;
+const SEEDS: u64 = 16;
+
+//Runs the steps one by one, fails on the first contradiction, returns the
+//number of steps
+fn run(parameters: &WFCParameters, seed: u64) -> Result<(Vec<usize>, usize), String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    let mut steps = 0;
+    while !wfc_state.done() {
+        parameters.step(SIZE, SIZE, &mut wfc_state, &mut rng)?;
+        steps += 1;
+    }
+    assert_eq!(wfc_state.remaining(), 0);
+    let ids = wfc_state
+        .superpositions()
+        .iter()
+        .map(|superposition| superposition.first().unwrap())
+        .collect();
+    Ok((ids, steps))
+}
+
+#[test]
+fn k_collapses_per_step_fully_collapse() {
+    let single = WFCParameters {
+        wfc_check_invariants: true,
+        ..load_input(INPUT)
+    };
+    let several = WFCParameters {
+        wfc_collapses_per_step: K,
+        wfc_collapse_separation: 4,
+        ..single.clone()
+    };
+    let mut finished = 0;
+    for seed in 0..SEEDS {
+        //A fixed seed always gives the same output
+        let Ok((ids, steps)) = run(&several, seed) else {
+            continue;
+        };
+        assert_eq!(run(&several, seed).unwrap(), (ids.clone(), steps));
+        finished += 1;
+
+        let pixels: Vec<u32> = ids.iter().map(|id| several.wfc_tiles[*id]).collect();
+        let image = ImageData::from_pixels(&pixels, SIZE, SIZE);
+        assert!(several.validate_image(&image).is_empty(), "seed {seed}");
+        if let Ok((_, single_steps)) = run(&single, seed) {
+            assert!(
+                steps < single_steps,
+                "seed {seed}: {steps} >= {single_steps}"
+            );
+        }
+    }
+    assert!(finished > SEEDS / 2, "{finished} of {SEEDS} seeds finished");
+}
+
+#[test]
+fn tiles_too_far_apart_are_collapsed_one_at_a_time() {
+    let single = load_input(INPUT);
+    //No two cells of the grid are this far apart
+    let separated = WFCParameters {
+        wfc_collapses_per_step: K,
+        wfc_collapse_separation: SIZE,
+        ..single.clone()
+    };
+    let mut finished = 0;
+    for seed in 0..4 {
+        let expected = run(&single, seed).ok();
+        assert_eq!(run(&separated, seed).ok(), expected, "seed {seed}");
+        finished += expected.is_some() as usize;
+    }
+    assert!(finished > 0);
+}
@@ -0,0 +1,51 @@
+//Checks that a color tolerance merges tiles that only differ by noise
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::{ExtractionOptions, WFCParameters};
+
+const SIZE: usize = 8;
+const TILE_SZ: isize = 3;
+const TOLERANCE: u32 = 3;
+
+//Vertical stripes two pixels wide, every channel of some pixels is one
+//higher than the stripe color like the noise in a photo
+fn dithered() -> ImageData {
+    let mut pixels = vec![];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let channel = if x / 2 % 2 == 0 { 0x40 } else { 0xc0 };
+            let noise = ((x * 7 + y * 3) % 5 == 0) as u32;
+            let channel = channel + noise;
+            pixels.push(0xff000000 | channel << 16 | channel << 8 | channel);
+        }
+    }
+    ImageData::from_pixels(&pixels, SIZE, SIZE)
+}
+
+fn tile_count(tolerance: u32) -> usize {
+    let options = ExtractionOptions {
+        color_tolerance: tolerance,
+        ..Default::default()
+    };
+    WFCParameters::from_image_data_with_options(&dithered(), TILE_SZ, &options)
+        .unwrap()
+        .wfc_tiles
+        .len()
+}
+
+#[test]
+fn noisy_tiles_are_merged() {
+    let exact = tile_count(0);
+    let merged = tile_count(TOLERANCE);
+    //Without the noise the stripes only have four different tiles
+    assert_eq!(merged, 4);
+    assert!(exact > 4 * merged, "{exact} tiles without a tolerance");
+}
@@ -0,0 +1,45 @@
+//Helpers shared by the tests, a test that uses them has to include the
+//image_data and wfc modules as well
+#![allow(dead_code)]
+
+use crate::{
+    image_data::ImageData,
+    wfc::{RuleTable, WFCParameters, SQUARE_OFFSETS},
+};
+
+//The four directions of a square grid
+pub const OFFSETS: [(isize, isize); 4] = SQUARE_OFFSETS;
+
+//The path of an image in the images directory of the repository
+pub fn image_path(input: &str) -> String {
+    format!("{}/images/{input}", env!("CARGO_MANIFEST_DIR"))
+}
+
+//Loads an image from the images directory of the repository
+pub fn load_image(input: &str) -> ImageData {
+    let path = image_path(input);
+    ImageData::load_png(&path).unwrap_or_else(|e| panic!("{path}: {e}"))
+}
+
+//Samples the 3x3 tiles of an image from the images directory
+pub fn load_input(input: &str) -> WFCParameters {
+    load_input_sized(input, 3)
+}
+
+pub fn load_input_sized(input: &str, tile_sz: isize) -> WFCParameters {
+    WFCParameters::from_image_data(&load_image(input), tile_sz)
+        .unwrap_or_else(|e| panic!("{input}: {e}"))
+}
+
+//Rules that allow every tile next to every tile in every direction
+pub fn all_allowed(tiles: usize, offsets: &[(isize, isize)]) -> RuleTable {
+    let mut rules = RuleTable::new(tiles, offsets).unwrap();
+    for direction in 0..offsets.len() {
+        for id1 in 0..tiles {
+            for id2 in 0..tiles {
+                rules.add_rule(direction, id1, id2);
+            }
+        }
+    }
+    rules
+}
@@ -0,0 +1,139 @@
+//Checks reading the options from a --config file and that the command line
+//takes precedence over it
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/cli.rs"]
+mod cli;
+#[path = "../src/config.rs"]
+mod config;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use cli::{Command, GenerateArgs};
+use config::Config;
+
+fn write_config(name: &str, json: &str) -> String {
+    let path = std::env::temp_dir().join(format!("config_{}_{name}.json", std::process::id()));
+    std::fs::write(&path, json).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn parse(args: &[&str]) -> Result<GenerateArgs, String> {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    args.insert(0, "wave-function-collapse".to_string());
+    match cli::parse(args)?.command {
+        Command::Generate(args) => Ok(*args),
+        _ => panic!("not the generate subcommand"),
+    }
+}
+
+#[test]
+fn configs_round_trip() {
+    let config = Config {
+        input: Some("images/brick.png".to_string()),
+        tile_size: Some(2),
+        rotations: Some(true),
+        frequency_alpha: Some(0.5),
+        seed: Some(7),
+        width: Some(32),
+        frames_dir: Some("frames".to_string()),
+        ..Config::default()
+    };
+    let json = serde_json::to_string(&config).unwrap();
+    assert!(json.contains("\"tile-size\":2"), "{json}");
+    let path = write_config("round_trip", &json);
+    assert_eq!(Config::load(&path).unwrap(), config);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn unknown_fields_are_reported() {
+    let path = write_config("unknown", r#"{ "input": "a.png", "tile-sz": 3 }"#);
+    let error = Config::load(&path).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains("tile-sz"), "{error}");
+}
+
+#[test]
+fn the_config_fills_in_the_arguments() {
+    let path = write_config(
+        "fills",
+        r#"{ "input": "in.png", "tile-size": 2, "width": 20, "headless": true, "out": "out.png", "seed": 5 }"#,
+    );
+    let args = parse(&["--config", &path]).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(args.input.input, "in.png");
+    assert_eq!(args.input.tile_size, 2);
+    assert_eq!((args.width, args.height), (20, 64));
+    assert!(args.headless);
+    assert_eq!(args.out.as_deref(), Some("out.png"));
+    assert_eq!(args.seed, Some(5));
+    assert!(!args.quiet);
+}
+
+#[test]
+fn the_command_line_takes_precedence() {
+    let path = write_config(
+        "precedence",
+        r#"{ "input": "in.png", "tile-size": 2, "width": 20, "headless": true, "out": "out.png" }"#,
+    );
+    let args = parse(&[
+        "--config",
+        &path,
+        "--frames-dir",
+        "d",
+        "--quiet",
+        "--width=8",
+    ])
+    .unwrap();
+    assert_eq!(args.frames_dir.as_deref(), Some("d"));
+    assert!(args.quiet);
+    assert_eq!(args.width, 8);
+    assert_eq!(args.out.as_deref(), Some("out.png"));
+
+    //Positional arguments given on the command line replace the config's,
+    //the ones after them still come from the config
+    let args = parse(&["other.png", "--config", &path]).unwrap();
+    assert_eq!(args.input.input, "other.png");
+    assert_eq!(args.input.tile_size, 2);
+    let args = parse(&["generate", "--config", &path, "other.png", "3"]).unwrap();
+    assert_eq!(
+        (args.input.input.as_str(), args.input.tile_size),
+        ("other.png", 3)
+    );
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn config_values_are_checked_like_the_command_line() {
+    //--frames-dir requires --headless whether it comes from the config or not
+    let path = write_config("requires", r#"{ "input": "in.png", "frames-dir": "d" }"#);
+    let error = parse(&["--config", &path]).err().unwrap();
+    assert!(error.contains("--headless"), "{error}");
+    assert!(parse(&["--config", &path, "--headless", "--out", "o.png"]).is_ok());
+    std::fs::remove_file(&path).unwrap();
+
+    let path = write_config("range", r#"{ "input": "in.png", "tile-size": 9 }"#);
+    let error = parse(&["--config", &path]).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains('9'), "{error}");
+}
+
+#[test]
+fn frequency_add_is_bounded() {
+    assert_eq!(
+        parse(&["in.png", "--frequency-add", "1000000"])
+            .unwrap()
+            .input
+            .frequency_add,
+        Some(1_000_000)
+    );
+    let error = parse(&["in.png", "--frequency-add", "1000001"])
+        .err()
+        .unwrap();
+    assert!(error.contains("--frequency-add"), "{error}");
+}
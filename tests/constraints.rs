@@ -0,0 +1,94 @@
+//Checks generating with caller supplied tiles for each cell
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use wfc::{RuleTable, WFCParameters};
+
+const TILES: usize = 4;
+const SIZE: usize = 8;
+const SEEDS: u64 = 8;
+
+//Any tile can be next to any other except tile 3 next to itself
+fn build_parameters() -> WFCParameters {
+    let mut rules = RuleTable::new(TILES, &OFFSETS).unwrap();
+    for direction in 0..OFFSETS.len() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if (id1, id2) != (3, 3) {
+                    rules.add_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+//Black squares of the checkerboard get tiles 0 and 1, the white squares
+//are left unconstrained
+fn checkerboard(x: usize, y: usize) -> Vec<usize> {
+    if (x + y).is_multiple_of(2) {
+        vec![0, 1]
+    } else {
+        (0..TILES).collect()
+    }
+}
+
+#[test]
+fn generation_respects_a_checkerboard_of_constraints() {
+    let parameters = build_parameters();
+    for seed in 0..SEEDS {
+        let ids = parameters
+            .generate_ids_constrained(SIZE, SIZE, seed, checkerboard)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+        for (i, id) in ids.iter().enumerate() {
+            let (x, y) = (i % SIZE, i / SIZE);
+            assert!(
+                checkerboard(x, y).contains(id),
+                "seed {seed}: cell ({x}, {y}) is tile {id}"
+            );
+        }
+        //The unconstrained cells still get every tile somewhere
+        assert!(ids.contains(&3), "seed {seed}");
+    }
+}
+
+#[test]
+fn invalid_constraints_are_rejected() {
+    let parameters = build_parameters();
+    let error = parameters
+        .generate_ids_constrained(SIZE, SIZE, 0, |x, y| {
+            if (x, y) == (2, 1) {
+                vec![]
+            } else {
+                vec![0]
+            }
+        })
+        .unwrap_err();
+    assert!(error.contains("(2, 1)"), "{error}");
+
+    let error = parameters
+        .generate_ids_constrained(SIZE, SIZE, 0, |_, _| vec![0, TILES])
+        .unwrap_err();
+    assert!(error.contains("tile 4"), "{error}");
+
+    //Two neighbors that can only be tile 3
+    let error = parameters
+        .generate_ids_constrained(SIZE, SIZE, 0, |x, y| {
+            if y == 0 && x < 2 {
+                vec![3]
+            } else {
+                (0..TILES).collect()
+            }
+        })
+        .unwrap_err();
+    assert!(error.contains("can't be met"), "{error}");
+}
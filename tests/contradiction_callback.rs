@@ -0,0 +1,84 @@
+//Checks that the caller decides what is done about contradictions
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::{ContradictionAction, RetryStrategy};
+
+//Some seeds run into contradictions with this input at this size
+const INPUT: &str = "inputimage3.png";
+const SIZE: (usize, usize) = (16, 16);
+const SEEDS: u64 = 16;
+
+//A seed that runs into at least one contradiction
+fn contradicting_seed() -> u64 {
+    let (w, h) = SIZE;
+    (0..SEEDS)
+        .find(|seed| {
+            let mut called = false;
+            let _ = load_input(INPUT).generate_grid_handled(w, h, *seed, 0, &mut |_| {
+                called = true;
+                ContradictionAction::Abort
+            });
+            called
+        })
+        .expect("no seed ran into a contradiction")
+}
+
+#[test]
+fn aborting_returns_an_error() {
+    let (w, h) = SIZE;
+    let seed = contradicting_seed();
+    let mut contradictions = vec![];
+    let result = load_input(INPUT).generate_grid_handled(w, h, seed, 0, &mut |contradiction| {
+        contradictions.push(*contradiction);
+        ContradictionAction::Abort
+    });
+    assert!(result.is_err());
+    //Nothing is generated after aborting
+    assert_eq!(contradictions.len(), 1);
+    let contradiction = contradictions[0];
+    assert_eq!(contradiction.count, 1);
+    assert!(contradiction.x < w && contradiction.y < h);
+    assert!(contradiction.remaining < w * h);
+}
+
+#[test]
+fn retrying_keeps_generating() {
+    let (w, h) = SIZE;
+    let seed = contradicting_seed();
+    let mut count = 0;
+    let (image, allowed) = load_input(INPUT)
+        .generate_grid_handled(w, h, seed, 8, &mut |contradiction| {
+            count += 1;
+            assert_eq!(contradiction.count, count);
+            ContradictionAction::Retry(RetryStrategy::Backtrack)
+        })
+        .unwrap();
+    assert!(count > 0);
+    assert_eq!(allowed, 0);
+    assert_eq!((image.width(), image.height()), SIZE);
+}
+
+#[test]
+fn relaxing_only_changes_the_rules_of_one_generation() {
+    let (w, h) = SIZE;
+    let seed = contradicting_seed();
+    let parameters = load_input(INPUT);
+    let original = parameters.wfc_rules.clone();
+    let (_, allowed) = parameters
+        .generate_grid_handled(w, h, seed, 0, &mut |_| {
+            ContradictionAction::Retry(RetryStrategy::Relax)
+        })
+        .unwrap();
+    assert!(allowed > 0);
+    assert!(parameters.wfc_rules == original);
+}
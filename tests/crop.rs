@@ -0,0 +1,33 @@
+//Checks cropping the input before the tiles are sampled from it
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::ImageData;
+
+//Each pixel is its own index so it is easy to tell where it came from
+fn numbered(w: usize, h: usize) -> ImageData {
+    let pixels: Vec<u32> = (0..(w * h) as u32).collect();
+    ImageData::from_pixels(&pixels, w, h)
+}
+
+#[test]
+fn crop_copies_the_region() {
+    let cropped = numbered(5, 4).crop(1, 2, 3, 2).unwrap();
+    assert_eq!((cropped.width(), cropped.height()), (3, 2));
+    assert_eq!(cropped.pixels(), &[11, 12, 13, 16, 17, 18]);
+}
+
+#[test]
+fn crop_is_clamped_to_the_image() {
+    let cropped = numbered(5, 4).crop(3, 1, 10, 10).unwrap();
+    assert_eq!((cropped.width(), cropped.height()), (2, 3));
+    assert_eq!(cropped.pixels(), &[8, 9, 13, 14, 18, 19]);
+}
+
+#[test]
+fn crop_outside_of_the_image_fails() {
+    let error = numbered(5, 4).crop(5, 0, 1, 1).err().unwrap();
+    assert!(error.contains("(5, 0)") && error.contains("5x4"), "{error}");
+    assert!(numbered(5, 4).crop(0, 0, 0, 1).is_err());
+}
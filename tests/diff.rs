@@ -0,0 +1,44 @@
+//Checks comparing two images pixel by pixel
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::ImageData;
+
+fn gradient(w: usize, h: usize) -> ImageData {
+    let pixels: Vec<u32> = (0..(w * h) as u32).map(|i| 0xff000000 | i).collect();
+    ImageData::from_pixels(&pixels, w, h)
+}
+
+#[test]
+fn identical_images_have_no_differences() {
+    let image = gradient(4, 3);
+    assert_eq!(image.diff(&image.clone()), Ok(vec![]));
+}
+
+#[test]
+fn one_changed_pixel_is_found() {
+    let image = gradient(4, 3);
+    let mut changed = image.clone();
+    changed.pixels_mut()[2 + 4] = 0xffffffff;
+    let different = image.diff(&changed).unwrap();
+    assert_eq!(different, vec![(2, 1)]);
+
+    //Only the changed pixel has the highlight color
+    let highlighted = image.highlight_diff(&different);
+    let highlight = highlighted.get_pixel(2, 1);
+    assert_eq!(
+        highlighted
+            .pixels()
+            .iter()
+            .filter(|pixel| **pixel == highlight)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn images_of_different_sizes_are_not_compared() {
+    let error = gradient(4, 3).diff(&gradient(3, 4)).unwrap_err();
+    assert!(error.contains("4x3") && error.contains("3x4"), "{error}");
+}
@@ -0,0 +1,57 @@
+//Checks that the difficulty estimate ranks rule tables sensibly
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use wfc::{RuleTable, WFCParameters};
+
+const TILES: usize = 8;
+
+//Each tile is allowed next to the tiles within distance of it (wrapping
+//around), so the rules always agree in both directions
+fn build_parameters(distance: usize) -> WFCParameters {
+    let mut rules = RuleTable::new(TILES, &OFFSETS).unwrap();
+    for direction in 0..OFFSETS.len() {
+        for id1 in 0..TILES {
+            for step in 0..=distance {
+                rules.add_rule(direction, id1, (id1 + step) % TILES);
+                rules.add_rule(direction, id1, (id1 + TILES - step) % TILES);
+            }
+        }
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+#[test]
+fn dense_rules_score_easier_than_sparse_rules() {
+    let sparse = build_parameters(0).difficulty_estimate();
+    let medium = build_parameters(1).difficulty_estimate();
+    let dense = build_parameters(TILES / 2).difficulty_estimate();
+
+    assert_eq!(sparse.average_neighbors, 1.0);
+    assert_eq!(dense.rule_density, 1.0);
+    assert!(sparse.score > medium.score, "{sparse:?} {medium:?}");
+    assert!(medium.score > dense.score, "{medium:?} {dense:?}");
+    //Any tile can be next to any other so nothing can go wrong
+    assert_eq!(dense.score, 0.0);
+    assert!((0.0..=1.0).contains(&sparse.score));
+}
+
+#[test]
+fn even_frequencies_score_harder_than_skewed_ones() {
+    let even = build_parameters(1);
+    let mut skewed = build_parameters(1);
+    skewed.wfc_frequency[0] = 1000;
+    let (even, skewed) = (even.difficulty_estimate(), skewed.difficulty_estimate());
+    assert!((even.frequency_evenness - 1.0).abs() < 1e-5);
+    assert!(skewed.frequency_evenness < even.frequency_evenness);
+    assert!(even.score > skewed.score);
+}
@@ -0,0 +1,93 @@
+//Checks a rule table with the diagonals as well as the four sides
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use wfc::{RuleTable, WFCParameters};
+
+const OFFSETS: [(isize, isize); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+//More than 64 tiles so the bitsets take more than one word
+const TILES: usize = 70;
+
+//Each direction allows a different pair (and its opposite), so an indexing
+//mistake shows up as a rule in the wrong direction
+fn pair(direction: usize) -> (usize, usize) {
+    (direction * 9 % TILES, (direction * 13 + 64) % TILES)
+}
+
+fn eight_directions() -> RuleTable {
+    let mut rules = RuleTable::new(TILES, &OFFSETS).unwrap();
+    for direction in 0..OFFSETS.len() {
+        let (id1, id2) = pair(direction);
+        rules.add_rule(direction, id1, id2);
+        rules.add_rule(rules.opposite(direction), id2, id1);
+    }
+    rules
+}
+
+fn expected(direction: usize, id1: usize, id2: usize) -> bool {
+    let opposite = (direction + OFFSETS.len() / 2) % OFFSETS.len();
+    pair(direction) == (id1, id2) || pair(opposite) == (id2, id1)
+}
+
+fn assert_rules(rules: &RuleTable) {
+    assert_eq!(rules.directions(), OFFSETS.len());
+    assert_eq!(rules.offsets(), &OFFSETS);
+    for (direction, offset) in OFFSETS.iter().enumerate() {
+        assert_eq!(rules.opposite(direction), (direction + 4) % 8);
+        assert_eq!(rules.direction(*offset), Some(direction));
+        for id1 in 0..TILES {
+            let allowed = rules.allowed_neighbors(direction, id1);
+            for id2 in 0..TILES {
+                let okay = expected(direction, id1, id2);
+                assert_eq!(
+                    rules.okay(direction, id1, id2),
+                    okay,
+                    "{direction} {id1} {id2}"
+                );
+                assert_eq!(allowed[id2 / 64] & (1 << (id2 % 64)) != 0, okay);
+            }
+        }
+    }
+    assert!(rules.check_symmetry().is_empty());
+}
+
+#[test]
+fn eight_directions_are_indexed_separately() {
+    assert_rules(&eight_directions());
+}
+
+#[test]
+fn eight_directions_round_trip() {
+    let parameters = WFCParameters::from_parts(
+        (0..TILES as u32).collect(),
+        eight_directions(),
+        vec![1; TILES],
+        1,
+    );
+    let loaded = WFCParameters::from_rules_bin(&parameters.to_rules_bin()).unwrap();
+    assert_rules(&loaded.wfc_rules);
+
+    let path = std::env::temp_dir().join(format!("eight_directions_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    parameters.save_rules_bin(path).unwrap();
+    let loaded = WFCParameters::load_rules_bin(path);
+    std::fs::remove_file(path).unwrap();
+    let loaded = loaded.unwrap();
+    assert_rules(&loaded.wfc_rules);
+    assert!(loaded.wfc_rules == parameters.wfc_rules);
+}
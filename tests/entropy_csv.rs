@@ -0,0 +1,76 @@
+//Checks the entropy of each cell that is saved with --entropy-csv
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const SIZE: (usize, usize) = (12, 8);
+const SEED: u64 = 7;
+
+//The rows after the header split into their values
+fn rows(csv: &str) -> Vec<Vec<String>> {
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("x,y,remaining,entropy"));
+    lines
+        .map(|line| line.split(',').map(|value| value.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn collapsed_cells_have_no_entropy() {
+    let (w, h) = SIZE;
+    let wfc_parameters = load_input("inputimage3.png");
+    let masked = vec![false; w * h];
+    let (wfc_state, result) =
+        wfc_parameters.generate_state_masked_seeded(w, h, &masked, SEED, None);
+    assert_eq!(result, Ok(()));
+
+    let csv = wfc::entropy_csv(
+        wfc_state.superpositions(),
+        wfc_state.masked(),
+        &wfc_parameters.wfc_frequency,
+        w,
+    );
+    let rows = rows(&csv);
+    assert_eq!(rows.len(), w * h);
+    for (i, row) in rows.iter().enumerate() {
+        let expected = [
+            (i % w).to_string(),
+            (i / w).to_string(),
+            "1".into(),
+            "0".into(),
+        ];
+        assert_eq!(row, &expected);
+    }
+}
+
+#[test]
+fn cells_that_are_not_collapsed_have_entropy() {
+    let (w, h) = SIZE;
+    let wfc_parameters = load_input("inputimage3.png");
+    //Checked after the first collapse, when most cells are not collapsed yet
+    let mut steps = 0;
+    let masked = vec![false; w * h];
+    let mut on_step = |superpositions: &[wfc::Superposition], _: usize| {
+        steps += 1;
+        if steps == 1 {
+            let csv = wfc::entropy_csv(superpositions, &masked, &wfc_parameters.wfc_frequency, w);
+            let uncollapsed = rows(&csv)
+                .iter()
+                .filter(|row| row[2].parse::<usize>().unwrap() > 1)
+                .inspect(|row| assert!(row[3].parse::<f32>().unwrap() > 0.0, "{row:?}"))
+                .count();
+            assert!(uncollapsed > 0);
+        }
+    };
+    let _ = wfc_parameters.generate_state_masked_seeded(w, h, &masked, SEED, Some(&mut on_step));
+    assert!(steps > 0);
+}
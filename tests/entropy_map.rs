@@ -0,0 +1,97 @@
+//Checks the grayscale image of the entropy of each cell saved with
+//--entropy-map
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::WFCState;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: (usize, usize) = (10, 6);
+const WHITE: u32 = 0xffffffff;
+const BLACK: u32 = 0xff000000;
+
+#[test]
+fn a_fresh_grid_exports_a_uniform_image() {
+    let parameters = load_input(INPUT);
+    let (w, h) = SIZE;
+    let wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let image = wfc::entropy_image(
+        wfc_state.superpositions(),
+        wfc_state.masked(),
+        &parameters.wfc_frequency,
+        w,
+        h,
+    );
+    assert_eq!((image.width(), image.height()), SIZE);
+    //Every cell has every tile left, which is the largest entropy
+    assert!(image.pixels().iter().all(|pixel| *pixel == WHITE));
+
+    //Saving and loading it again keeps it uniform
+    let path = std::env::temp_dir().join(format!("entropy_map_{}.png", std::process::id()));
+    let path = path.to_string_lossy();
+    image.save_png(&path).unwrap();
+    let loaded = ImageData::load_png(&path).unwrap_or_else(|e| panic!("{e}"));
+    std::fs::remove_file(&*path).unwrap();
+    assert_eq!(loaded.pixels(), image.pixels());
+}
+
+#[test]
+fn collapsed_and_masked_cells_are_black() {
+    let parameters = load_input(INPUT);
+    let (w, h) = SIZE;
+    let mut masked = vec![false; w * h];
+    masked[0] = true;
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut wfc_state = WFCState::new_masked(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &masked,
+        &mut rng,
+    );
+    parameters.step(w, h, &mut wfc_state, &mut rng).unwrap();
+    let image = wfc::entropy_image(
+        wfc_state.superpositions(),
+        wfc_state.masked(),
+        &parameters.wfc_frequency,
+        w,
+        h,
+    );
+    assert_eq!(image.pixels()[0], BLACK);
+    for (pixel, superposition) in image
+        .pixels()
+        .iter()
+        .zip(wfc_state.superpositions())
+        .skip(1)
+    {
+        if superposition.len() == 1 {
+            assert_eq!(*pixel, BLACK);
+        }
+    }
+    assert!(
+        image
+            .pixels()
+            .iter()
+            .filter(|pixel| **pixel == BLACK)
+            .count()
+            > 1
+    );
+}
@@ -0,0 +1,79 @@
+//Checks the explanation of why a cell ran out of tiles
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{WFCParameters, WFCState};
+
+const RIGHT: usize = 1;
+const LEFT: usize = 3;
+const A: usize = 0;
+const B: usize = 1;
+const C: usize = 2;
+const TILES: usize = 3;
+//A row of three cells, the center is between an A and a C
+const SIZE: (usize, usize) = (3, 1);
+const CENTER: (usize, usize) = (1, 0);
+
+//Everything is allowed except that only B can be right of A and only C can
+//be left of C
+fn build_parameters() -> WFCParameters {
+    let mut rules = all_allowed(TILES, &OFFSETS);
+    for (direction, id1, id2) in [(RIGHT, A, A), (RIGHT, A, C), (LEFT, C, A), (LEFT, C, B)] {
+        rules.remove_rule(direction, id1, id2);
+        rules.remove_rule(rules.opposite(direction), id2, id1);
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+#[test]
+fn both_neighbors_are_named() {
+    let (w, h) = SIZE;
+    let mut parameters = build_parameters();
+    let mut wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    //A on the left leaves only B for the center, then C on the right only
+    //allows C there
+    parameters
+        .place_tile(&mut wfc_state, 0, A, w, h, false)
+        .unwrap();
+    assert!(parameters
+        .place_tile(&mut wfc_state, 2, C, w, h, false)
+        .is_err());
+    let contradiction = wfc_state.contradiction(w, 1).unwrap();
+    assert_eq!((contradiction.x, contradiction.y), CENTER);
+
+    let (x, y) = CENTER;
+    let explanation = parameters.explain_contradiction(&wfc_state, x, y, w, h);
+    assert_eq!((explanation.x, explanation.y), CENTER);
+    let mut named: Vec<(usize, usize, usize, Vec<usize>)> = explanation
+        .constraints
+        .iter()
+        .map(|constraint| {
+            let allowed = constraint.allowed.clone();
+            (constraint.direction, constraint.x, constraint.tile, allowed)
+        })
+        .collect();
+    named.sort();
+    assert_eq!(named, vec![(RIGHT, 2, C, vec![C]), (LEFT, 0, A, vec![B])]);
+    assert!(explanation.remaining.is_empty());
+
+    let text = explanation.to_string();
+    assert!(text.contains("tile 0 at (0, 0)"), "{text}");
+    assert!(text.contains("tile 2 at (2, 0)"), "{text}");
+    assert!(text.contains("no tile is allowed by all of them"), "{text}");
+}
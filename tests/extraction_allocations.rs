@@ -0,0 +1,75 @@
+//Checks that sampling the tiles of an input doesn't allocate for every
+//position of the input, only for the tiles that are new
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use image_data::ImageData;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use wfc::{ExtractionOptions, WFCParameters};
+
+//Counts every allocation made by this test binary
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+//inputimage4.png has only a few tiles, repeated until the input is this
+//large
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 128;
+
+//The input repeated to SIZE x SIZE
+fn large_input() -> ImageData {
+    let data = load_image(INPUT);
+    let mut pixels = vec![0; SIZE * SIZE];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let (x, y) = (i % SIZE, i / SIZE);
+        *pixel = data.get_pixel_wrap(x as isize, y as isize);
+    }
+    ImageData::from_pixels(&pixels, SIZE, SIZE)
+}
+
+#[test]
+fn sampling_allocates_per_tile_not_per_position() {
+    let data = large_input();
+    for rotations in [false, true] {
+        let options = ExtractionOptions {
+            rotations,
+            ..ExtractionOptions::default()
+        };
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        WFCParameters::from_image_data_with_options(&data, 3, &options).unwrap();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        //Sampling used to allocate at least once per position
+        assert!(
+            allocations < SIZE * SIZE,
+            "rotations {rotations}: {allocations} allocations for {} positions",
+            SIZE * SIZE
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//Checks that filling the cells that run out of tiles always finishes the
+//output
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::{ContradictionPolicy, Issue};
+
+//brick.png runs into a contradiction at this size with every seed
+const SIZE: (usize, usize) = (12, 10);
+const SEEDS: u64 = 8;
+const FILL_TILE: usize = 0;
+
+#[test]
+fn filling_always_finishes() {
+    let mut parameters = load_input("brick.png");
+    let (w, h) = SIZE;
+    let masked = vec![false; w * h];
+    for seed in 0..SEEDS {
+        let (_, result) = parameters.generate_state_masked_seeded(w, h, &masked, seed, None);
+        assert!(
+            result.is_err(),
+            "seed {seed} did not run into a contradiction"
+        );
+    }
+
+    parameters.wfc_contradiction_policy = ContradictionPolicy::Fill(FILL_TILE);
+    assert_eq!(parameters.validate(), Ok(()));
+    for seed in 0..SEEDS {
+        let (wfc_state, result) =
+            parameters.generate_state_masked_seeded(w, h, &masked, seed, None);
+        assert_eq!(result, Ok(()), "seed {seed}");
+        assert!(wfc_state.done());
+        assert!(
+            wfc_state
+                .superpositions()
+                .iter()
+                .all(|superposition| superposition.len() == 1),
+            "seed {seed} left cells that are not collapsed"
+        );
+        assert!(!wfc_state.filled().is_empty(), "seed {seed} filled nothing");
+        let mut unique = wfc_state.filled().to_vec();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(
+            unique.len(),
+            wfc_state.filled().len(),
+            "seed {seed} listed a cell twice"
+        );
+        for index in wfc_state.filled() {
+            assert_eq!(
+                wfc_state.superpositions()[*index].first(),
+                Some(FILL_TILE),
+                "seed {seed}, cell {index}"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_fill_tile_has_to_exist() {
+    let mut parameters = load_input("brick.png");
+    let tile = parameters.wfc_tiles.len();
+    parameters.wfc_contradiction_policy = ContradictionPolicy::Fill(tile);
+    assert_eq!(
+        parameters.validate(),
+        Err(vec![Issue::UnknownFillTile { tile }])
+    );
+
+    //Generating without validating first fails instead of panicking
+    let (w, h) = SIZE;
+    let masked = vec![false; w * h];
+    for seed in 0..SEEDS {
+        let (_, result) = parameters.generate_state_masked_seeded(w, h, &masked, seed, None);
+        let msg = result.err().unwrap().to_string();
+        assert!(
+            msg.contains(&format!("no tile {tile}")),
+            "seed {seed}: {msg}"
+        );
+    }
+}
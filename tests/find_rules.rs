@@ -0,0 +1,46 @@
+//Checks that grouping the tiles by edge finds the same rules as comparing
+//every pair of tiles
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use wfc::{tiles_match, ExtractionOptions, WFCParameters};
+
+const INPUTS: [&str; 2] = ["inputimage1.png", "inputimage6.png"];
+const TILE_SZ: isize = 3;
+
+#[test]
+fn rules_match_comparing_every_pair() {
+    for input in INPUTS {
+        let options = ExtractionOptions {
+            keep_patterns: true,
+            rotations: true,
+            rotation_weight: 100,
+            ..ExtractionOptions::default()
+        };
+        let parameters =
+            WFCParameters::from_image_data_with_options(&load_image(input), TILE_SZ, &options)
+                .unwrap();
+        let tiles = parameters.wfc_patterns.as_ref().unwrap();
+        let rules = &parameters.wfc_rules;
+        for (direction, offset) in rules.offsets().iter().enumerate() {
+            for (id1, tile1) in tiles.iter().enumerate() {
+                for (id2, tile2) in tiles.iter().enumerate() {
+                    assert_eq!(
+                        rules.okay(direction, id1, id2),
+                        tiles_match(tile1, tile2, offset.0, offset.1, TILE_SZ),
+                        "{input}: tiles {id1} and {id2} in direction {direction}"
+                    );
+                }
+            }
+        }
+    }
+}
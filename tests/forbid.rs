@@ -0,0 +1,53 @@
+//Checks that a forbidden pair of tiles never shows up in the output
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use wfc::WFCParameters;
+
+const TILES: usize = 3;
+//Direction of (1, 0)
+const RIGHT: usize = 1;
+const SIZE: usize = 8;
+const SEEDS: u64 = 64;
+
+#[test]
+fn forbidden_pairs_never_appear() {
+    let mut parameters = WFCParameters::from_parts(
+        (0..TILES as u32).collect(),
+        all_allowed(TILES, &OFFSETS),
+        vec![1; TILES],
+        1,
+    );
+    parameters.forbid(RIGHT, 0, 1);
+    //The rules still agree in both directions
+    assert!(!parameters.wfc_rules.okay(RIGHT, 0, 1));
+    assert!(!parameters
+        .wfc_rules
+        .okay(parameters.wfc_rules.opposite(RIGHT), 1, 0));
+    assert!(parameters.wfc_rules.check_symmetry().is_empty());
+
+    let mut lefts = 0;
+    for seed in 0..SEEDS {
+        let ids = parameters.generate_ids_seeded(SIZE, SIZE, seed).unwrap();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let (id, right) = (ids[y * SIZE + x], ids[y * SIZE + (x + 1) % SIZE]);
+                assert!((id, right) != (0, 1), "seed {seed} at ({x}, {y})");
+                if (id, right) == (1, 0) {
+                    lefts += 1;
+                }
+            }
+        }
+    }
+    //The pair the other way around is still allowed
+    assert!(lefts > 0);
+}
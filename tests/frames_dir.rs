@@ -0,0 +1,76 @@
+//Checks the numbered frames saved with --frames-dir
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/frames.rs"]
+mod frames;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use frames::FrameWriter;
+use image_data::ImageData;
+use wfc::{RenderOptions, Superposition};
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 8;
+const SPEED: usize = 5;
+
+#[test]
+fn a_short_run_saves_numbered_frames() {
+    let parameters = load_input(INPUT);
+    let dir = std::env::temp_dir().join(format!("frames_dir_{}", std::process::id()));
+    //The directory is created along with its parents
+    let frames_dir = dir.join("frames");
+    let mut frame_writer =
+        FrameWriter::new(&frames_dir.to_string_lossy(), SPEED, SIZE, SIZE).unwrap();
+
+    let masked = [false; SIZE * SIZE];
+    let mut collapses: usize = 0;
+    let mut on_step = |superpositions: &[Superposition], remaining: usize| {
+        collapses += 1;
+        frame_writer.step(
+            superpositions,
+            remaining,
+            &masked,
+            &parameters,
+            (SIZE, SIZE),
+            &RenderOptions::default(),
+        );
+    };
+    let seed = (0..64)
+        .find(|seed| {
+            parameters
+                .generate_grid_masked_seeded(SIZE, SIZE, &masked, *seed, None)
+                .is_ok()
+        })
+        .expect("a seed that succeeds");
+    let output = parameters
+        .generate_grid_masked_seeded(SIZE, SIZE, &masked, seed, Some(&mut on_step))
+        .unwrap();
+    assert!(frame_writer.error().is_none());
+
+    //A frame every SPEED collapses and the last state
+    let expected = collapses.div_ceil(SPEED);
+    assert!(expected > 1);
+    assert_eq!(frame_writer.count(), expected);
+    let mut names: Vec<String> = std::fs::read_dir(&frames_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    let expected_names: Vec<String> = (1..=expected)
+        .map(|i| format!("frame_{i:05}.png"))
+        .collect();
+    assert_eq!(names, expected_names);
+
+    let last = ImageData::load_png(&frames_dir.join(&names[expected - 1]).to_string_lossy())
+        .unwrap_or_else(|e| panic!("{e}"));
+    assert_eq!(last.pixels(), output.pixels());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
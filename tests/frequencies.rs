@@ -0,0 +1,71 @@
+//Checks the normalized frequencies and the entropy computed from them
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::{EntropyWeights, Superposition};
+
+const EPSILON: f32 = 1e-5;
+
+#[test]
+fn normalized_frequencies_sum_to_one() {
+    for input in ["inputimage1.png", "inputimage4.png", "brick.png"] {
+        let normalized = load_input(input).normalized_frequencies();
+        let sum: f32 = normalized.iter().sum();
+        assert!((sum - 1.0).abs() < EPSILON, "{input}: sum is {sum}");
+    }
+
+    assert_eq!(wfc::normalize_frequencies(&[0, 0]), vec![0.0, 0.0]);
+}
+
+#[test]
+fn entropy_from_normalized_frequencies_matches_counts() {
+    let frequencies = [5, 1, 0, 12, 3, 7];
+    let entropy_weights = EntropyWeights::new(&frequencies);
+    //Every subset of the tiles
+    for bits in 1u32..(1 << frequencies.len()) {
+        let mut superposition = Superposition::default();
+        superposition.fill(frequencies.len());
+        superposition.retain(|tile| bits & (1 << tile) != 0);
+        let expected = wfc::entropy(superposition.iter(), &frequencies);
+        let entropy = entropy_weights.entropy(&superposition);
+        assert!(
+            (entropy - expected).abs() < EPSILON,
+            "tiles {bits:b}: {entropy} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn tiles_that_are_never_seen_give_no_nan_entropy() {
+    //0 * log2(0) is NaN so tiles with a frequency of 0 have to be skipped
+    let frequencies = [0, 3, 0, 1];
+    let entropy_weights = EntropyWeights::new(&frequencies);
+    for bits in 1u32..(1 << frequencies.len()) {
+        let mut superposition = Superposition::default();
+        superposition.fill(frequencies.len());
+        superposition.retain(|tile| bits & (1 << tile) != 0);
+        let entropy = wfc::entropy(superposition.iter(), &frequencies);
+        assert!(!entropy.is_nan(), "tiles {bits:b}");
+        assert!(
+            !entropy_weights.entropy(&superposition).is_nan(),
+            "tiles {bits:b}"
+        );
+    }
+
+    //Only the unseen tiles are left
+    let mut superposition = Superposition::default();
+    superposition.fill(frequencies.len());
+    superposition.retain(|tile| frequencies[tile] == 0);
+    assert_eq!(wfc::entropy(superposition.iter(), &frequencies), 0.0);
+    assert_eq!(entropy_weights.entropy(&superposition), 0.0);
+    assert_eq!(wfc::entropy(0..2, &[0, 0]), 0.0);
+}
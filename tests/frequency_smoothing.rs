@@ -0,0 +1,100 @@
+//Checks that smoothing the frequencies lets rare tiles show up more often
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{generate_weighted, WFCParameters, WeightTransform};
+
+const COMMON: u32 = 0xff000000;
+const RARE: u32 = 0xffffffff;
+const SIZE: usize = 16;
+const SEEDS: u64 = 8;
+
+//Two tiles that can be next to anything, one seen far more often than the
+//other so the output is almost all the common tile
+fn dominated(transform: WeightTransform) -> WFCParameters {
+    let rules = all_allowed(2, &OFFSETS);
+    WFCParameters {
+        wfc_weight_transform: transform,
+        ..WFCParameters::from_parts(vec![COMMON, RARE], rules, vec![100, 1], 1)
+    }
+}
+
+//Rare pixels in the outputs of every seed
+fn rare_count(transform: WeightTransform) -> usize {
+    let parameters = dominated(transform);
+    (0..SEEDS)
+        .map(|seed| {
+            let output = parameters.generate_grid_seeded(SIZE, SIZE, seed).unwrap();
+            output
+                .pixels()
+                .iter()
+                .filter(|pixel| **pixel == RARE)
+                .count()
+        })
+        .sum()
+}
+
+#[test]
+fn lower_alpha_flattens_the_weights() {
+    assert_eq!(WeightTransform::Power(1.0).apply(100, 100), 1600);
+    assert_eq!(WeightTransform::Power(0.5).apply(100, 100), 160);
+    assert_eq!(WeightTransform::Power(0.5).apply(1, 100), 16);
+    assert_eq!(WeightTransform::AddK(10).apply(1, 100), 11);
+}
+
+#[test]
+fn smoothing_makes_rare_tiles_more_common() {
+    let unsmoothed = rare_count(WeightTransform::Power(1.0));
+    let alpha = rare_count(WeightTransform::Power(0.5));
+    let flattest = rare_count(WeightTransform::Power(0.1));
+    assert!(
+        unsmoothed < alpha,
+        "{unsmoothed} rare tiles with alpha 1, {alpha} with 0.5"
+    );
+    assert!(
+        alpha < flattest,
+        "{alpha} rare tiles with alpha 0.5, {flattest} with 0.1"
+    );
+    assert!(unsmoothed < rare_count(WeightTransform::AddK(50)));
+}
+
+#[test]
+fn inverse_weights_favor_rare_tiles() {
+    assert_eq!(WeightTransform::Inverse.apply(100, 100), 1);
+    assert_eq!(WeightTransform::Inverse.apply(1, 100), 100);
+    assert_eq!(WeightTransform::Sqrt.apply(100, 100), 160);
+
+    //The rare tile now has the larger weight so it takes over the output
+    let identity = rare_count(WeightTransform::Identity);
+    let inverse = rare_count(WeightTransform::Inverse);
+    assert!(inverse > SIZE * SIZE * SEEDS as usize / 2, "{inverse}");
+    assert!(
+        identity < inverse,
+        "{identity} rare tiles, {inverse} inverted"
+    );
+}
+
+#[test]
+fn weights_that_add_up_past_u32_do_not_overflow() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let weights = [u32::MAX, 0, u32::MAX];
+    let mut picks = [0; 3];
+    for _ in 0..1000 {
+        picks[generate_weighted(&mut rng, &weights)] += 1;
+    }
+    assert_eq!(picks[1], 0);
+    assert!(picks[0] > 400 && picks[2] > 400, "{picks:?}");
+
+    //AddK saturates instead of wrapping around
+    assert_eq!(WeightTransform::AddK(u32::MAX).apply(10, 10), u32::MAX);
+}
@@ -0,0 +1,40 @@
+//Checks the tile id grid that generate_ids returns
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const SEEDS: u64 = 8;
+
+#[test]
+fn ids_are_tiles_and_match_the_colors() {
+    let parameters = load_input(INPUT);
+    let mut generated = 0;
+    for seed in 0..SEEDS {
+        let Ok(ids) = parameters.generate_ids_seeded(SIZE, SIZE, seed) else {
+            continue;
+        };
+        generated += 1;
+        assert_eq!(ids.len(), SIZE * SIZE);
+        assert!(
+            ids.iter().all(|id| *id < parameters.wfc_tiles.len()),
+            "seed {seed}"
+        );
+
+        //The same seed gives the image drawn from those tiles
+        let image = parameters.generate_grid_seeded(SIZE, SIZE, seed).unwrap();
+        let colors: Vec<u32> = ids.iter().map(|id| parameters.wfc_tiles[*id]).collect();
+        assert_eq!(image.pixels(), &colors[..], "seed {seed}");
+    }
+    assert!(generated > 0);
+}
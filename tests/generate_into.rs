@@ -0,0 +1,76 @@
+//Checks generating into an image and a state that are reused
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::WFCState;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const SEEDS: u64 = 8;
+
+#[test]
+fn reused_buffers_give_the_same_output() {
+    let parameters = load_input(INPUT);
+    let mut wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let mut target = ImageData::new(SIZE, SIZE);
+    let mut generated = 0;
+    for seed in 0..SEEDS {
+        if parameters
+            .generate_into(&mut target, &mut wfc_state, seed)
+            .is_err()
+        {
+            continue;
+        }
+        generated += 1;
+        assert!(parameters.validate_image(&target).is_empty(), "seed {seed}");
+
+        //A fresh image and state give the same output for the seed
+        let mut fresh_state = WFCState::new(
+            SIZE,
+            SIZE,
+            &parameters.wfc_tiles,
+            &parameters.wfc_frequency,
+            &mut StdRng::seed_from_u64(seed),
+        );
+        let mut fresh = ImageData::new(SIZE, SIZE);
+        parameters
+            .generate_into(&mut fresh, &mut fresh_state, seed)
+            .unwrap();
+        assert_eq!(fresh.pixels(), target.pixels(), "seed {seed}");
+    }
+    assert!(generated > 0);
+}
+
+#[test]
+fn the_state_has_to_match_the_image() {
+    let parameters = load_input(INPUT);
+    let mut wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let mut target = ImageData::new(SIZE + 1, SIZE);
+    let error = parameters
+        .generate_into(&mut target, &mut wfc_state, 0)
+        .unwrap_err();
+    assert!(error.contains("13x12"), "{error}");
+}
@@ -0,0 +1,54 @@
+//Checks that generating and saving works without the window, these tests
+//are also run with the gui feature disabled (--no-default-features)
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{image_path, load_input};
+use image_data::ImageData;
+use std::process::Command;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 16;
+
+#[test]
+fn generate_grid_works_without_the_window() {
+    let parameters = load_input(INPUT);
+    let image = (0..16)
+        .find_map(|_| parameters.generate_grid(SIZE, SIZE).ok())
+        .expect("an output");
+    assert_eq!((image.width(), image.height()), (SIZE, SIZE));
+    assert!(parameters.validate_image(&image).is_empty());
+}
+
+#[test]
+fn the_binary_saves_the_output_headless() {
+    let dir = std::env::temp_dir().join(format!("headless_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let out = dir.join("output.png");
+    let output = Command::new(env!("CARGO_BIN_EXE_wave-function-collapse"))
+        .arg(image_path(INPUT))
+        .args(["--headless", "--quiet", "--seed", "1"])
+        .args(["--width", &SIZE.to_string(), "--height", &SIZE.to_string()])
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let image = ImageData::load_png(&out.to_string_lossy()).unwrap_or_else(|e| panic!("{e}"));
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!((image.width(), image.height()), (SIZE, SIZE));
+    assert!(load_input(INPUT).validate_image(&image).is_empty());
+}
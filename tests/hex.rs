@@ -0,0 +1,126 @@
+//Checks generating a grid of hexagons with six neighbors per cell
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::{ExtractionOptions, RuleTable, WFCParameters, HEX_OFFSETS};
+
+const TILES: usize = 3;
+const SIZE: usize = 6;
+const SEEDS: u64 = 8;
+const MAX_SEEDS: u64 = 64;
+const COLORS: [u32; TILES] = [0xffff0000, 0xff00ff00, 0xff0000ff];
+
+fn hex_neighbors(index: usize) -> Vec<(usize, usize)> {
+    wfc::neighbors(
+        &HEX_OFFSETS,
+        index % SIZE,
+        index / SIZE,
+        SIZE,
+        SIZE,
+        wfc::Boundary::Wrap,
+    )
+    .map(|(direction, x, y)| (direction, x + y * SIZE))
+    .collect()
+}
+
+//Neighboring hexagons can't be the same tile, three tiles are enough since
+//every hexagon and two of its neighbors that are next to each other use up
+//all three
+fn different_neighbors() -> WFCParameters {
+    let mut rules = RuleTable::new(TILES, &HEX_OFFSETS).unwrap();
+    for direction in 0..HEX_OFFSETS.len() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if id1 != id2 {
+                    rules.add_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters::from_parts(COLORS.to_vec(), rules, vec![1; TILES], 1)
+}
+
+#[test]
+fn hex_offsets_come_in_opposite_pairs() {
+    let rules = RuleTable::new(TILES, &HEX_OFFSETS).unwrap();
+    assert_eq!(rules.directions(), 6);
+    for direction in 0..6 {
+        let (dx, dy) = HEX_OFFSETS[direction];
+        assert_eq!(HEX_OFFSETS[rules.opposite(direction)], (-dx, -dy));
+    }
+    //Every cell has six different neighbors
+    let mut neighbors: Vec<usize> = hex_neighbors(0)
+        .into_iter()
+        .map(|(_, index)| index)
+        .collect();
+    neighbors.sort();
+    neighbors.dedup();
+    assert_eq!(neighbors.len(), 6);
+}
+
+#[test]
+fn generated_hexagons_follow_the_rules() {
+    let wfc_parameters = different_neighbors();
+    let mut finished = 0;
+    for seed in 0..MAX_SEEDS {
+        let Ok(ids) = wfc_parameters.generate_ids_seeded(SIZE, SIZE, seed) else {
+            continue;
+        };
+        for (index, id) in ids.iter().enumerate() {
+            for (direction, adj) in hex_neighbors(index) {
+                assert_ne!(
+                    *id, ids[adj],
+                    "seed {seed}: cell {index} in direction {direction}"
+                );
+            }
+        }
+        finished += 1;
+        if finished == SEEDS {
+            return;
+        }
+    }
+    panic!("only {finished} of {MAX_SEEDS} seeds finished");
+}
+
+//The color of each hexagon of the input is (x - y) mod 3, which gives each
+//color exactly one neighbor in each direction so the whole output follows
+//from the first collapse
+#[test]
+fn rules_are_learned_from_hexagons() {
+    let (w, h) = (3, 3);
+    let pixels: Vec<u32> = (0..w * h)
+        .map(|i| COLORS[(i % w + h - i / w) % TILES])
+        .collect();
+    let input = ImageData::from_pixels(&pixels, w, h);
+    let options = ExtractionOptions {
+        hex: true,
+        ..ExtractionOptions::default()
+    };
+    let wfc_parameters = WFCParameters::from_image_data_with_options(&input, 3, &options).unwrap();
+    assert_eq!(wfc_parameters.wfc_tiles.len(), TILES);
+    assert_eq!(wfc_parameters.wfc_rules.offsets(), &HEX_OFFSETS);
+    assert_eq!(wfc_parameters.wfc_frequency, vec![3; TILES]);
+
+    for seed in 0..SEEDS {
+        let output = wfc_parameters
+            .generate_grid_seeded(SIZE, SIZE, seed)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+        let shift = COLORS
+            .iter()
+            .position(|color| *color == output.get_pixel(0, 0))
+            .unwrap();
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let expected = COLORS[(x + shift + SIZE - y) % TILES];
+                assert_eq!(output.get_pixel(x, y), expected, "seed {seed}: ({x}, {y})");
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//Checks that the queue of tiles to collapse stays in sync with the
+//superpositions, and that check_invariants notices when it doesn't
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{WFCParameters, WFCState};
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const SEEDS: u64 = 8;
+
+fn checked_parameters() -> WFCParameters {
+    WFCParameters {
+        wfc_check_invariants: true,
+        ..load_input(INPUT)
+    }
+}
+
+fn new_state(parameters: &WFCParameters, seed: u64) -> (WFCState, StdRng) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    (wfc_state, rng)
+}
+
+#[test]
+fn generating_keeps_the_queue_in_sync() {
+    let plain = checked_parameters();
+    let variants = [
+        WFCParameters {
+            wfc_grow_from_collapsed: true,
+            ..plain.clone()
+        },
+        WFCParameters {
+            wfc_prefer_constrained: true,
+            ..plain.clone()
+        },
+        WFCParameters {
+            wfc_temperature: 1.0,
+            ..plain.clone()
+        },
+        WFCParameters {
+            wfc_collapses_per_step: 4,
+            ..plain.clone()
+        },
+        plain,
+    ];
+    for parameters in &variants {
+        for seed in 0..SEEDS {
+            //Panics as soon as a step leaves the state out of sync
+            let _ = parameters.generate_ids_seeded(SIZE, SIZE, seed);
+        }
+    }
+}
+
+//Collapses a few tiles and then gives a collapsed tile two options back
+//without putting it back in the queue
+fn desynced_state(parameters: &WFCParameters) -> (WFCState, StdRng) {
+    let (mut wfc_state, mut rng) = new_state(parameters, 0);
+    for _ in 0..4 {
+        parameters
+            .step(SIZE, SIZE, &mut wfc_state, &mut rng)
+            .unwrap();
+    }
+    assert!(wfc_state.check_invariants().is_ok());
+    let collapsed = (0..SIZE * SIZE)
+        .find(|i| wfc_state.superpositions()[*i].len() == 1)
+        .expect("a collapsed tile");
+    let superposition = wfc_state.superposition_mut(collapsed);
+    superposition.fill(parameters.wfc_tiles.len());
+    superposition.retain(|tile| tile < 2);
+    (wfc_state, rng)
+}
+
+#[test]
+fn a_tile_missing_from_the_queue_is_reported() {
+    let (wfc_state, _) = desynced_state(&checked_parameters());
+    let error = wfc_state.check_invariants().unwrap_err();
+    assert!(error.contains("not in the queue"), "{error}");
+}
+
+#[test]
+#[should_panic(expected = "out of sync")]
+fn checked_steps_panic_when_the_queue_is_out_of_sync() {
+    let parameters = checked_parameters();
+    let (mut wfc_state, mut rng) = desynced_state(&parameters);
+    let _ = parameters.step(SIZE, SIZE, &mut wfc_state, &mut rng);
+}
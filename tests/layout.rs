@@ -0,0 +1,97 @@
+//Checks which pixels of an image are drawn when part of it is outside of
+//the window
+#[path = "../src/layout.rs"]
+#[allow(dead_code)]
+mod layout;
+
+use layout::{compute_layout, visible_pixels};
+
+const WINDOW: (u32, u32) = (100, 80);
+const MAX_PIXEL_SIZE: f32 = 8.0;
+//Sizes a resized window can have, including very narrow and very flat ones
+const WINDOW_SIZES: [(u32, u32); 6] = [
+    (800, 640),
+    (1920, 1080),
+    (320, 240),
+    (200, 900),
+    (1200, 150),
+    (64, 64),
+];
+
+#[test]
+fn images_inside_the_window_are_drawn_whole() {
+    assert_eq!(
+        visible_pixels((10.0, 10.0), 4.0, (16, 16), WINDOW),
+        Some((0, 0, 16, 16))
+    );
+}
+
+#[test]
+fn pixels_outside_the_window_are_skipped() {
+    //Pixels 0 to 4 end at or left of x = 0 and rows 0 to 2 above y = 0,
+    //pixel 5 starts at x = -2 so it is partly visible
+    assert_eq!(
+        visible_pixels((-22.0, -12.0), 4.0, (40, 40), WINDOW),
+        Some((5, 3, 26, 20))
+    );
+
+    //Columns from x = 100 and rows from y = 80 on are past the window
+    assert_eq!(
+        visible_pixels((0.0, 0.0), 10.0, (20, 20), WINDOW),
+        Some((0, 0, 10, 8))
+    );
+}
+
+#[test]
+fn images_outside_the_window_are_not_drawn() {
+    assert_eq!(visible_pixels((100.0, 0.0), 4.0, (8, 8), WINDOW), None);
+    assert_eq!(visible_pixels((0.0, -32.0), 4.0, (8, 8), WINDOW), None);
+    assert_eq!(visible_pixels((-40.0, -40.0), 4.0, (8, 8), WINDOW), None);
+    assert_eq!(visible_pixels((0.0, 0.0), 0.0, (8, 8), WINDOW), None);
+}
+
+//Where an image placed by a layout ends in the window
+fn image_end(pos: (f32, f32), pixel_size: f32, image: (usize, usize)) -> (f32, f32) {
+    (
+        pos.0 + image.0 as f32 * pixel_size,
+        pos.1 + image.1 as f32 * pixel_size,
+    )
+}
+
+#[test]
+fn images_fit_any_window_size_without_overlapping() {
+    let images = [
+        ((16, 16), (64, 64)),
+        ((32, 8), (48, 48)),
+        ((5, 40), (80, 20)),
+    ];
+    for window in WINDOW_SIZES {
+        for (input, output) in images {
+            let layout = compute_layout(input, output, window, MAX_PIXEL_SIZE);
+            assert!(layout.pixel_size > 0.0 && layout.pixel_size <= MAX_PIXEL_SIZE);
+
+            let input_end = image_end(layout.input_pos, layout.pixel_size, input);
+            let output_end = image_end(layout.output_pos, layout.pixel_size, output);
+            for end in [input_end, output_end] {
+                assert!(
+                    end.0 <= window.0 as f32 && end.1 <= window.1 as f32,
+                    "{input:?} and {output:?} don't fit in {window:?}: {layout:?}"
+                );
+            }
+
+            let apart = input_end.0 <= layout.output_pos.0 || input_end.1 <= layout.output_pos.1;
+            assert!(
+                apart,
+                "{input:?} and {output:?} overlap in {window:?}: {layout:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn larger_windows_never_shrink_the_images() {
+    let (input, output) = ((16, 16), (64, 64));
+    let small = compute_layout(input, output, (320, 240), MAX_PIXEL_SIZE);
+    let large = compute_layout(input, output, (800, 640), MAX_PIXEL_SIZE);
+    assert!(large.pixel_size >= small.pixel_size);
+}
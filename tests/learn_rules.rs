@@ -0,0 +1,64 @@
+//Checks that placing a tile by hand can teach the rules a new pair of tiles
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{WFCParameters, WFCState};
+
+const TILES: usize = 3;
+const SIZE: usize = 4;
+//Tile 1 is not allowed to the right of tile 0 (direction 1 is (1, 0))
+const RIGHT: usize = 1;
+const FORBIDDEN: (usize, usize) = (0, 1);
+
+//Every pair of tiles is allowed except FORBIDDEN
+fn build_parameters() -> WFCParameters {
+    let mut rules = all_allowed(TILES, &OFFSETS);
+    let (id1, id2) = FORBIDDEN;
+    rules.remove_rule(RIGHT, id1, id2);
+    rules.remove_rule(rules.opposite(RIGHT), id2, id1);
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+//Places the second tile of FORBIDDEN and then the first tile to its left
+fn place_forbidden_pair(parameters: &mut WFCParameters, learn: bool) -> Result<usize, String> {
+    let mut wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let (id1, id2) = FORBIDDEN;
+    parameters.place_tile(&mut wfc_state, 1, id2, SIZE, SIZE, false)?;
+    parameters.place_tile(&mut wfc_state, 0, id1, SIZE, SIZE, learn)
+}
+
+#[test]
+fn placing_a_forbidden_pair_fails_without_learning() {
+    let mut parameters = build_parameters();
+    assert!(place_forbidden_pair(&mut parameters, false).is_err());
+    assert!(!parameters.wfc_rules.okay(RIGHT, FORBIDDEN.0, FORBIDDEN.1));
+}
+
+#[test]
+fn placing_a_forbidden_pair_with_learning_allows_it() {
+    let mut parameters = build_parameters();
+    let (id1, id2) = FORBIDDEN;
+    assert_eq!(place_forbidden_pair(&mut parameters, true), Ok(1));
+    assert!(parameters.wfc_rules.okay(RIGHT, id1, id2));
+    let opposite = parameters.wfc_rules.opposite(RIGHT);
+    assert!(parameters.wfc_rules.okay(opposite, id2, id1));
+
+    //Nothing is left to learn the second time
+    assert_eq!(place_forbidden_pair(&mut parameters, true), Ok(0));
+}
@@ -0,0 +1,46 @@
+//Checks that loading a png that can't be used says why
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::{ImageData, ImageError};
+use std::{fs::File, io::BufWriter};
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("load_png_{}_{name}", std::process::id()));
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn missing_files_are_io_errors() {
+    let error = ImageData::load_png(&temp_path("missing.png"))
+        .err()
+        .unwrap();
+    assert!(matches!(error, ImageError::Io(_)), "{error}");
+}
+
+#[test]
+fn files_that_are_not_pngs_are_decode_errors() {
+    let path = format!("{}/Cargo.toml", env!("CARGO_MANIFEST_DIR"));
+    let error = ImageData::load_png(&path).err().unwrap();
+    assert!(matches!(error, ImageError::Decode(_)), "{error}");
+}
+
+#[test]
+fn rgb_pngs_are_rejected() {
+    let path = temp_path("rgb.png");
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(&path).unwrap()), 2, 1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[255, 0, 0, 0, 255, 0]).unwrap();
+    writer.finish().unwrap();
+
+    let error = ImageData::load_png(&path).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(
+        matches!(error, ImageError::UnsupportedColorType(png::ColorType::Rgb)),
+        "{error}"
+    );
+    assert!(error.to_string().contains("RGBA"), "{error}");
+}
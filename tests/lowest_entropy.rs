@@ -0,0 +1,103 @@
+//Checks that the solver collapses the same cells in the same order as
+//scanning every cell for the lowest entropy would. Ties are broken by the
+//noise the queue draws for each cell when the state is reset, the scan
+//draws the same noise from a copy of the random number generator
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use cell_queue::quantize_entropy;
+use common::load_input;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wfc::{random_element, EntropyWeights, WFCParameters, WFCState};
+
+const INPUTS: [&str; 2] = ["inputimage3.png", "inputimage4.png"];
+const SIZE: usize = 16;
+const SEEDS: u64 = 4;
+
+//The cell with the lowest (entropy, noise, index) of the cells that are not
+//collapsed yet
+fn lowest_entropy(wfc_state: &WFCState, weights: &EntropyWeights, noise: &[u32]) -> Option<usize> {
+    let superpositions = wfc_state.superpositions();
+    (0..superpositions.len())
+        .filter(|i| superpositions[*i].len() > 1)
+        .min_by_key(|i| {
+            let entropy = quantize_entropy(weights.entropy(&superpositions[*i]));
+            (entropy, noise[*i], *i)
+        })
+}
+
+//Collapses the cell into one of its tiles the way the solver does and
+//propagates it
+fn collapse(
+    parameters: &WFCParameters,
+    wfc_state: &mut WFCState,
+    index: usize,
+    rng: &mut StdRng,
+) -> Result<(), String> {
+    let options: Vec<usize> = wfc_state.superpositions()[index].iter().collect();
+    let weights: Vec<u32> = options
+        .iter()
+        .map(|tile| parameters.wfc_frequency[*tile])
+        .collect();
+    let tile = random_element(&options, rng, Some(&weights)).unwrap();
+    parameters.restrict_cell(wfc_state, index, SIZE, SIZE, |id| id == tile)?;
+    Ok(())
+}
+
+#[test]
+fn collapses_match_a_full_scan() {
+    for input in INPUTS {
+        let parameters = load_input(input);
+        let weights = EntropyWeights::new(&parameters.wfc_frequency);
+        for seed in 0..SEEDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut solver = WFCState::new(
+                SIZE,
+                SIZE,
+                &parameters.wfc_tiles,
+                &parameters.wfc_frequency,
+                &mut rng,
+            );
+            let start = rng.gen_range(0..SIZE * SIZE);
+            let noise: Vec<u32> = {
+                let mut rng = rng.clone();
+                (0..SIZE * SIZE).map(|_| rng.gen()).collect()
+            };
+            solver.set_start(
+                Some(start),
+                &parameters.wfc_tiles,
+                &parameters.wfc_frequency,
+                &mut rng,
+            );
+            let mut scanned = solver.clone();
+            let mut scan_rng = rng.clone();
+
+            //Only the start cell is queued before the first collapse
+            let mut next = Some(start);
+            let mut steps = 0;
+            while let Some(index) = next {
+                let expected = collapse(&parameters, &mut scanned, index, &mut scan_rng);
+                let result = parameters.step(SIZE, SIZE, &mut solver, &mut rng);
+                assert_eq!(result.is_ok(), expected.is_ok(), "{input} seed {seed}");
+                assert!(
+                    solver.superpositions() == scanned.superpositions(),
+                    "{input} seed {seed}: the solver did not collapse cell {index} at step {steps}"
+                );
+                if result.is_err() {
+                    break;
+                }
+                next = lowest_entropy(&scanned, &weights, &noise);
+                steps += 1;
+            }
+            assert!(steps > 1, "{input} seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//Checks that a manifest saved next to an output can be loaded again
+#[path = "../src/manifest.rs"]
+mod manifest;
+
+use manifest::{manifest_path, Manifest};
+
+fn temp_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("manifest_{}_{name}", std::process::id()));
+    path.to_string_lossy().into_owned()
+}
+
+fn manifest() -> Manifest {
+    Manifest {
+        input: "inputimage4.png".to_string(),
+        mask: Some("mask.png".to_string()),
+        palette: None,
+        guide: None,
+        seed: 1234,
+        width: 48,
+        height: 32,
+        render_size: Some((96, 64)),
+        tile_size: 3,
+        color_tolerance: 2,
+        rotation_weight: Some(50),
+        hex: false,
+        finite_input: true,
+        frequency_alpha: Some(0.5),
+        frequency_add: None,
+        fill_contradictions: Some(0),
+        band_height: None,
+        symmetric: true,
+        boundary: "finite".to_string(),
+        version: "0.1.0".to_string(),
+    }
+}
+
+#[test]
+fn saving_and_loading_gives_the_same_manifest() {
+    let path = manifest_path(&temp_path("output.png"));
+    assert!(path.ends_with(".json"), "{path}");
+    manifest().save(&path).unwrap();
+    let loaded = Manifest::from_manifest(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), manifest());
+}
+
+#[test]
+fn the_arguments_set_the_saved_options() {
+    let args = manifest().to_args();
+    let has = |flag: &str, value: &str| args.windows(2).any(|pair| pair == [flag, value]);
+    assert_eq!(&args[..3], ["generate", "inputimage4.png", "3"]);
+    assert!(has("--seed", "1234"));
+    assert!(has("--width", "48") && has("--height", "32"));
+    assert!(has("--render-width", "96") && has("--render-height", "64"));
+    assert!(has("--rotation-weight", "50"));
+    assert!(has("--mask", "mask.png"));
+    assert!(args.contains(&"--finite-output".to_string()));
+    assert!(args.contains(&"--finite-input".to_string()));
+}
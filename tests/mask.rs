@@ -0,0 +1,59 @@
+//Checks that masked out parts of the output are left blank
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use image_data::ImageData;
+use wfc::WFCParameters;
+
+const COLORS: [u32; 2] = [0xff0000ff, 0xff00ff00];
+const SIZE: usize = 8;
+const SEEDS: u64 = 4;
+
+#[test]
+fn transparent_mask_pixels_are_scaled_to_the_output() {
+    //Only the top left pixel is transparent
+    let mask = ImageData::from_pixels(&[0, 0xffffffff, 0xffffffff, 0xff000000], 2, 2);
+    let masked = mask.transparent_mask(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            assert_eq!(
+                masked[y * SIZE + x],
+                x < SIZE / 2 && y < SIZE / 2,
+                "({x}, {y})"
+            );
+        }
+    }
+}
+
+#[test]
+fn masked_cells_are_left_blank() {
+    let parameters = WFCParameters::from_parts(
+        COLORS.to_vec(),
+        all_allowed(COLORS.len(), &OFFSETS),
+        vec![1; COLORS.len()],
+        1,
+    );
+    let mask = ImageData::from_pixels(&[0, 0xffffffff, 0xffffffff, 0xffffffff], 2, 2);
+    let masked = mask.transparent_mask(SIZE, SIZE);
+    for seed in 0..SEEDS {
+        let output = parameters
+            .generate_grid_masked_seeded(SIZE, SIZE, &masked, seed, None)
+            .unwrap();
+        for (pixel, masked) in output.pixels().iter().zip(&masked) {
+            if *masked {
+                assert_eq!(*pixel, 0);
+            } else {
+                assert!(COLORS.contains(pixel), "{pixel:#x}");
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//Checks the memory estimates that reject huge inputs, the sizes are only
+//passed to the estimates so nothing large is allocated
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageError;
+use wfc::RuleTable;
+
+const HUGE: usize = 100_000;
+
+#[test]
+fn huge_images_are_rejected_before_loading() {
+    assert_eq!(
+        image_data::load_bytes(HUGE, HUGE, 1),
+        8 * (HUGE * HUGE) as u128
+    );
+    match image_data::check_load_size(HUGE, HUGE, 1) {
+        Err(e @ ImageError::TooLarge { .. }) => assert!(e.to_string().contains("smaller crop")),
+        other => panic!("expected TooLarge, got {other:?}"),
+    }
+    //A small image with a huge number of frames
+    assert!(image_data::check_load_size(256, 256, u32::MAX as usize).is_err());
+    assert!(image_data::check_load_size(usize::MAX, usize::MAX, usize::MAX).is_err());
+
+    assert_eq!(image_data::load_bytes(64, 64, 1), 8 * 64 * 64);
+    assert!(image_data::check_load_size(1024, 1024, 1).is_ok());
+}
+
+#[test]
+fn huge_rule_tables_are_rejected() {
+    //A bool for each of the 4 * 100 * 100 pairs and 2 words of bits for
+    //each of the 4 * 100 neighbor sets
+    assert_eq!(RuleTable::bytes(100, 4), 40000 + 400 * 2 * 8);
+    assert!(wfc::check_memory(RuleTable::bytes(100, 4), "rules").is_ok());
+
+    let error = wfc::check_memory(RuleTable::bytes(HUGE, 4), "rules").unwrap_err();
+    assert!(error.contains("smaller tile size"), "{error}");
+    assert!(wfc::check_memory(RuleTable::bytes(usize::MAX, 4), "rules").is_err());
+}
+
+#[test]
+fn huge_inputs_are_rejected_before_sampling() {
+    assert!(wfc::check_memory(wfc::tile_bytes(64, 64, 3, 4), "tiles").is_ok());
+    assert!(wfc::check_memory(wfc::tile_bytes(HUGE, HUGE, 3, 1), "tiles").is_err());
+    assert!(wfc::check_memory(wfc::tile_bytes(usize::MAX, usize::MAX, 4, 4), "tiles").is_err());
+}
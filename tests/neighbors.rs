@@ -0,0 +1,102 @@
+//Checks the neighbors the solver visits around a cell for each boundary
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use wfc::{neighbors, Boundary, WFCParameters};
+
+const W: usize = 4;
+const H: usize = 3;
+
+fn neighbors_of(x: usize, y: usize, boundary: Boundary) -> Vec<(usize, usize, usize)> {
+    neighbors(&OFFSETS, x, y, W, H, boundary).collect()
+}
+
+#[test]
+fn interior_cells_have_every_neighbor() {
+    let expected = vec![(0, 1, 2), (1, 2, 1), (2, 1, 0), (3, 0, 1)];
+    assert_eq!(neighbors_of(1, 1, Boundary::Wrap), expected);
+    assert_eq!(neighbors_of(1, 1, Boundary::Finite), expected);
+}
+
+#[test]
+fn edge_cells() {
+    //Right edge
+    assert_eq!(
+        neighbors_of(3, 1, Boundary::Wrap),
+        vec![(0, 3, 2), (1, 0, 1), (2, 3, 0), (3, 2, 1)]
+    );
+    assert_eq!(
+        neighbors_of(3, 1, Boundary::Finite),
+        vec![(0, 3, 2), (2, 3, 0), (3, 2, 1)]
+    );
+    //Bottom edge
+    assert_eq!(
+        neighbors_of(2, 2, Boundary::Wrap),
+        vec![(0, 2, 0), (1, 3, 2), (2, 2, 1), (3, 1, 2)]
+    );
+    assert_eq!(
+        neighbors_of(2, 2, Boundary::Finite),
+        vec![(1, 3, 2), (2, 2, 1), (3, 1, 2)]
+    );
+}
+
+#[test]
+fn corner_cells() {
+    assert_eq!(
+        neighbors_of(0, 0, Boundary::Wrap),
+        vec![(0, 0, 1), (1, 1, 0), (2, 0, 2), (3, 3, 0)]
+    );
+    assert_eq!(
+        neighbors_of(0, 0, Boundary::Finite),
+        vec![(0, 0, 1), (1, 1, 0)]
+    );
+    assert_eq!(
+        neighbors_of(3, 2, Boundary::Finite),
+        vec![(2, 3, 1), (3, 2, 2)]
+    );
+    //Only the top and bottom wrap
+    assert_eq!(
+        neighbors_of(0, 0, Boundary::WrapY),
+        vec![(0, 0, 1), (1, 1, 0), (2, 0, 2)]
+    );
+    //Only the left and right wrap
+    assert_eq!(
+        neighbors_of(0, 0, Boundary::WrapX),
+        vec![(0, 0, 1), (1, 1, 0), (3, 3, 0)]
+    );
+    assert_eq!(Boundary::Wrap.finite_y(), Boundary::WrapX);
+    assert_eq!(Boundary::WrapY.finite_y(), Boundary::Finite);
+    assert_eq!(Boundary::WrapX.finite_x(), Boundary::Finite);
+}
+
+#[test]
+fn the_solver_uses_the_boundary_of_the_parameters() {
+    //Two tiles that have to alternate horizontally, which an odd width can
+    //only do if the left and right edges don't wrap
+    let mut rules = all_allowed(2, &OFFSETS);
+    for (direction, _) in OFFSETS.iter().enumerate().filter(|(_, (dx, _))| *dx != 0) {
+        rules.remove_rule(direction, 0, 0);
+        rules.remove_rule(direction, 1, 1);
+    }
+    let wrapped = WFCParameters::from_parts(vec![0, 1], rules, vec![1, 1], 1);
+    assert_eq!(wrapped.wfc_boundary, Boundary::Wrap);
+    let finite = WFCParameters {
+        wfc_boundary: Boundary::Finite,
+        ..wrapped.clone()
+    };
+    for seed in 0..4 {
+        assert!(wrapped.generate_ids_seeded(3, 2, seed).is_err());
+        let ids = finite.generate_ids_seeded(3, 2, seed).unwrap();
+        assert!(ids[0] != ids[1] && ids[1] != ids[2], "seed {seed}: {ids:?}");
+        assert!(wrapped.generate_ids_seeded(4, 2, seed).is_ok());
+    }
+}
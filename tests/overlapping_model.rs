@@ -0,0 +1,78 @@
+//Checks the overlapping model on inputs small enough to work out the output
+//by hand
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::WFCParameters;
+
+const A: u32 = 0xffff0000;
+const B: u32 = 0xff00ff00;
+const C: u32 = 0xff0000ff;
+const SEEDS: u64 = 8;
+
+fn generate(input: &ImageData, n: isize, w: usize, h: usize) -> Vec<ImageData> {
+    let wfc_parameters = WFCParameters::from_image_data(input, n).unwrap();
+    (0..SEEDS)
+        .map(|seed| {
+            wfc_parameters
+                .generate_grid_seeded(w, h, seed)
+                .unwrap_or_else(|e| panic!("seed {seed}: {e}"))
+        })
+        .collect()
+}
+
+//Every row of the input is A B C, with N = 2 the patterns are the columns
+//A B, B C and C A, each of which can only have the next one to its right
+//and itself above and below. The output has to be the same row everywhere,
+//A B C repeated from some column on
+#[test]
+fn stripes_are_continued() {
+    let input = ImageData::from_pixels(&[A, B, C, A, B, C, A, B, C], 3, 3);
+    let cycle = [A, B, C];
+    let (w, h) = (6, 4);
+    for output in generate(&input, 2, w, h) {
+        let start = cycle
+            .iter()
+            .position(|color| *color == output.get_pixel(0, 0));
+        let start = start.expect("the first pixel is not a color of the input");
+        for y in 0..h {
+            for x in 0..w {
+                assert_eq!(output.get_pixel(x, y), cycle[(start + x) % 3], "({x}, {y})");
+            }
+        }
+    }
+}
+
+//The two patterns of a checkerboard with N = 2 are each other's only
+//neighbor, so the output is a checkerboard. With N = 1 there is no overlap
+//and the two colors can be anywhere
+#[test]
+fn checkerboards_stay_checkerboards_with_overlap() {
+    let input = ImageData::from_pixels(&[A, B, B, A], 2, 2);
+    let (w, h) = (8, 8);
+    for output in generate(&input, 2, w, h) {
+        let corner = output.get_pixel(0, 0);
+        for y in 0..h {
+            for x in 0..w {
+                let same = (x + y) % 2 == 0;
+                assert_eq!(output.get_pixel(x, y) == corner, same, "({x}, {y})");
+            }
+        }
+    }
+
+    let outputs = generate(&input, 1, w, h);
+    let breaks_pattern = outputs.iter().any(|output| {
+        (0..w * h).any(|i| {
+            let (x, y) = (i % w, i / w);
+            (output.get_pixel(x, y) == output.get_pixel(0, 0)) != ((x + y) % 2 == 0)
+        })
+    });
+    assert!(breaks_pattern);
+}
@@ -0,0 +1,75 @@
+//Checks recoloring a generated image with a --palette remap file
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/palette.rs"]
+mod palette;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use palette::{load_palette, remap};
+
+const INPUT: &str = "inputimage1.png";
+const SIZE: usize = 16;
+
+//Pixels keep red in the lowest byte, the file has 0xAARRGGBB
+fn argb(pixel: u32) -> u32 {
+    (pixel & 0xff00ff00) | ((pixel >> 16) & 0xff) | ((pixel & 0xff) << 16)
+}
+
+#[test]
+fn a_two_entry_remap_swaps_the_colors() {
+    let (generated, _) = load_input(INPUT)
+        .generate_grid_until_success(SIZE, SIZE, 0, 64)
+        .unwrap();
+    let mut colors: Vec<u32> = generated.pixels().to_vec();
+    colors.sort();
+    colors.dedup();
+    assert!(
+        colors.len() > 2,
+        "the output needs a color that is not remapped"
+    );
+    let (a, b) = (colors[0], colors[1]);
+
+    let path = std::env::temp_dir().join(format!("palette_{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        format!(
+            "# swap the first two colors\n0x{:08x} 0x{:08x}\n\n0x{:08X} 0X{:08x}\n",
+            argb(a),
+            argb(b),
+            argb(b),
+            argb(a)
+        ),
+    )
+    .unwrap();
+    let palette = load_palette(&path.to_string_lossy()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(palette.len(), 2);
+
+    let mut recolored = generated.clone();
+    remap(&mut recolored, &palette);
+    for (before, after) in generated.pixels().iter().zip(recolored.pixels()) {
+        let expected = match *before {
+            color if color == a => b,
+            color if color == b => a,
+            color => color,
+        };
+        assert_eq!(*after, expected);
+    }
+}
+
+#[test]
+fn lines_without_two_colors_are_rejected() {
+    let path = std::env::temp_dir().join(format!("palette_bad_{}.txt", std::process::id()));
+    std::fs::write(&path, "0xff000000 0xffffffff\n0xff000000\n").unwrap();
+    let error = load_palette(&path.to_string_lossy()).unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains(":2:"), "{error}");
+}
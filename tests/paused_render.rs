@@ -0,0 +1,88 @@
+//Checks that the output drawn while paused is always the current state
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/layout.rs"]
+mod layout;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use layout::throttled;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{RenderOptions, WFCState};
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const SPEED: u32 = 16;
+const FRAMES: u32 = 200;
+
+#[test]
+fn only_running_frames_are_throttled() {
+    for frame in 0..FRAMES {
+        assert!(!throttled(false, frame, SPEED));
+        assert_eq!(throttled(true, frame, SPEED), frame % SPEED != 0);
+    }
+}
+
+//Runs the window's loop without a window, pausing every few frames and
+//stepping while paused, and checks what would be drawn after each frame
+#[test]
+fn paused_frames_show_the_current_state() {
+    let parameters = load_input(INPUT);
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut wfc_state = WFCState::new(
+        SIZE,
+        SIZE,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    let mut drawn = ImageData::new(SIZE, SIZE);
+    let mut changed = true;
+    for frame in 0..FRAMES {
+        let paused = frame / 7 % 2 == 1;
+        let step = paused && frame % 3 == 0;
+        let running = !paused && !wfc_state.done();
+        if (running || step) && !wfc_state.done() {
+            changed = true;
+            if parameters
+                .step(SIZE, SIZE, &mut wfc_state, &mut rng)
+                .is_err()
+            {
+                wfc_state.reset(&parameters.wfc_tiles, &parameters.wfc_frequency, &mut rng);
+            }
+        }
+        if changed && !throttled(running, frame, SPEED) {
+            wfc::copy_superpositions_to_grid(
+                drawn.pixels_mut(),
+                wfc_state.superpositions(),
+                wfc_state.masked(),
+                (SIZE, SIZE),
+                &parameters.wfc_tiles,
+                &parameters.wfc_frequency,
+                &RenderOptions::default(),
+            );
+            changed = false;
+        }
+
+        if paused {
+            let current = wfc::superpositions_to_image(
+                wfc_state.superpositions(),
+                wfc_state.masked(),
+                &parameters.wfc_tiles,
+                &parameters.wfc_frequency,
+                SIZE,
+                SIZE,
+                &RenderOptions::default(),
+            );
+            assert!(drawn.pixels() == current.pixels(), "frame {frame}");
+        }
+    }
+}
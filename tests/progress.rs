@@ -0,0 +1,61 @@
+//Checks the progress shown while generating headless
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/progress.rs"]
+mod progress;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use progress::Progress;
+
+const SIZE: usize = 16;
+//A seed that generates the output without a contradiction
+const SEED: u64 = 1;
+
+#[test]
+fn progress_reaches_100_percent() {
+    let parameters = load_input("inputimage3.png");
+    let masked = vec![false; SIZE * SIZE];
+
+    let mut progress = Progress::new(vec![], masked.len());
+    let mut calls = 0;
+    let mut on_step = |_: &[wfc::Superposition], remaining: usize| {
+        calls += 1;
+        progress.step(remaining);
+    };
+    let (_, result) =
+        parameters.generate_state_masked_seeded(SIZE, SIZE, &masked, SEED, Some(&mut on_step));
+    result.unwrap();
+    progress.finish();
+
+    assert!(calls > 0);
+    assert_eq!(progress.shown(), Some(100));
+    let shown = String::from_utf8(progress.into_inner()).unwrap();
+    let last = shown.trim_end().rsplit('\r').next().unwrap();
+    assert_eq!(last.trim_end(), "collapsing 100%");
+    assert!(shown.ends_with('\n'));
+}
+
+#[test]
+fn the_line_is_only_rewritten_when_the_percentage_changes() {
+    let mut progress = Progress::new(vec![], 1000);
+    for remaining in (995..1000).rev() {
+        progress.step(remaining);
+    }
+    let shown = String::from_utf8(progress.into_inner()).unwrap();
+    assert_eq!(shown.matches('\r').count(), 1);
+}
+
+#[test]
+fn nothing_to_collapse_is_done() {
+    let mut progress = Progress::new(vec![], 0);
+    progress.step(0);
+    assert_eq!(progress.shown(), Some(100));
+}
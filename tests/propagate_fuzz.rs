@@ -0,0 +1,212 @@
+//Randomized check of propagation, run with `cargo test`
+//The solver lives in the binary so its modules are included directly like
+//in the benchmarks
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wfc::{RuleTable, SymmetryPolicy, WFCParameters, WFCState};
+
+const CASES: u64 = 500;
+const MAX_TILES: usize = 6;
+const MAX_SIZE: usize = 5;
+const MAX_RESTRICTIONS: usize = 8;
+
+//A rule table and the cells that are restricted (a bitset of the tiles that
+//are kept) one after another, each restriction is propagated
+#[derive(Clone, Debug)]
+struct Case {
+    tiles: usize,
+    w: usize,
+    h: usize,
+    rules: Vec<(usize, usize, usize)>,
+    restrictions: Vec<(usize, u64)>,
+}
+
+fn random_case(rng: &mut StdRng) -> Case {
+    let tiles = rng.gen_range(1..=MAX_TILES);
+    let (w, h) = (rng.gen_range(1..=MAX_SIZE), rng.gen_range(1..=MAX_SIZE));
+    let density = rng.gen_range(0.1..0.9);
+    let mut rules = vec![];
+    for direction in 0..OFFSETS.len() {
+        for id1 in 0..tiles {
+            for id2 in 0..tiles {
+                if rng.gen_bool(density) {
+                    rules.push((direction, id1, id2));
+                }
+            }
+            //Every tile needs a neighbor in each direction so that the
+            //starting state (every tile everywhere) is consistent
+            rules.push((direction, id1, rng.gen_range(0..tiles)));
+        }
+    }
+    let restrictions = (0..rng.gen_range(1..=MAX_RESTRICTIONS))
+        .map(|_| (rng.gen_range(0..(w * h)), rng.gen::<u64>()))
+        .collect();
+    Case {
+        tiles,
+        w,
+        h,
+        rules,
+        restrictions,
+    }
+}
+
+fn build_parameters(case: &Case) -> Option<WFCParameters> {
+    let mut rules = RuleTable::new(case.tiles, &OFFSETS).ok()?;
+    for (direction, id1, id2) in &case.rules {
+        rules.add_rule(*direction, *id1, *id2);
+    }
+    //Propagation assumes that the rules agree in both directions
+    rules.make_symmetric(SymmetryPolicy::Or);
+    Some(WFCParameters::from_parts(
+        (0..case.tiles as u32).collect(),
+        rules,
+        vec![1; case.tiles],
+        1,
+    ))
+}
+
+//Every tile left in a cell needs an allowed tile in each neighbor
+fn check_supported(
+    parameters: &WFCParameters,
+    wfc_state: &WFCState,
+    w: usize,
+    h: usize,
+) -> Result<(), String> {
+    let superpositions = wfc_state.superpositions();
+    for (index, superposition) in superpositions.iter().enumerate() {
+        let neighbors = wfc::neighbors(&OFFSETS, index % w, index / w, w, h, wfc::Boundary::Wrap);
+        for (direction, adj_x, adj_y) in neighbors {
+            let adjacent = &superpositions[adj_x + adj_y * w];
+            for tile in superposition.iter() {
+                if !adjacent
+                    .iter()
+                    .any(|adj_tile| parameters.wfc_rules.okay(direction, tile, adj_tile))
+                {
+                    return Err(format!(
+                        "tile {tile} in cell {index} has no allowed neighbor in direction {direction}"
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+//Returns the invariant that was broken, contradictions are fine
+fn run_case(case: &Case) -> Result<(), String> {
+    let Some(parameters) = build_parameters(case) else {
+        return Ok(());
+    };
+    let (w, h) = (case.w, case.h);
+    if parameters.validate().is_err() {
+        //Shrinking can remove the rules that made the start consistent
+        return Ok(());
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    check_supported(&parameters, &wfc_state, w, h).map_err(|msg| format!("at the start: {msg}"))?;
+    for (step, (index, keep)) in case.restrictions.iter().enumerate() {
+        let before = wfc_state.superpositions().to_vec();
+        let result =
+            parameters.restrict_cell(&mut wfc_state, *index, w, h, |tile| keep & (1 << tile) != 0);
+        for (cell, (old, new)) in before.iter().zip(wfc_state.superpositions()).enumerate() {
+            if let Some(tile) = new.iter().find(|tile| !old.contains(*tile)) {
+                return Err(format!(
+                    "restriction {step}: tile {tile} was added to cell {cell}"
+                ));
+            }
+        }
+        if result.is_err() {
+            return Ok(());
+        }
+        check_supported(&parameters, &wfc_state, w, h)
+            .map_err(|msg| format!("restriction {step}: {msg}"))?;
+    }
+    Ok(())
+}
+
+//Smaller versions of a case, the first one that still fails is kept
+fn shrink_candidates(case: &Case) -> Vec<Case> {
+    let mut candidates = vec![];
+    for i in 0..case.restrictions.len() {
+        let mut smaller = case.clone();
+        smaller.restrictions.remove(i);
+        candidates.push(smaller);
+    }
+    for i in 0..case.rules.len() {
+        let mut smaller = case.clone();
+        smaller.rules.remove(i);
+        candidates.push(smaller);
+    }
+    if case.w > 1 {
+        candidates.push(Case {
+            w: case.w - 1,
+            ..case.clone()
+        });
+    }
+    if case.h > 1 {
+        candidates.push(Case {
+            h: case.h - 1,
+            ..case.clone()
+        });
+    }
+    if case.tiles > 1 {
+        let tiles = case.tiles - 1;
+        candidates.push(Case {
+            tiles,
+            rules: case
+                .rules
+                .iter()
+                .copied()
+                .filter(|(_, id1, id2)| *id1 < tiles && *id2 < tiles)
+                .collect(),
+            ..case.clone()
+        });
+    }
+    //Restrictions must stay inside the grid
+    for candidate in &mut candidates {
+        let cells = candidate.w * candidate.h;
+        candidate.restrictions.retain(|(index, _)| *index < cells);
+    }
+    candidates
+}
+
+fn shrink(mut case: Case) -> Case {
+    while let Some(smaller) = shrink_candidates(&case)
+        .into_iter()
+        .find(|candidate| run_case(candidate).is_err())
+    {
+        case = smaller;
+    }
+    case
+}
+
+#[test]
+fn propagate_keeps_invariants() {
+    for seed in 0..CASES {
+        let case = random_case(&mut StdRng::seed_from_u64(seed));
+        if run_case(&case).is_err() {
+            let minimal = shrink(case);
+            let msg = run_case(&minimal).unwrap_err();
+            panic!("seed {seed} failed: {msg}\nminimal case: {minimal:?}");
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//Checks that propagation passes on changes in which tiles a cell can be,
+//not just changes in how many tiles it can be
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use wfc::{RuleTable, WFCParameters};
+
+const TILES: usize = 2;
+const SEEDS: u64 = 16;
+const SIZE: (usize, usize) = (6, 4);
+
+//Each tile has to be next to the other tile horizontally and next to itself
+//vertically, so collapsing one cell decides every other cell
+fn swapped_neighbors() -> WFCParameters {
+    let mut rules = RuleTable::new(TILES, &OFFSETS).unwrap();
+    for (direction, (dx, _)) in OFFSETS.iter().enumerate() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if (*dx == 0) == (id1 == id2) {
+                    rules.add_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+#[test]
+fn swapped_tiles_are_propagated() {
+    let parameters = swapped_neighbors();
+    let (w, h) = SIZE;
+    let mut firsts = [false; TILES];
+    for seed in 0..SEEDS {
+        let ids = parameters.generate_ids_seeded(w, h, seed).unwrap();
+        let first = ids[0];
+        firsts[first] = true;
+        for y in 0..h {
+            for x in 0..w {
+                //Columns alternate between the tiles
+                let expected = if x % 2 == 0 { first } else { 1 - first };
+                assert_eq!(ids[x + y * w], expected, "seed {seed} at ({x}, {y})");
+            }
+        }
+    }
+    //Both ways around come up
+    assert_eq!(firsts, [true; TILES]);
+}
@@ -0,0 +1,84 @@
+//Checks how far changes are propagated and the reach that is reported
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{RuleTable, WFCParameters, WFCState};
+
+const TILES: usize = 2;
+const SIZE: (usize, usize) = (8, 3);
+//The cell that is restricted, in the middle row
+const CELL: (usize, usize) = (0, 1);
+
+//A tile can only be next to the same tile horizontally and next to anything
+//vertically, so picking a tile for a cell fills its whole row with it
+fn build_parameters(max_depth: Option<usize>) -> WFCParameters {
+    let mut rules = RuleTable::new(TILES, &OFFSETS).unwrap();
+    for (direction, (dx, _)) in OFFSETS.iter().enumerate() {
+        for id1 in 0..TILES {
+            for id2 in 0..TILES {
+                if *dx == 0 || id1 == id2 {
+                    rules.add_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters {
+        wfc_max_propagation_depth: max_depth,
+        ..WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+    }
+}
+
+//Restricts CELL to tile 0, returns the reach and the cells that were changed
+fn restrict(max_depth: Option<usize>) -> (usize, Vec<(usize, usize)>) {
+    let (w, h) = SIZE;
+    let parameters = build_parameters(max_depth);
+    let mut wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut StdRng::seed_from_u64(0),
+    );
+    let (x, y) = CELL;
+    let reach = parameters
+        .restrict_cell(&mut wfc_state, x + y * w, w, h, |tile| tile == 0)
+        .unwrap();
+    let changed = (0..w * h)
+        .filter(|i| wfc_state.superpositions()[*i].len() < TILES)
+        .map(|i| (i % w, i / w))
+        .collect();
+    (reach, changed)
+}
+
+#[test]
+fn unbounded_propagation_reports_its_reach() {
+    let (w, _) = SIZE;
+    let (reach, changed) = restrict(None);
+    //The whole row is changed, the cell furthest away is halfway around
+    let row: Vec<(usize, usize)> = (0..w).map(|x| (x, CELL.1)).collect();
+    assert_eq!(changed, row);
+    assert_eq!(reach, w / 2);
+}
+
+#[test]
+fn depth_one_only_changes_the_neighbors() {
+    let (w, _) = SIZE;
+    let (reach, changed) = restrict(Some(1));
+    let (x, y) = CELL;
+    assert_eq!(changed, vec![(x, y), (x + 1, y), (w - 1, y)]);
+    assert_eq!(reach, 1);
+
+    let (reach, changed) = restrict(Some(2));
+    assert_eq!(changed.len(), 5);
+    assert_eq!(reach, 2);
+}
@@ -0,0 +1,82 @@
+//Checks that regenerating an output keeps the cells it is asked to keep
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use wfc::WFCParameters;
+
+const SIZE: usize = 16;
+const MAX_SEEDS: u64 = 64;
+
+//Regenerates with the first seed from first_seed on that succeeds
+fn regenerate(
+    wfc_parameters: &WFCParameters,
+    ids: &[usize],
+    keep: &[(usize, usize)],
+    first_seed: u64,
+) -> Vec<usize> {
+    (first_seed..first_seed + MAX_SEEDS)
+        .find_map(|seed| {
+            wfc_parameters
+                .regenerate_except(ids, SIZE, SIZE, keep, seed)
+                .ok()
+        })
+        .expect("every seed failed")
+}
+
+#[test]
+fn kept_cells_are_unchanged() {
+    let wfc_parameters = load_input("inputimage3.png");
+    let ids = (0..MAX_SEEDS)
+        .find_map(|seed| wfc_parameters.generate_ids_seeded(SIZE, SIZE, seed).ok())
+        .expect("every seed failed");
+    //The top left quarter of the output
+    let keep: Vec<(usize, usize)> = (0..SIZE / 2)
+        .flat_map(|y| (0..SIZE / 2).map(move |x| (x, y)))
+        .collect();
+
+    let first = regenerate(&wfc_parameters, &ids, &keep, 0);
+    let second = regenerate(&wfc_parameters, &ids, &keep, 1);
+    for (x, y) in &keep {
+        let index = x + y * SIZE;
+        assert_eq!(first[index], ids[index], "({x}, {y})");
+        assert_eq!(second[index], ids[index], "({x}, {y})");
+    }
+    let changed = (0..SIZE * SIZE)
+        .filter(|index| !keep.contains(&(index % SIZE, index / SIZE)))
+        .filter(|index| first[*index] != second[*index])
+        .count();
+    assert!(changed > 0);
+
+    //Both are still outputs the rules allow
+    let colors = |ids: &[usize]| -> Vec<u32> {
+        ids.iter().map(|id| wfc_parameters.wfc_tiles[*id]).collect()
+    };
+    for output in [&first, &second] {
+        let image = ImageData::from_pixels(&colors(output), SIZE, SIZE);
+        assert_eq!(wfc_parameters.validate_image(&image), vec![]);
+    }
+}
+
+#[test]
+fn kept_cells_must_be_inside_the_output() {
+    let wfc_parameters = load_input("inputimage1.png");
+    let ids = vec![0; SIZE * SIZE];
+    let error = wfc_parameters
+        .regenerate_except(&ids, SIZE, SIZE, &[(SIZE, 0)], 0)
+        .unwrap_err();
+    assert!(error.contains("outside"), "{error}");
+    let error = wfc_parameters
+        .regenerate_except(&ids[1..], SIZE, SIZE, &[], 0)
+        .unwrap_err();
+    assert!(error.contains("16x16"), "{error}");
+}
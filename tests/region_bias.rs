@@ -0,0 +1,87 @@
+//Checks that regions of the output can prefer some tiles without forcing them
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::{RegionBias, WFCParameters};
+
+//inputimage1.png has a little bit of green grass
+const INPUT: &str = "inputimage1.png";
+const GRASS: u32 = 0xff1aff00;
+const SIZE: usize = 24;
+//First row of the lower third of the output
+const LOWER_THIRD: usize = SIZE * 2 / 3;
+const MULTIPLIER: f32 = 10.0;
+const SEEDS: u64 = 8;
+const MAX_ATTEMPTS: u32 = 64;
+
+//Fraction of the pixels in the lower third of the outputs of every seed
+//that are grass, and whether any of them are not grass
+fn grass_in_lower_third(wfc_parameters: &WFCParameters) -> (f32, bool) {
+    let mut grass = 0;
+    let mut other = 0;
+    for seed in 0..SEEDS {
+        let (image, _) = wfc_parameters
+            .generate_grid_until_success(SIZE, SIZE, seed, MAX_ATTEMPTS)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+        for pixel in &image.pixels()[LOWER_THIRD * SIZE..] {
+            if *pixel == GRASS {
+                grass += 1;
+            } else {
+                other += 1;
+            }
+        }
+    }
+    (grass as f32 / (grass + other) as f32, other > 0)
+}
+
+#[test]
+fn lower_third_bias_grows_grass_there() {
+    let unbiased = load_input(INPUT);
+    let mut biased = load_input(INPUT);
+    let grass_tiles = biased.tiles_with_color(GRASS);
+    assert!(!grass_tiles.is_empty());
+    biased.wfc_regions.push(RegionBias {
+        x: 0,
+        y: LOWER_THIRD,
+        width: SIZE,
+        height: SIZE - LOWER_THIRD,
+        tiles: grass_tiles,
+        multiplier: MULTIPLIER,
+    });
+
+    let (unbiased_grass, _) = grass_in_lower_third(&unbiased);
+    let (biased_grass, has_other) = grass_in_lower_third(&biased);
+    assert!(
+        biased_grass > unbiased_grass,
+        "grass went from {unbiased_grass} to {biased_grass}"
+    );
+    assert!(has_other, "the bias removed every other tile");
+}
+
+#[test]
+fn overlapping_regions_multiply() {
+    let region = |x, multiplier| RegionBias {
+        x,
+        y: 0,
+        width: 2,
+        height: 1,
+        tiles: vec![0, 2],
+        multiplier,
+    };
+    let regions = [region(0, 2.0), region(1, 3.0)];
+    let weight = |tile, x| -> f32 { regions.iter().map(|r| r.weight(tile, x, 0)).product() };
+    assert_eq!(weight(0, 0), 2.0);
+    assert_eq!(weight(0, 1), 6.0);
+    assert_eq!(weight(2, 2), 3.0);
+    assert_eq!(weight(1, 1), 1.0);
+    assert_eq!(weight(0, 3), 1.0);
+}
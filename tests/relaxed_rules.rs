@@ -0,0 +1,45 @@
+//Checks that relaxing the rules of a rule set that can't be solved
+//eventually gives a full output
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use wfc::{RuleTable, WFCParameters};
+
+const COLORS: [u32; 2] = [0xff0000ff, 0xff00ff00];
+const SIZE: usize = 6;
+const ATTEMPTS: u32 = 4;
+
+#[test]
+fn relaxing_an_impossible_rule_set_gives_a_full_grid() {
+    //No tile is allowed next to any tile so every attempt fails
+    let parameters = WFCParameters::from_parts(
+        COLORS.to_vec(),
+        RuleTable::new(COLORS.len(), &OFFSETS).unwrap(),
+        vec![3, 1],
+        1,
+    );
+    assert!(parameters
+        .generate_grid_until_success(SIZE, SIZE, 0, ATTEMPTS)
+        .is_err());
+    assert!(parameters
+        .generate_grid_relaxed(SIZE, SIZE, 0, ATTEMPTS, 0)
+        .is_err());
+
+    let (image, allowed) = parameters
+        .generate_grid_relaxed(SIZE, SIZE, 0, ATTEMPTS, 8)
+        .unwrap();
+    assert!(allowed > 0);
+    assert_eq!((image.width(), image.height()), (SIZE, SIZE));
+    //The pairs of the most frequent tile are allowed first, which is enough
+    //to fill the grid with it
+    assert!(image.pixels().iter().all(|pixel| *pixel == COLORS[0]));
+}
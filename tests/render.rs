@@ -0,0 +1,238 @@
+//Checks how cells that are not collapsed yet are drawn
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use wfc::{PartialRender, RenderOptions, Superposition};
+
+const OPAQUE_RED: u32 = 0xff0000ff;
+const TRANSPARENT_BLACK: u32 = 0x00000000;
+
+//Color of a single cell that can still be either of the tiles
+fn render_cell(tiles: &[u32], partial: PartialRender) -> u32 {
+    render_weighted_cell(tiles, &vec![1; tiles.len()], partial)
+}
+
+fn render_weighted_cell(tiles: &[u32], frequencies: &[u32], partial: PartialRender) -> u32 {
+    let mut superposition = Superposition::default();
+    superposition.fill(tiles.len());
+    let render_options = RenderOptions {
+        partial,
+        ..RenderOptions::default()
+    };
+    let image = wfc::superpositions_to_image(
+        &[superposition],
+        &[false],
+        tiles,
+        frequencies,
+        1,
+        1,
+        &render_options,
+    );
+    image.pixels()[0]
+}
+
+#[test]
+fn partial_cells_are_drawn_the_way_they_are_asked_for() {
+    const OPAQUE_BLUE: u32 = 0xffff0000;
+    let tiles = [OPAQUE_RED, OPAQUE_BLUE];
+    let frequencies = [1, 3];
+    assert_eq!(
+        render_weighted_cell(&tiles, &frequencies, PartialRender::Average),
+        0xff800080
+    );
+    assert_eq!(
+        render_weighted_cell(&tiles, &frequencies, PartialRender::MostLikely),
+        OPAQUE_BLUE
+    );
+    //Ties go to the tile with the lowest id
+    assert_eq!(
+        render_weighted_cell(&tiles, &[2, 2], PartialRender::MostLikely),
+        OPAQUE_RED
+    );
+    assert_eq!(
+        render_weighted_cell(&tiles, &frequencies, PartialRender::Blank),
+        0
+    );
+}
+
+#[test]
+fn averages_are_rounded_to_the_nearest_value() {
+    //Red averages to 1/3, green to 2/3 and blue to 254 2/3, rounding down
+    //would give 0xfffe0000
+    let tiles = [0xffff0000, 0xffff0100, 0xfffe0101];
+    assert_eq!(render_cell(&tiles, PartialRender::Average), 0xffff0100);
+}
+
+#[test]
+fn premultiplied_average_ignores_transparent_colors() {
+    let color = render_cell(
+        &[OPAQUE_RED, TRANSPARENT_BLACK],
+        PartialRender::PremultipliedAverage,
+    );
+    assert_eq!(color & 0xffffff, OPAQUE_RED & 0xffffff);
+    assert_eq!(color >> 24, 0x80);
+
+    //The plain average mixes in the black of the transparent tile
+    let color = render_cell(&[OPAQUE_RED, TRANSPARENT_BLACK], PartialRender::Average);
+    assert_eq!(color, 0xff000080);
+}
+
+#[test]
+fn premultiplied_average_of_transparent_tiles_is_transparent() {
+    let color = render_cell(
+        &[TRANSPARENT_BLACK, 0x00ffffff],
+        PartialRender::PremultipliedAverage,
+    );
+    assert_eq!(color, 0);
+}
+
+#[test]
+fn ids_that_are_not_tiles_get_the_invalid_color() {
+    const INVALID: u32 = 0xff00ffff;
+    let render_options = RenderOptions {
+        invalid_color: INVALID,
+        ..RenderOptions::default()
+    };
+    let tiles = [OPAQUE_RED, TRANSPARENT_BLACK];
+    //Tile 70 is past the first word of the bitset
+    let mut superpositions = vec![Superposition::default(); 3];
+    for (superposition, tile) in superpositions.iter_mut().zip([1, 2, 70]) {
+        superposition.fill(tile + 1);
+        superposition.collapse(tile);
+    }
+    //A cell that could be either a tile or an invalid id
+    superpositions[0].fill(3);
+    let image = wfc::superpositions_to_image(
+        &superpositions,
+        &[false; 3],
+        &tiles,
+        &[1; 2],
+        3,
+        1,
+        &render_options,
+    );
+    assert_eq!(image.pixels(), &[INVALID, INVALID, INVALID]);
+
+    //Valid ids are still drawn with their color
+    let mut valid = Superposition::default();
+    valid.fill(2);
+    valid.collapse(0);
+    let image =
+        wfc::superpositions_to_image(&[valid], &[false], &tiles, &[1; 2], 1, 1, &render_options);
+    assert_eq!(image.pixels(), &[OPAQUE_RED]);
+}
+
+#[test]
+fn emptied_cells_get_the_contradiction_color() {
+    const MAGENTA: u32 = 0xffff00ff;
+    let render_options = RenderOptions {
+        contradiction_color: MAGENTA,
+        ..RenderOptions::default()
+    };
+    let tiles = [OPAQUE_RED, TRANSPARENT_BLACK];
+    let mut superpositions = vec![Superposition::default(); 3];
+    for superposition in &mut superpositions {
+        superposition.fill(tiles.len());
+        superposition.collapse(0);
+    }
+    //The middle cell ran out of tiles and the last one is masked out
+    superpositions[1].clear();
+    superpositions[2].clear();
+    let mut grid = [0; 3];
+    wfc::copy_superpositions_to_grid(
+        &mut grid,
+        &superpositions,
+        &[false, false, true],
+        (3, 1),
+        &tiles,
+        &[1; 2],
+        &render_options,
+    );
+    assert_eq!(grid, [OPAQUE_RED, MAGENTA, 0]);
+}
+
+#[test]
+fn non_square_grids_are_stored_row_by_row() {
+    let (w, h) = (3, 2);
+    let tiles: Vec<u32> = (0..(w * h) as u32).map(|i| 0xff000000 | i).collect();
+    //Cell (x, y) is collapsed to tile y * w + x
+    let superpositions: Vec<Superposition> = (0..w * h)
+        .map(|tile| {
+            let mut superposition = Superposition::default();
+            superposition.fill(tiles.len());
+            superposition.collapse(tile);
+            superposition
+        })
+        .collect();
+    let image = wfc::superpositions_to_image(
+        &superpositions,
+        &vec![false; w * h],
+        &tiles,
+        &vec![1; tiles.len()],
+        w,
+        h,
+        &RenderOptions::default(),
+    );
+    assert_eq!((image.width(), image.height()), (w, h));
+    for y in 0..h {
+        for x in 0..w {
+            assert_eq!(image.get_pixel(x, y), tiles[y * w + x], "({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn the_image_matches_the_grid_written_in_place() {
+    const OPAQUE_BLUE: u32 = 0xffff0000;
+    let (w, h) = (2, 2);
+    let tiles = [OPAQUE_RED, OPAQUE_BLUE, TRANSPARENT_BLACK];
+    let frequencies = [1, 2, 3];
+    let mut superpositions = vec![Superposition::default(); w * h];
+    for superposition in &mut superpositions {
+        superposition.fill(tiles.len());
+    }
+    //Two collapsed cells and a cell that is only partly narrowed down
+    superpositions[0].collapse(1);
+    superpositions[3].collapse(0);
+    superpositions[1].retain(|tile| tile != 2);
+    let masked = vec![false; w * h];
+    for partial in [
+        PartialRender::Average,
+        PartialRender::PremultipliedAverage,
+        PartialRender::MostLikely,
+        PartialRender::Blank,
+    ] {
+        let render_options = RenderOptions {
+            partial,
+            ..RenderOptions::default()
+        };
+        let mut grid = vec![0; w * h];
+        wfc::copy_superpositions_to_grid(
+            &mut grid,
+            &superpositions,
+            &masked,
+            (w, h),
+            &tiles,
+            &frequencies,
+            &render_options,
+        );
+        let image = wfc::superpositions_to_image(
+            &superpositions,
+            &masked,
+            &tiles,
+            &frequencies,
+            w,
+            h,
+            &render_options,
+        );
+        assert_eq!((image.width(), image.height()), (w, h));
+        assert_eq!(image.pixels(), &grid[..], "{partial:?}");
+        assert_eq!((grid[0], grid[3]), (OPAQUE_BLUE, OPAQUE_RED), "{partial:?}");
+    }
+}
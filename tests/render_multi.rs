@@ -0,0 +1,43 @@
+//Checks rendering one output at several scales
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const SIZE: (usize, usize) = (12, 7);
+const SEED: u64 = 7;
+const SCALES: [usize; 3] = [1, 8, 32];
+
+#[test]
+fn every_scale_has_the_same_structure() {
+    let wfc_parameters = load_input("inputimage3.png");
+    let (w, h) = SIZE;
+    let base = wfc_parameters.generate_grid_seeded(w, h, SEED).unwrap();
+
+    let images = base.render_multi(&SCALES);
+    assert_eq!(images.len(), SCALES.len());
+    for (image, scale) in images.iter().zip(SCALES) {
+        assert_eq!((image.width(), image.height()), (w * scale, h * scale));
+        //Every pixel of the base is a scale x scale block of its color
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                assert_eq!(
+                    image.get_pixel(x, y),
+                    base.get_pixel(x / scale, y / scale),
+                    "scale {scale} at ({x}, {y})"
+                );
+            }
+        }
+    }
+
+    assert_eq!(base.render_multi(&[1])[0].pixels(), base.pixels());
+    assert_eq!(base.render_multi(&[0])[0].pixels(), base.pixels());
+}
@@ -0,0 +1,40 @@
+//Checks scaling the solved grid up to the size the output is saved at
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 32;
+const RENDER_SIZE: usize = 256;
+const BLOCK: usize = RENDER_SIZE / SIZE;
+
+#[test]
+fn a_32x32_solve_rendered_at_256x256_has_8x8_blocks() {
+    let parameters = load_input(INPUT);
+    let (grid, _) = parameters
+        .generate_grid_until_success(SIZE, SIZE, 0, 64)
+        .unwrap();
+    let scaled = grid.scale_nearest(RENDER_SIZE, RENDER_SIZE);
+    assert_eq!(
+        (scaled.width(), scaled.height()),
+        (RENDER_SIZE, RENDER_SIZE)
+    );
+    for y in 0..RENDER_SIZE {
+        for x in 0..RENDER_SIZE {
+            assert_eq!(
+                scaled.get_pixel(x, y),
+                grid.get_pixel(x / BLOCK, y / BLOCK),
+                "({x}, {y})"
+            );
+        }
+    }
+}
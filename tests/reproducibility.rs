@@ -0,0 +1,60 @@
+//Checks that a seed gives exactly the same output as the golden images in
+//tests/golden, run with UPDATE_GOLDEN=1 to write them again after a change
+//that is meant to change the output.
+//
+//The output of a seed only depends on the input, the tile size, the output
+//size and the seed: tiles are numbered in the order they are found in the
+//input, entropies are rounded before they are compared, cells with the same
+//entropy are ordered by noise drawn from the seeded random number generator
+//and the generator is always drawn from in the same order. It is the same
+//on every platform but may change between versions of rand.
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input_sized;
+use image_data::ImageData;
+
+//(input image, tile size, output size, seed)
+const CASES: [(&str, isize, usize, u64); 2] =
+    [("inputimage3.png", 3, 16, 7), ("inputimage4.png", 3, 16, 7)];
+
+fn golden_path(input: &str, seed: u64) -> String {
+    let name = input.trim_end_matches(".png");
+    format!(
+        "{}/tests/golden/{name}_seed{seed}.png",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[test]
+fn seeded_output_matches_golden() {
+    for (input, tile_sz, size, seed) in CASES {
+        let wfc_parameters = load_input_sized(input, tile_sz);
+        let output = wfc_parameters
+            .generate_grid_seeded(size, size, seed)
+            .unwrap_or_else(|e| panic!("{input} with seed {seed}: {e}"));
+
+        let golden = golden_path(input, seed);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            output.save_png(&golden).unwrap();
+            continue;
+        }
+        let expected = ImageData::load_png(&golden).unwrap_or_else(|e| panic!("{golden}: {e}"));
+        assert_eq!(
+            (output.width(), output.height()),
+            (expected.width(), expected.height())
+        );
+        assert!(
+            output.pixels() == expected.pixels(),
+            "{input} with seed {seed} does not match {golden}"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//Checks that every way of recovering from a contradiction leaves the state
+//in a form the solver can continue from, even when the strategy is changed
+//in the middle of a generation
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use rand::{rngs::StdRng, SeedableRng};
+use wfc::{RetryStrategy, WFCState};
+
+//brick.png often runs into contradictions at this size
+const INPUT: &str = "brick.png";
+const SIZE: (usize, usize) = (12, 10);
+const SEEDS: u64 = 8;
+const MAX_STEPS: usize = 20000;
+const BACKTRACK_DEPTH: usize = 8;
+
+#[test]
+fn every_strategy_is_reachable() {
+    let mut strategy = RetryStrategy::default();
+    assert_eq!(strategy, RetryStrategy::Restart);
+    let mut seen = vec![];
+    for _ in 0..RetryStrategy::ALL.len() {
+        seen.push(strategy);
+        strategy = strategy.next();
+    }
+    assert_eq!(seen, RetryStrategy::ALL);
+    assert_eq!(strategy, RetryStrategy::default());
+}
+
+#[test]
+fn switching_strategies_keeps_the_state_consistent() {
+    let (w, h) = SIZE;
+    let mut contradictions = 0;
+    for seed in 0..SEEDS {
+        let mut wfc_parameters = load_input(INPUT);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut wfc_state = WFCState::new(
+            w,
+            h,
+            &wfc_parameters.wfc_tiles,
+            &wfc_parameters.wfc_frequency,
+            &mut rng,
+        );
+        let mut history = vec![];
+        //Every contradiction is handled by the next strategy
+        let mut strategy = RetryStrategy::ALL[seed as usize % RetryStrategy::ALL.len()];
+
+        let mut steps = 0;
+        while !wfc_state.done() {
+            steps += 1;
+            assert!(steps < MAX_STEPS, "seed {seed} did not finish");
+
+            history.push(wfc_state.clone());
+            if history.len() > BACKTRACK_DEPTH {
+                history.remove(0);
+            }
+            if wfc_parameters.step(w, h, &mut wfc_state, &mut rng).is_ok() {
+                continue;
+            }
+
+            contradictions += 1;
+            wfc_parameters.retry(strategy, w, h, &mut wfc_state, &mut history, &mut rng);
+            wfc_state
+                .check_invariants()
+                .unwrap_or_else(|e| panic!("seed {seed} after {strategy}: {e}"));
+            assert!(
+                wfc_state.superpositions().iter().all(|s| !s.is_empty()),
+                "seed {seed}: {strategy} left a contradiction"
+            );
+            strategy = strategy.next();
+        }
+        assert_eq!(wfc_state.remaining(), 0, "seed {seed}");
+    }
+    assert!(contradictions > 0, "no seed ran into a contradiction");
+}
+
+#[test]
+fn generating_until_success_tries_the_following_seeds() {
+    //inputimage3.png fails for some seeds but not others at this size
+    let wfc_parameters = load_input("inputimage3.png");
+    let (w, h) = (16, 16);
+    let failing = (0..64)
+        .find(|seed| wfc_parameters.generate_grid_seeded(w, h, *seed).is_err())
+        .expect("a seed that fails");
+    let (image, attempts) = wfc_parameters
+        .generate_grid_until_success(w, h, failing, 64)
+        .unwrap();
+    assert!(attempts > 1);
+    let seed = failing + attempts as u64 - 1;
+    assert_eq!(
+        image.pixels(),
+        wfc_parameters
+            .generate_grid_seeded(w, h, seed)
+            .unwrap()
+            .pixels()
+    );
+
+    assert!(wfc_parameters
+        .generate_grid_until_success(w, h, failing, 1)
+        .is_err());
+    //brick.png always runs into a contradiction at SIZE
+    let (w, h) = SIZE;
+    let Err(error) = load_input(INPUT).generate_grid_until_success(w, h, 0, 3) else {
+        panic!("{INPUT} was generated at {w}x{h}");
+    };
+    assert!(error.contains("3 attempts"), "{error}");
+}
@@ -0,0 +1,72 @@
+//Checks that the solver can be driven by any random number generator, here
+//one that always returns 0 so that every choice it makes is known
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use rand::RngCore;
+use wfc::{WFCParameters, WFCState};
+
+const TILES: usize = 3;
+const SIZE: (usize, usize) = (5, 3);
+
+struct ZeroRng;
+
+impl RngCore for ZeroRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+#[test]
+fn a_stub_rng_picks_the_first_option_every_time() {
+    let parameters = WFCParameters::from_parts(
+        (0..TILES as u32).collect(),
+        all_allowed(TILES, &OFFSETS),
+        vec![1; TILES],
+        1,
+    );
+    let (w, h) = SIZE;
+    let mut rng = ZeroRng;
+    let mut wfc_state = WFCState::new(
+        w,
+        h,
+        &parameters.wfc_tiles,
+        &parameters.wfc_frequency,
+        &mut rng,
+    );
+    //The first cell is collapsed first since it is the first one picked
+    parameters.step(w, h, &mut wfc_state, &mut rng).unwrap();
+    let collapsed: Vec<usize> = (0..w * h)
+        .filter(|i| wfc_state.superpositions()[*i].len() == 1)
+        .collect();
+    assert_eq!(collapsed, vec![0]);
+
+    while !wfc_state.done() {
+        parameters.step(w, h, &mut wfc_state, &mut rng).unwrap();
+    }
+    for superposition in wfc_state.superpositions() {
+        assert_eq!(superposition.iter().collect::<Vec<_>>(), vec![0]);
+    }
+}
@@ -0,0 +1,53 @@
+//Checks that a reduced rotation weight favors the orientation of the input
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::{ExtractionOptions, WFCParameters};
+
+const RED: u32 = 0xff0000ff;
+const BLUE: u32 = 0xffff0000;
+const SIZE: usize = 8;
+const SEEDS: u64 = 64;
+
+//Horizontal stripes one pixel high, rotated they become vertical stripes
+//which can't be next to the horizontal ones
+fn stripes() -> ImageData {
+    let pixels: Vec<u32> = (0..16)
+        .map(|i| if i / 4 % 2 == 0 { RED } else { BLUE })
+        .collect();
+    ImageData::from_pixels(&pixels, 4, 4)
+}
+
+//How many seeds give horizontal stripes, the rest give vertical ones
+fn horizontal_outputs(rotation_weight: u32) -> u64 {
+    let options = ExtractionOptions {
+        rotations: true,
+        rotation_weight,
+        ..ExtractionOptions::default()
+    };
+    let parameters = WFCParameters::from_image_data_with_options(&stripes(), 2, &options).unwrap();
+    (0..SEEDS)
+        .filter(|seed| {
+            let output = parameters.generate_grid_seeded(SIZE, SIZE, *seed).unwrap();
+            output.pixels()[0] == output.pixels()[1]
+        })
+        .count() as u64
+}
+
+#[test]
+fn the_original_orientation_is_favored() {
+    //The original tiles weigh 2000 and the rotated ones 800
+    let favored = horizontal_outputs(25);
+    assert!(favored > SEEDS * 5 / 8, "{favored} of {SEEDS}");
+
+    //At full weight both orientations are as likely
+    let even = horizontal_outputs(100);
+    assert!(favored > even, "{favored} and {even} of {SEEDS}");
+}
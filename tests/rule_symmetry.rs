@@ -0,0 +1,61 @@
+//Checks finding and repairing rules that only allow a pair one way around
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, load_input, OFFSETS};
+use wfc::{RuleTable, SymmetryPolicy};
+
+const RIGHT: usize = 1;
+const LEFT: usize = 3;
+const TILES: usize = 3;
+
+//Tile 1 is allowed to the right of tile 0 but tile 0 is not allowed to the
+//left of tile 1, and the same for tile 2 above tile 1
+fn asymmetric() -> RuleTable {
+    let mut rules = all_allowed(TILES, &OFFSETS);
+    rules.remove_rule(LEFT, 1, 0);
+    rules.remove_rule(2, 2, 1);
+    rules
+}
+
+#[test]
+fn extracted_rules_are_symmetric() {
+    let parameters = load_input("inputimage4.png");
+    assert!(parameters.wfc_rules.check_symmetry().is_empty());
+}
+
+#[test]
+fn asymmetric_rules_are_found() {
+    let rules = asymmetric();
+    let mut found = rules.check_symmetry();
+    found.sort();
+    assert_eq!(found, vec![(0, 1, 2), (RIGHT, 0, 1)]);
+}
+
+#[test]
+fn and_removes_the_rules_that_are_only_one_way() {
+    let mut rules = asymmetric();
+    assert_eq!(rules.make_symmetric(SymmetryPolicy::And), 2);
+    assert!(rules.check_symmetry().is_empty());
+    assert!(!rules.okay(RIGHT, 0, 1) && !rules.okay(LEFT, 1, 0));
+    assert!(!rules.okay(0, 1, 2) && !rules.okay(2, 2, 1));
+    //The rest of the rules are left alone
+    assert!(rules.okay(RIGHT, 1, 0) && rules.okay(LEFT, 0, 1));
+    assert_eq!(rules.make_symmetric(SymmetryPolicy::And), 0);
+}
+
+#[test]
+fn or_adds_the_missing_rules() {
+    let mut rules = asymmetric();
+    assert_eq!(rules.make_symmetric(SymmetryPolicy::Or), 2);
+    assert!(rules.check_symmetry().is_empty());
+    assert!(rules == all_allowed(TILES, &OFFSETS));
+}
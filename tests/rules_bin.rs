@@ -0,0 +1,100 @@
+//Checks saving and loading the tiles and rules in the binary format
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::WFCParameters;
+
+fn assert_same_rules(loaded: &WFCParameters, original: &WFCParameters, input: &str) {
+    assert_eq!(loaded.wfc_tiles, original.wfc_tiles, "{input}");
+    assert_eq!(loaded.wfc_frequency, original.wfc_frequency, "{input}");
+    assert_eq!(loaded.wfc_tile_sz, original.wfc_tile_sz, "{input}");
+    assert!(loaded.wfc_rules == original.wfc_rules, "{input}");
+}
+
+#[test]
+fn rules_survive_a_round_trip() {
+    //inputimage1.png has more than 64 tiles so the bitsets take more than
+    //one word
+    for input in ["inputimage1.png", "inputimage3.png", "brick.png"] {
+        let original = load_input(input);
+        let loaded = WFCParameters::from_rules_bin(&original.to_rules_bin()).unwrap();
+        assert_same_rules(&loaded, &original, input);
+    }
+
+    let mut weighted = load_input("inputimage4.png");
+    weighted.discourage(1, 0, 0, 0.25);
+    let loaded = WFCParameters::from_rules_bin(&weighted.to_rules_bin()).unwrap();
+    assert!(loaded.wfc_rules.has_weights());
+    assert_same_rules(&loaded, &weighted, "weighted");
+}
+
+#[test]
+fn rules_survive_a_round_trip_through_a_file() {
+    let original = load_input("inputimage4.png");
+    let path = std::env::temp_dir().join(format!("rules_bin_{}.bin", std::process::id()));
+    let path = path.to_str().unwrap();
+    original.save_rules_bin(path).unwrap();
+    let loaded = WFCParameters::load_rules_bin(path);
+    std::fs::remove_file(path).unwrap();
+    assert_same_rules(&loaded.unwrap(), &original, path);
+}
+
+#[test]
+fn truncated_files_fail() {
+    let bytes = load_input("inputimage3.png").to_rules_bin();
+    for len in 0..bytes.len() {
+        assert!(
+            WFCParameters::from_rules_bin(&bytes[..len]).is_err(),
+            "{len} of {} bytes loaded",
+            bytes.len()
+        );
+    }
+
+    let mut extra = bytes.clone();
+    extra.push(0);
+    assert!(WFCParameters::from_rules_bin(&extra).is_err());
+    let mut wrong_version = bytes;
+    wrong_version[4] += 1;
+    let msg = WFCParameters::from_rules_bin(&wrong_version).err().unwrap();
+    assert!(msg.contains("version"), "{msg}");
+}
+
+#[test]
+fn huge_counts_and_stray_bits_fail() {
+    let original = load_input("inputimage3.png");
+    let bytes = original.to_rules_bin();
+    let count = original.wfc_tiles.len();
+    let directions = original.wfc_rules.directions();
+    assert!(!count.is_multiple_of(64));
+
+    //A file with the tiles for a large tile count but none of its bitsets
+    let huge_count: u32 = 4096;
+    let mut huge = bytes[..12].to_vec();
+    //One direction with the offset (1, 0)
+    for value in [huge_count, 1, 1, 0] {
+        huge.extend_from_slice(&value.to_le_bytes());
+    }
+    huge.extend(std::iter::repeat_n(0, 8 * huge_count as usize + 1));
+    let msg = WFCParameters::from_rules_bin(&huge).err().unwrap();
+    assert!(msg.contains("truncated"), "{msg}");
+
+    //The bitsets start after the header, the offsets, the tiles, the
+    //frequencies and the weight flag, set the bit just past the last tile in
+    //the last bitset
+    let start = 20 + 8 * directions + 8 * count + 1;
+    let words = count.div_ceil(64);
+    let last_word = start + 8 * (count * directions * words - 1);
+    let mut stray = bytes;
+    stray[last_word + count % 64 / 8] |= 1 << (count % 8);
+    let msg = WFCParameters::from_rules_bin(&stray).err().unwrap();
+    assert!(msg.contains("past the last tile"), "{msg}");
+}
@@ -0,0 +1,80 @@
+//Checks picking several different elements at random
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashSet;
+use wfc::sample_without_replacement;
+
+const TRIALS: usize = 10000;
+const SIZE: usize = 24;
+const SEEDS: u64 = 4;
+const MAX_ATTEMPTS: u32 = 64;
+
+#[test]
+fn picks_k_different_elements() {
+    let candidates: Vec<usize> = (10..30).collect();
+    let weights: Vec<u32> = (1..=20).collect();
+    let mut rng = StdRng::seed_from_u64(0);
+    for k in 0..=candidates.len() {
+        for weights in [None, Some(weights.as_slice())] {
+            let picked = sample_without_replacement(&candidates, k, weights, &mut rng);
+            assert_eq!(picked.len(), k);
+            assert_eq!(picked.iter().collect::<HashSet<_>>().len(), k);
+            assert!(picked.iter().all(|element| candidates.contains(element)));
+        }
+    }
+}
+
+#[test]
+fn picks_at_most_every_element() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut picked = sample_without_replacement(&[1, 2, 3], 10, Some(&[0, 5, 1]), &mut rng);
+    picked.sort();
+    assert_eq!(picked, vec![1, 2, 3]);
+    assert!(sample_without_replacement::<u32, _>(&[], 2, None, &mut rng).is_empty());
+}
+
+#[test]
+fn heavier_elements_are_picked_first_more_often() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut first = [0; 3];
+    let mut heaviest_picked = 0;
+    for _ in 0..TRIALS {
+        let picked = sample_without_replacement(&[0, 1, 2], 2, Some(&[1, 2, 7]), &mut rng);
+        first[picked[0]] += 1;
+        if picked.contains(&2) {
+            heaviest_picked += 1;
+        }
+    }
+    //The first pick has the same odds as a single weighted pick
+    let expected = [0.1, 0.2, 0.7];
+    for (count, expected) in first.iter().zip(expected) {
+        let fraction = *count as f32 / TRIALS as f32;
+        assert!((fraction - expected).abs() < 0.02, "{first:?}");
+    }
+    //The second pick only chooses from the elements that are left, so the
+    //heaviest element is almost always one of the two
+    assert!(heaviest_picked > TRIALS * 9 / 10, "{heaviest_picked}");
+}
+
+#[test]
+fn several_collapses_per_step_follow_the_rules() {
+    let mut wfc_parameters = load_input("inputimage1.png");
+    wfc_parameters.wfc_collapses_per_step = 4;
+    for seed in 0..SEEDS {
+        let (image, _) = wfc_parameters
+            .generate_grid_until_success(SIZE, SIZE, seed, MAX_ATTEMPTS)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+        assert_eq!(wfc_parameters.validate_image(&image), vec![], "seed {seed}");
+    }
+}
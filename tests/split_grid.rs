@@ -0,0 +1,58 @@
+//Checks cutting a sprite sheet into its tiles
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::ImageData;
+
+//A 2x2 sheet of 2x2 tiles where every pixel is 0xff0000TP, T is the tile
+//and P the pixel in the tile
+fn sheet(margin: usize, spacing: usize) -> ImageData {
+    let size = 2 * margin + 4 + spacing;
+    let mut sheet = ImageData::new(size, size);
+    for tile in 0..4 {
+        for pixel in 0..4 {
+            let x = margin + (tile % 2) * (2 + spacing) + pixel % 2;
+            let y = margin + (tile / 2) * (2 + spacing) + pixel / 2;
+            sheet.pixels_mut()[x + y * size] = 0xff000000 | (tile << 4 | pixel) as u32;
+        }
+    }
+    sheet
+}
+
+fn check_tiles(tiles: &[ImageData]) {
+    assert_eq!(tiles.len(), 4);
+    for (tile, image) in tiles.iter().enumerate() {
+        assert_eq!((image.width(), image.height()), (2, 2));
+        let expected: Vec<u32> = (0..4)
+            .map(|pixel| 0xff000000 | (tile << 4 | pixel) as u32)
+            .collect();
+        assert_eq!(image.pixels(), expected.as_slice(), "tile {tile}");
+    }
+}
+
+#[test]
+fn a_sheet_is_split_row_by_row() {
+    check_tiles(&sheet(0, 0).split_grid(2, 2, 2, 2).unwrap());
+}
+
+#[test]
+fn margins_and_spacing_are_skipped() {
+    let tiles = sheet(3, 1)
+        .split_grid_spaced(2, 2, 2, 2, (3, 3), (1, 1))
+        .unwrap();
+    check_tiles(&tiles);
+}
+
+#[test]
+fn tiles_outside_of_the_sheet_are_an_error() {
+    let Err(error) = sheet(0, 0).split_grid(2, 2, 3, 2) else {
+        panic!("3x2 tiles fit in a 4x4 sheet");
+    };
+    assert!(error.contains("6x4"), "{error}");
+    //The margin pushes the second column past the edge of the 6x6 sheet
+    assert!(sheet(1, 0)
+        .split_grid_spaced(2, 2, 2, 2, (2, 2), (1, 0))
+        .is_err());
+    assert!(sheet(0, 0).split_grid(0, 2, 2, 2).is_err());
+}
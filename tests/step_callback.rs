@@ -0,0 +1,50 @@
+//Checks the callback that is called after each collapse
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use wfc::Superposition;
+
+const INPUT: &str = "inputimage4.png";
+const SIZE: usize = 12;
+const ATTEMPTS: usize = 8;
+
+#[test]
+fn the_callback_sees_the_count_shrink() {
+    let parameters = load_input(INPUT);
+    for _ in 0..ATTEMPTS {
+        let mut counts = vec![];
+        let mut on_step = |superpositions: &[Superposition], remaining: usize| {
+            let counted = superpositions
+                .iter()
+                .filter(|superposition| superposition.len() > 1)
+                .count();
+            assert_eq!(counted, remaining);
+            counts.push(remaining);
+        };
+        if parameters
+            .generate_grid_streaming(SIZE, SIZE, &mut on_step)
+            .is_err()
+        {
+            continue;
+        }
+
+        //Every call is a collapse that left fewer tiles to collapse
+        assert!(!counts.is_empty() && counts.len() <= SIZE * SIZE);
+        assert!(
+            counts.windows(2).all(|pair| pair[1] < pair[0]),
+            "{counts:?}"
+        );
+        assert_eq!(counts.last(), Some(&0));
+        return;
+    }
+    panic!("no generation finished in {ATTEMPTS} attempts");
+}
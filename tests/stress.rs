@@ -0,0 +1,72 @@
+//Checks that the stress test tells easy rules from ones that always fail
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use image_data::ImageData;
+use wfc::WFCParameters;
+
+const RUNS: u32 = 16;
+
+#[test]
+fn a_single_color_never_fails() {
+    let data = ImageData::from_pixels(&[0xff102030; 16], 4, 4);
+    let wfc_parameters = WFCParameters::from_image_data(&data, 3).unwrap();
+    let (w, h) = (16, 16);
+    let report = wfc_parameters.stress_test(w, h, &vec![false; w * h], 0, RUNS);
+    assert_eq!(report.runs, RUNS);
+    assert_eq!(report.failures, 0);
+    assert_eq!(report.failure_rate(), 0.0);
+    //Every cell starts with the only tile so there is nothing to collapse
+    assert_eq!(report.worst_steps, 0);
+    assert_eq!(report.average_steps, 0.0);
+}
+
+#[test]
+fn over_constrained_rules_fail_often() {
+    //The bricks can't fit into a 12x10 output that wraps around
+    let wfc_parameters = load_input("brick.png");
+    let (w, h) = (12, 10);
+    let report = wfc_parameters.stress_test(w, h, &vec![false; w * h], 0, RUNS);
+    assert!(report.failure_rate() > 0.9, "{report:?}");
+}
+
+#[test]
+fn steps_are_counted_for_the_runs_that_finish() {
+    let wfc_parameters = load_input("inputimage3.png");
+    let (w, h) = (16, 16);
+    let report = wfc_parameters.stress_test(w, h, &vec![false; w * h], 0, RUNS);
+    assert!(report.failures < RUNS, "{report:?}");
+    assert!(report.average_steps >= 1.0, "{report:?}");
+    assert!(
+        report.worst_steps as f32 >= report.average_steps,
+        "{report:?}"
+    );
+    assert!(report.worst_steps <= w * h, "{report:?}");
+}
+
+#[test]
+fn preferring_constrained_cells_changes_the_contradiction_rate() {
+    //inputimage1.png is one of the inputs the tie-break helps, it doesn't
+    //help every input (see PREFER_CONSTRAINED in main.rs)
+    let mut wfc_parameters = load_input("inputimage1.png");
+    let (w, h) = (16, 16);
+    let runs = 50;
+    let baseline = wfc_parameters.stress_test(w, h, &vec![false; w * h], 0, runs);
+    wfc_parameters.wfc_prefer_constrained = true;
+    let constrained = wfc_parameters.stress_test(w, h, &vec![false; w * h], 0, runs);
+    assert!(
+        constrained.failures < baseline.failures,
+        "{} contradictions with the tie-break, {} without",
+        constrained.failures,
+        baseline.failures
+    );
+}
@@ -0,0 +1,93 @@
+//Checks the bitsets that store the tiles of each cell against the list of
+//tile ids they replaced, every change to a cell goes through these methods
+//so a seed gives the same output with either of them
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wfc::{Superposition, WFCState};
+
+const TILE_COUNTS: [usize; 5] = [1, 5, 64, 65, 200];
+const OPERATIONS: usize = 200;
+const SEEDS: u64 = 8;
+//Size of a large output with a lot of tiles
+const LARGE: (usize, usize) = (128, 128);
+const LARGE_TILES: usize = 500;
+
+fn assert_same(superposition: &Superposition, ids: &[usize], tiles: usize) {
+    assert_eq!(superposition.iter().collect::<Vec<_>>(), ids);
+    assert_eq!(superposition.len(), ids.len());
+    assert_eq!(superposition.is_empty(), ids.is_empty());
+    assert_eq!(superposition.first(), ids.first().copied());
+    assert_eq!(superposition.last(), ids.last().copied());
+    for tile in 0..tiles + 64 {
+        assert_eq!(superposition.contains(tile), ids.contains(&tile), "{tile}");
+    }
+}
+
+#[test]
+fn bitsets_match_lists_of_ids() {
+    for tiles in TILE_COUNTS {
+        for seed in 0..SEEDS {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut superposition = Superposition::default();
+            let mut ids: Vec<usize> = vec![];
+            for _ in 0..OPERATIONS {
+                match rng.gen_range(0..5) {
+                    //Cells are only collapsed into one of their tiles
+                    2 if !ids.is_empty() => {
+                        let tile = ids[rng.gen_range(0..ids.len())];
+                        superposition.collapse(tile);
+                        ids = vec![tile];
+                    }
+                    0 => {
+                        superposition.fill(tiles);
+                        ids = (0..tiles).collect();
+                    }
+                    1 | 2 => {
+                        superposition.clear();
+                        ids.clear();
+                    }
+                    3 => {
+                        let modulus = rng.gen_range(1..5);
+                        superposition.retain(|tile| tile % modulus != 0);
+                        ids.retain(|tile| tile % modulus != 0);
+                    }
+                    _ => {
+                        let allowed: Vec<u64> =
+                            (0..tiles.div_ceil(64)).map(|_| rng.gen()).collect();
+                        superposition.intersect(&allowed);
+                        ids.retain(|tile| allowed[tile / 64] & (1 << (tile % 64)) != 0);
+                    }
+                }
+                assert_same(&superposition, &ids, tiles);
+            }
+        }
+    }
+}
+
+#[test]
+fn bitsets_take_a_bit_per_tile() {
+    let (w, h) = LARGE;
+    let tiles: Vec<u32> = (0..LARGE_TILES as u32).collect();
+    let wfc_state = WFCState::new(
+        w,
+        h,
+        &tiles,
+        &vec![1; LARGE_TILES],
+        &mut StdRng::seed_from_u64(0),
+    );
+    let bitset_bytes = LARGE_TILES.div_ceil(64) * std::mem::size_of::<u64>();
+    let cell_bytes = std::mem::size_of::<Superposition>() + bitset_bytes;
+    assert_eq!(wfc_state.superposition_bytes(), w * h * cell_bytes);
+    //A list of every tile id in every cell
+    let ids_bytes =
+        w * h * (std::mem::size_of::<Vec<usize>>() + LARGE_TILES * std::mem::size_of::<usize>());
+    assert!(wfc_state.superposition_bytes() * 40 < ids_bytes);
+}
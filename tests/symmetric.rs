@@ -0,0 +1,106 @@
+//Checks that symmetric outputs are mirrored and that the halves only meet
+//at the seams
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::OFFSETS;
+use image_data::ImageData;
+use wfc::{Boundary, RuleTable, WFCParameters};
+
+const COLORS: [u32; 3] = [0xff0000ff, 0xff00ff00, 0xffff0000];
+//Only the last tile can be next to itself horizontally
+const SELF_ADJACENT: usize = 2;
+const SIZE: (usize, usize) = (6, 4);
+const SEEDS: u64 = 32;
+
+//Horizontally 0 and 1 can be next to each other, so can 1 and 2 and 2 and
+//itself, both ways around so that a mirrored row follows the same rules.
+//Vertically each tile is only next to itself
+fn mirrorable(boundary: Boundary) -> WFCParameters {
+    let mut rules = RuleTable::new(COLORS.len(), &OFFSETS).unwrap();
+    for (direction, (dx, _)) in OFFSETS.iter().enumerate() {
+        for id1 in 0..COLORS.len() {
+            for id2 in 0..COLORS.len() {
+                let allowed = if *dx == 0 {
+                    id1 == id2
+                } else {
+                    id1.abs_diff(id2) == 1 || id1 == SELF_ADJACENT && id2 == SELF_ADJACENT
+                };
+                if allowed {
+                    rules.add_rule(direction, id1, id2);
+                }
+            }
+        }
+    }
+    WFCParameters {
+        wfc_boundary: boundary,
+        ..WFCParameters::from_parts(COLORS.to_vec(), rules, vec![1; COLORS.len()], 1)
+    }
+}
+
+fn id(image: &ImageData, x: usize, y: usize) -> usize {
+    COLORS
+        .iter()
+        .position(|color| *color == image.get_pixel(x, y))
+        .unwrap()
+}
+
+#[test]
+fn outputs_are_mirrored_and_follow_the_rules() {
+    let (w, h) = SIZE;
+    for boundary in [Boundary::Wrap, Boundary::Finite] {
+        let parameters = mirrorable(boundary);
+        for seed in 0..SEEDS {
+            let output = parameters
+                .generate_symmetric_grid_seeded(w, h, seed)
+                .unwrap();
+            for y in 0..h {
+                for x in 0..w {
+                    assert_eq!(output.get_pixel(x, y), output.get_pixel(w - 1 - x, y));
+                }
+                //The middle seam is a tile next to itself
+                assert_eq!(id(&output, w / 2 - 1, y), SELF_ADJACENT);
+            }
+            assert!(
+                parameters.validate_image(&output).is_empty(),
+                "{boundary:?} seed {seed}"
+            );
+        }
+    }
+}
+
+#[test]
+fn the_mirror_axis_does_not_wrap() {
+    let (w, h) = SIZE;
+    //Wrapping the output makes the outer seam a tile next to itself too
+    let wrapped = mirrorable(Boundary::Wrap);
+    for seed in 0..SEEDS {
+        let output = wrapped.generate_symmetric_grid_seeded(w, h, seed).unwrap();
+        assert_eq!(id(&output, 0, 0), SELF_ADJACENT);
+    }
+
+    //With a finite output the first column isn't next to the last column of
+    //the half, so it can be a tile that isn't allowed next to the seam
+    let finite = mirrorable(Boundary::Finite);
+    assert!((0..SEEDS).any(|seed| {
+        let output = finite.generate_symmetric_grid_seeded(w, h, seed).unwrap();
+        id(&output, 0, 0) == 0
+    }));
+}
+
+#[test]
+fn odd_widths_are_rejected() {
+    let error = mirrorable(Boundary::Wrap)
+        .generate_symmetric_grid_seeded(5, 4, 0)
+        .err()
+        .unwrap();
+    assert!(error.contains("even"), "{error}");
+}
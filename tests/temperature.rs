@@ -0,0 +1,107 @@
+//Checks that the temperature only changes which cell is collapsed next
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+use rand::{rngs::StdRng, SeedableRng};
+
+const INPUT: &str = "inputimage1.png";
+const SIZE: usize = 16;
+const SEEDS: u64 = 4;
+const MAX_ATTEMPTS: u32 = 64;
+const PICKS: usize = 1000;
+
+#[test]
+fn zero_temperature_picks_the_lowest_entropy() {
+    let entropies = [2.0, 0.5, 3.0, 0.75];
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..PICKS {
+        assert_eq!(wfc::pick_tempered(&entropies, 0.0, &mut rng), Some(1));
+    }
+    assert_eq!(wfc::pick_tempered(&[], 1.0, &mut rng), None);
+}
+
+#[test]
+fn high_temperature_picks_higher_entropies_sometimes() {
+    let entropies = [2.0, 0.5, 3.0, 0.75];
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut counts = [0; 4];
+    for _ in 0..PICKS {
+        counts[wfc::pick_tempered(&entropies, 10.0, &mut rng).unwrap()] += 1;
+    }
+    assert!(counts.iter().all(|count| *count > 0), "{counts:?}");
+    //The lowest entropy is still the most likely
+    assert!(counts[1] > counts[2], "{counts:?}");
+}
+
+#[test]
+fn zero_temperature_matches_the_default() {
+    let default = load_input(INPUT);
+    let mut cold = load_input(INPUT);
+    cold.wfc_temperature = 0.0;
+    for seed in 0..SEEDS {
+        let expected = default.generate_grid_seeded(SIZE, SIZE, seed);
+        let output = cold.generate_grid_seeded(SIZE, SIZE, seed);
+        match (expected, output) {
+            (Ok(expected), Ok(output)) => assert_eq!(expected.pixels(), output.pixels()),
+            (Err(_), Err(_)) => {}
+            _ => panic!("seed {seed}: only one of the outputs failed"),
+        }
+    }
+}
+
+#[test]
+fn high_temperature_outputs_follow_the_rules() {
+    let mut wfc_parameters = load_input(INPUT);
+    wfc_parameters.wfc_temperature = 5.0;
+    for seed in 0..SEEDS {
+        let (image, _) = wfc_parameters
+            .generate_grid_until_success(SIZE, SIZE, seed, MAX_ATTEMPTS)
+            .unwrap_or_else(|e| panic!("seed {seed}: {e}"));
+        assert_eq!(wfc_parameters.validate_image(&image), vec![], "seed {seed}");
+    }
+}
+
+#[test]
+fn nan_entropies_are_not_picked() {
+    let entropies = [f32::NAN, 2.0, -f32::NAN, 1.0];
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(wfc::pick_tempered(&entropies, 0.0, &mut rng), Some(3));
+    for _ in 0..PICKS {
+        let picked = wfc::pick_tempered(&entropies, 10.0, &mut rng).unwrap();
+        assert!(picked == 1 || picked == 3, "{picked}");
+    }
+
+    //With nothing but NaN every entropy is as good as the others
+    let entropies = [f32::NAN; 3];
+    let mut counts = [0; 3];
+    for _ in 0..PICKS {
+        counts[wfc::pick_tempered(&entropies, 1.0, &mut rng).unwrap()] += 1;
+    }
+    assert!(counts.iter().all(|count| *count > 0), "{counts:?}");
+}
+
+#[test]
+fn tiles_that_are_never_seen_still_collapse() {
+    //Frequencies of zero give every cell the same entropy of zero
+    let mut wfc_parameters = load_input(INPUT);
+    wfc_parameters
+        .wfc_frequency
+        .iter_mut()
+        .for_each(|frequency| *frequency = 0);
+    for temperature in [0.0, 1.0] {
+        wfc_parameters.wfc_temperature = temperature;
+        let (image, _) = wfc_parameters
+            .generate_grid_until_success(SIZE, SIZE, 0, MAX_ATTEMPTS)
+            .unwrap_or_else(|e| panic!("temperature {temperature}: {e}"));
+        assert_eq!(wfc_parameters.validate_image(&image), vec![]);
+    }
+}
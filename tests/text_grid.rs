@@ -0,0 +1,96 @@
+//Checks reading a text grid, where each character is a tile, as the input
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::{text_palette, ImageData, ImageError};
+use std::collections::HashMap;
+use wfc::WFCParameters;
+
+const WALL: u32 = 0xff000000;
+const FLOOR: u32 = 0xffffffff;
+const WATER: u32 = 0xffff0000;
+
+fn palette() -> HashMap<char, u32> {
+    HashMap::from([('#', WALL), ('.', FLOOR), ('~', WATER)])
+}
+
+#[test]
+fn a_3x3_grid_gives_the_expected_pixels() {
+    let image = ImageData::from_text_grid("#.#\n.~.\n#.#\n", &palette()).unwrap();
+    assert_eq!((image.width(), image.height()), (3, 3));
+    assert_eq!(
+        image.pixels(),
+        &[WALL, FLOOR, WALL, FLOOR, WATER, FLOOR, WALL, FLOOR, WALL]
+    );
+    //The grid is used like any other input
+    let parameters = WFCParameters::from_image_data(&image, 2).unwrap();
+    assert!(parameters.wfc_tiles.contains(&WATER));
+}
+
+#[test]
+fn windows_line_endings_are_accepted() {
+    let image = ImageData::from_text_grid("#.\r\n.#\r\n", &palette()).unwrap();
+    assert_eq!(image.pixels(), &[WALL, FLOOR, FLOOR, WALL]);
+}
+
+#[test]
+fn ragged_lines_are_rejected() {
+    let error = ImageData::from_text_grid("#.#\n.~\n#.#\n", &palette())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(
+            error,
+            ImageError::RaggedLine {
+                line: 2,
+                width: 2,
+                expected: 3
+            }
+        ),
+        "{error}"
+    );
+    assert!(error.to_string().contains("line 2"), "{error}");
+}
+
+#[test]
+fn unknown_characters_and_empty_grids_are_rejected() {
+    let error = ImageData::from_text_grid("#.#\n.x.\n", &palette())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(
+            error,
+            ImageError::UnknownCharacter {
+                character: 'x',
+                line: 2
+            }
+        ),
+        "{error}"
+    );
+    assert!(matches!(
+        ImageData::from_text_grid("\n\n", &palette()),
+        Err(ImageError::EmptyImage)
+    ));
+}
+
+#[test]
+fn characters_get_colors_in_the_order_they_appear() {
+    let palette = text_palette("ab\nba\ncc\n").unwrap();
+    assert_eq!(palette.len(), 3);
+    let image = ImageData::from_text_grid("ab\nba\ncc\n", &palette).unwrap();
+    let pixels = image.pixels();
+    assert_eq!((pixels[0], pixels[1]), (pixels[3], pixels[2]));
+    assert!(pixels[0] != pixels[1] && pixels[4] != pixels[0] && pixels[4] != pixels[1]);
+
+    let too_many: String = ('a'..='z').collect();
+    assert!(matches!(
+        text_palette(&too_many),
+        Err(ImageError::TooManyCharacters(13))
+    ));
+}
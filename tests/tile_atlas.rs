@@ -0,0 +1,66 @@
+//Checks the image that shows every tile sampled from an input
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use wfc::{ExtractionOptions, WFCParameters};
+
+const COLUMNS: usize = 16;
+
+fn load_input(input: &str, tile_sz: isize) -> WFCParameters {
+    let options = ExtractionOptions {
+        keep_patterns: true,
+        ..ExtractionOptions::default()
+    };
+    WFCParameters::from_image_data_with_options(&load_image(input), tile_sz, &options).unwrap()
+}
+
+#[test]
+fn the_atlas_fits_every_tile() {
+    for tile_sz in 1..=4 {
+        let wfc_parameters = load_input("inputimage1.png", tile_sz);
+        let tiles = wfc_parameters.wfc_tiles.len();
+        let atlas = wfc_parameters.tile_atlas(COLUMNS).unwrap();
+
+        //One pixel between the tiles
+        let columns = tiles.min(COLUMNS);
+        let rows = tiles.div_ceil(COLUMNS);
+        let tile_sz = tile_sz as usize;
+        assert_eq!(
+            atlas.width(),
+            columns * (tile_sz + 1) - 1,
+            "tile size {tile_sz}"
+        );
+        assert_eq!(
+            atlas.height(),
+            rows * (tile_sz + 1) - 1,
+            "tile size {tile_sz}"
+        );
+
+        let cut = atlas
+            .split_grid_spaced(tile_sz, tile_sz, columns, rows, (0, 0), (1, 1))
+            .unwrap_or_else(|e| panic!("tile size {tile_sz}: {e}"));
+        for (id, tile) in cut.iter().take(tiles).enumerate() {
+            let pattern = wfc_parameters.tile_pattern(id).unwrap();
+            assert_eq!(tile.pixels(), pattern.pixels(), "tile {id}");
+        }
+        //The rest of the last row is empty
+        assert!(cut[tiles..]
+            .iter()
+            .all(|tile| tile.pixels().iter().all(|pixel| *pixel == 0)));
+    }
+}
+
+#[test]
+fn the_atlas_needs_the_patterns() {
+    let wfc_parameters = common::load_input("inputimage1.png");
+    assert!(wfc_parameters.tile_atlas(COLUMNS).is_err());
+}
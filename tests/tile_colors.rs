@@ -0,0 +1,32 @@
+//Checks looking up tiles by the color they are drawn with
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use image_data::ImageData;
+use wfc::WFCParameters;
+
+const A: u32 = 0xff0000ff;
+const B: u32 = 0xffff0000;
+
+#[test]
+fn tiles_sharing_a_top_left_color_are_all_returned() {
+    //Every 2x2 tile of this image (wrapping around) is different, three of
+    //them start with A and one with B
+    let data = ImageData::from_pixels(&[A, A, A, B], 2, 2);
+    let wfc_parameters = WFCParameters::from_image_data(&data, 2).unwrap();
+    assert_eq!(wfc_parameters.wfc_tiles.len(), 4);
+
+    let with_a = wfc_parameters.tiles_with_color(A);
+    assert_eq!(with_a.len(), 3);
+    for id in &with_a {
+        assert_eq!(wfc_parameters.wfc_tiles[*id], A);
+    }
+    assert_eq!(wfc_parameters.tiles_with_color(B).len(), 1);
+    assert!(wfc_parameters.tiles_with_color(0).is_empty());
+}
@@ -0,0 +1,56 @@
+//Checks that the pattern of a tile id is the square of pixels it was sampled
+//from
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use wfc::{ExtractionOptions, Tile, WFCParameters};
+
+const INPUT: &str = "inputimage1.png";
+const TILE_SZ: isize = 3;
+
+#[test]
+fn patterns_match_the_sampled_pixels() {
+    let data = load_image(INPUT);
+    let options = ExtractionOptions {
+        keep_patterns: true,
+        ..ExtractionOptions::default()
+    };
+    let wfc_parameters =
+        WFCParameters::from_image_data_with_options(&data, TILE_SZ, &options).unwrap();
+
+    //Ids are given out in the order the tiles are first seen
+    let mut seen = Vec::<Tile>::new();
+    for y in 0..data.height() {
+        for x in 0..data.width() {
+            let tile = wfc::sample_square(&data, TILE_SZ, x as isize, y as isize, 0);
+            if !seen.contains(&tile) {
+                let pattern = wfc_parameters.tile_pattern(seen.len()).unwrap();
+                assert_eq!(pattern.width(), TILE_SZ as usize);
+                assert_eq!(pattern.height(), TILE_SZ as usize);
+                assert_eq!(pattern.pixels(), tile.as_slice(), "tile at ({x}, {y})");
+                assert_eq!(
+                    pattern.get_pixel(0, 0),
+                    wfc_parameters.wfc_tiles[seen.len()]
+                );
+                seen.push(tile);
+            }
+        }
+    }
+    assert_eq!(seen.len(), wfc_parameters.wfc_tiles.len());
+    assert!(wfc_parameters.tile_pattern(seen.len()).is_none());
+}
+
+#[test]
+fn patterns_are_not_kept_by_default() {
+    let wfc_parameters = WFCParameters::from_image_data(&load_image(INPUT), TILE_SZ).unwrap();
+    assert!(wfc_parameters.tile_pattern(0).is_none());
+}
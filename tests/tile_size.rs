@@ -0,0 +1,57 @@
+//Checks that extracting the rules again with another tile size (as the T key
+//in the window does) gives a different set of tiles
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use image_data::ImageData;
+use wfc::{ExtractionOptions, WFCParameters};
+
+#[test]
+fn changing_the_tile_size_changes_the_tile_count() {
+    for input in ["inputimage1.png", "inputimage3.png", "brick.png"] {
+        let data = load_image(input);
+        let counts: Vec<usize> = (1..=4)
+            .map(|tile_sz| {
+                WFCParameters::from_image_data(&data, tile_sz)
+                    .unwrap()
+                    .wfc_tiles
+                    .len()
+            })
+            .collect();
+        for pair in counts.windows(2) {
+            assert!(pair[0] < pair[1], "{input}: tile counts {counts:?}");
+        }
+
+        //Rotations (the O key) add tiles unless every tile is symmetric
+        let rotated = ExtractionOptions {
+            rotations: true,
+            ..ExtractionOptions::default()
+        };
+        let with_rotations =
+            WFCParameters::from_image_data_with_options(&data, 3, &rotated).unwrap();
+        assert!(with_rotations.wfc_tiles.len() > counts[2], "{input}");
+    }
+}
+
+#[test]
+fn tile_sizes_larger_than_the_input_are_rejected() {
+    let data = ImageData::from_pixels(&[0xff000000; 25], 5, 5);
+    let Err(error) = WFCParameters::from_image_data(&data, 8) else {
+        panic!("a tile size of 8 was accepted for a 5x5 input");
+    };
+    assert!(
+        error.contains("tile size 8") && error.contains("5x5"),
+        "{error}"
+    );
+    assert!(WFCParameters::from_image_data(&data, 0).is_err());
+    assert!(WFCParameters::from_image_data(&data, 5).is_ok());
+}
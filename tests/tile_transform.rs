@@ -0,0 +1,70 @@
+//Checks changing the tiles as they are sampled
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use image_data::ImageData;
+use wfc::{ExtractionOptions, Tile, WFCParameters};
+
+const GREEN: u32 = 0x0000ff00;
+
+fn zero_green(tile: &mut Tile) {
+    for pixel in tile.iter_mut() {
+        *pixel &= !GREEN;
+    }
+}
+
+//Columns that only differ in how much green they have
+fn green_stripes() -> ImageData {
+    let colors = [0xff000010, 0xff004010, 0xff008010, 0xff00c010];
+    let pixels: Vec<u32> = (0..16).map(|i| colors[i % 4]).collect();
+    ImageData::from_pixels(&pixels, 4, 4)
+}
+
+#[test]
+fn tiles_that_only_differ_in_green_become_one() {
+    let data = green_stripes();
+    let options = ExtractionOptions::default();
+    let plain = WFCParameters::from_image_data_with_options(&data, 2, &options).unwrap();
+    assert_eq!(plain.wfc_tiles.len(), 4);
+
+    let transformed =
+        WFCParameters::from_image_data_transformed(&data, 2, &options, zero_green).unwrap();
+    assert_eq!(transformed.wfc_tiles, vec![0xff000010]);
+    assert_eq!(transformed.wfc_frequency, vec![16]);
+
+    //Each pixel is a tile with hex
+    let hex = ExtractionOptions {
+        hex: true,
+        ..ExtractionOptions::default()
+    };
+    let transformed =
+        WFCParameters::from_image_data_transformed(&data, 1, &hex, zero_green).unwrap();
+    assert_eq!(transformed.wfc_tiles, vec![0xff000010]);
+}
+
+#[test]
+fn no_transform_is_the_same_as_none() {
+    let data = load_image("inputimage1.png");
+    let options = ExtractionOptions {
+        rotations: true,
+        rotation_weight: 50,
+        ..ExtractionOptions::default()
+    };
+    let plain = WFCParameters::from_image_data_with_options(&data, 3, &options).unwrap();
+    let mut calls = 0;
+    let transformed =
+        WFCParameters::from_image_data_transformed(&data, 3, &options, |_| calls += 1).unwrap();
+    assert_eq!(calls, data.width() * data.height());
+    assert_eq!(plain.wfc_tiles, transformed.wfc_tiles);
+    assert_eq!(plain.wfc_frequency, transformed.wfc_frequency);
+    assert!(plain.wfc_rules == transformed.wfc_rules);
+}
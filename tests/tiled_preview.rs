@@ -0,0 +1,42 @@
+//Checks the image that repeats an output to show its seams
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::ImageData;
+
+#[test]
+fn the_output_is_repeated_in_a_grid() {
+    let (w, h) = (3, 2);
+    let pixels: Vec<u32> = (0..(w * h) as u32).map(|i| 0xff000000 | i).collect();
+    let image = ImageData::from_pixels(&pixels, w, h);
+
+    let tiled = image.tiled(2, 2);
+    assert_eq!((tiled.width(), tiled.height()), (2 * w, 2 * h));
+    for y in 0..2 * h {
+        for x in 0..2 * w {
+            assert_eq!(
+                tiled.get_pixel(x, y),
+                image.get_pixel(x % w, y % h),
+                "({x}, {y})"
+            );
+        }
+    }
+
+    let tiled = image.tiled(3, 1);
+    assert_eq!((tiled.width(), tiled.height()), (3 * w, h));
+    let first_row: Vec<u32> = pixels[..w].repeat(3);
+    assert_eq!(&tiled.pixels()[..3 * w], first_row.as_slice());
+}
+
+#[test]
+fn saving_keeps_the_tiled_size() {
+    let image = ImageData::from_pixels(&[0xff0000ff, 0xff00ff00, 0xffff0000, 0xffffffff], 2, 2);
+    let path = std::env::temp_dir().join(format!("tiled_preview_{}.png", std::process::id()));
+    let path = path.to_str().unwrap();
+    image.tiled(2, 2).save_png(path).unwrap();
+    let loaded = ImageData::load_png(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!((loaded.width(), loaded.height()), (4, 4));
+    assert_eq!(loaded.pixels(), image.tiled(2, 2).pixels());
+}
@@ -0,0 +1,62 @@
+//Checks the time measured for collapsing and propagating
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_image;
+use std::time::Instant;
+use wfc::{Timings, WFCParameters};
+
+const INPUT: &str = "inputimage1.png";
+const SIZE: usize = 48;
+const MAX_ATTEMPTS: u64 = 16;
+
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn the_phases_add_up_to_the_total_time() {
+    let start = Instant::now();
+    let mut parameters = WFCParameters::from_image_data(&load_image(INPUT), 3).unwrap();
+    let extraction = start.elapsed();
+    parameters.wfc_measure_timings = true;
+    let masked = vec![false; SIZE * SIZE];
+    let mut timings = Timings::default();
+    for seed in 0..MAX_ATTEMPTS {
+        let (wfc_state, result) =
+            parameters.generate_state_masked_seeded(SIZE, SIZE, &masked, seed, None);
+        timings.add(wfc_state.timings());
+        if result.is_ok() {
+            break;
+        }
+    }
+    let total = start.elapsed();
+
+    //Propagating is part of collapsing
+    assert!(timings.propagate <= timings.collapse, "{timings:?}");
+    let measured = extraction + timings.collapse;
+    assert!(measured <= total, "{measured:?} of {total:?}");
+    //What is not measured is setting up the states and drawing the output,
+    //which is quick next to solving
+    assert!(
+        measured.as_secs_f64() > total.as_secs_f64() * 0.5,
+        "{measured:?} of {total:?}"
+    );
+    assert!(timings.propagation_reach > 0);
+}
+
+#[test]
+fn nothing_is_measured_unless_asked_for() {
+    //The timings are kept in the state so the parameters can be shared
+    //between threads
+    assert_sync::<WFCParameters>();
+    let parameters = WFCParameters::from_image_data(&load_image(INPUT), 3).unwrap();
+    let (wfc_state, _) = parameters.generate_state_masked_seeded(8, 8, &[false; 64], 0, None);
+    assert_eq!(wfc_state.timings(), Timings::default());
+}
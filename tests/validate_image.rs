@@ -0,0 +1,57 @@
+//Checks images against the rules they are supposed to follow
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::load_input;
+
+const SIZE: usize = 16;
+const SEED: u64 = 7;
+//Cell that is edited and a color that is not in any of the inputs
+const TAMPERED: (usize, usize) = (5, 9);
+const UNKNOWN_COLOR: u32 = 0x12345678;
+
+#[test]
+fn generated_images_follow_the_rules() {
+    for input in ["inputimage3.png", "inputimage4.png"] {
+        let wfc_parameters = load_input(input);
+        let image = wfc_parameters
+            .generate_grid_seeded(SIZE, SIZE, SEED)
+            .unwrap();
+        assert_eq!(wfc_parameters.validate_image(&image), vec![], "{input}");
+    }
+}
+
+#[test]
+fn edited_cells_are_reported() {
+    let wfc_parameters = load_input("inputimage3.png");
+    let mut image = wfc_parameters
+        .generate_grid_seeded(SIZE, SIZE, SEED)
+        .unwrap();
+    let (x, y) = TAMPERED;
+    image.pixels_mut()[x + y * SIZE] = UNKNOWN_COLOR;
+
+    let violations = wfc_parameters.validate_image(&image);
+    //Every pair the cell is in is broken, both from the cell and from each
+    //of its neighbors
+    let directions = wfc_parameters.wfc_rules.directions();
+    assert_eq!(violations.len(), 2 * directions);
+    for direction in 0..directions {
+        assert!(violations.contains(&(x, y, direction)), "{violations:?}");
+    }
+    for (violation_x, violation_y, direction) in violations {
+        let (dx, dy) = wfc_parameters.wfc_rules.offsets()[direction];
+        let adj = (
+            (violation_x as isize + dx) as usize,
+            (violation_y as isize + dy) as usize,
+        );
+        assert!((violation_x, violation_y) == TAMPERED || adj == TAMPERED);
+    }
+}
@@ -0,0 +1,71 @@
+//Checks that rules that can't be satisfied are found before generating
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+mod common;
+
+use common::{all_allowed, OFFSETS};
+use wfc::{Boundary, Issue, WFCParameters};
+
+const TILES: usize = 3;
+//Directions of (1, 0) and (-1, 0)
+const RIGHT: usize = 1;
+const LEFT: usize = 3;
+
+//Nothing is allowed to the right of tile 1
+fn dead_end() -> WFCParameters {
+    let mut rules = all_allowed(TILES, &OFFSETS);
+    for id2 in 0..TILES {
+        rules.remove_rule(RIGHT, 1, id2);
+        rules.remove_rule(LEFT, id2, 1);
+    }
+    WFCParameters::from_parts((0..TILES as u32).collect(), rules, vec![1; TILES], 1)
+}
+
+#[test]
+fn a_tile_with_no_right_neighbor_is_a_dead_end() {
+    let issues = dead_end().validate().unwrap_err();
+    assert_eq!(
+        issues,
+        [Issue::DeadEnd {
+            tile: 1,
+            direction: RIGHT
+        }]
+    );
+    let message = issues[0].to_string();
+    assert!(
+        message.contains("tile 1") && message.contains("direction 1"),
+        "{message}"
+    );
+}
+
+#[test]
+fn dead_ends_are_fine_at_finite_edges() {
+    let parameters = WFCParameters {
+        wfc_boundary: Boundary::Finite,
+        ..dead_end()
+    };
+    assert_eq!(parameters.validate(), Ok(()));
+    let parameters = WFCParameters {
+        wfc_boundary: Boundary::WrapY,
+        ..dead_end()
+    };
+    assert_eq!(parameters.validate(), Ok(()));
+}
+
+#[test]
+fn rules_that_allow_everything_are_valid() {
+    let parameters = WFCParameters::from_parts(
+        (0..TILES as u32).collect(),
+        all_allowed(TILES, &OFFSETS),
+        vec![1; TILES],
+        1,
+    );
+    assert_eq!(parameters.validate(), Ok(()));
+}
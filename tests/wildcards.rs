@@ -0,0 +1,46 @@
+//Checks that fully transparent input pixels match any color
+#![allow(dead_code)]
+
+#[path = "../src/cell_queue.rs"]
+mod cell_queue;
+#[path = "../src/image_data.rs"]
+mod image_data;
+#[path = "../src/wfc/mod.rs"]
+mod wfc;
+
+use wfc::{tiles_match, Tile};
+
+const TILE_SZ: isize = 3;
+const RED: u32 = 0xff0000ff;
+const GREEN: u32 = 0xff00ff00;
+const BLUE: u32 = 0xffff0000;
+const TRANSPARENT: u32 = 0x00ffffff;
+
+//A red tile with a different center pixel
+fn with_center(center: u32) -> Tile {
+    let mut tile = vec![RED; (TILE_SZ * TILE_SZ) as usize];
+    tile[4] = center;
+    tile
+}
+
+#[test]
+fn a_transparent_center_matches_any_center() {
+    let (wildcard, green, blue) = (
+        with_center(TRANSPARENT),
+        with_center(GREEN),
+        with_center(BLUE),
+    );
+    assert!(tiles_match(&wildcard, &green, 0, 0, TILE_SZ));
+    assert!(tiles_match(&wildcard, &blue, 0, 0, TILE_SZ));
+    assert!(tiles_match(&green, &wildcard, 0, 0, TILE_SZ));
+    //The two tiles still don't match each other
+    assert!(!tiles_match(&green, &blue, 0, 0, TILE_SZ));
+    //Only alpha 0 is a wildcard
+    assert!(!tiles_match(
+        &with_center(0x80ffffff),
+        &green,
+        0,
+        0,
+        TILE_SZ
+    ));
+}
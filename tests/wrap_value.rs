@@ -0,0 +1,42 @@
+//Checks wrapping values of any size into a range
+#[path = "../src/image_data.rs"]
+#[allow(dead_code)]
+mod image_data;
+
+use image_data::wrap_value;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const SAMPLES: usize = 100_000;
+
+#[test]
+fn wrapping_matches_rem_euclid() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..SAMPLES {
+        let max = rng.gen_range(1..=1_000_000usize);
+        let v = rng.gen_range(-1_000_000_000isize..=1_000_000_000);
+        assert_eq!(
+            wrap_value(v, max) as i64,
+            (v as i64).rem_euclid(max as i64),
+            "{v} wrapped into 0..{max}"
+        );
+    }
+}
+
+#[test]
+fn multiples_of_the_size_wrap_to_zero() {
+    for max in [1, 2, 3, 7, 64, 100] {
+        for multiple in -3..=3 {
+            assert_eq!(wrap_value(multiple * max as isize, max), 0);
+        }
+        assert_eq!(wrap_value(-1, max), max - 1);
+        assert_eq!(wrap_value(-(max as isize) - 1, max), max - 1);
+    }
+    assert_eq!(
+        wrap_value(isize::MIN, 3),
+        (isize::MIN as i64).rem_euclid(3) as usize
+    );
+    assert_eq!(
+        wrap_value(isize::MAX, 3),
+        (isize::MAX as i64).rem_euclid(3) as usize
+    );
+}